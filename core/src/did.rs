@@ -0,0 +1,7 @@
+mod document;
+mod formatter_one_or_many;
+pub mod multicodec;
+mod url;
+
+pub use document::*;
+pub use url::*;
@@ -0,0 +1,826 @@
+use super::formatter_one_or_many;
+use super::multicodec::{decode_multikey, KeyType};
+use super::{Did, DidUrl};
+use crate::error::{DocumentError, DocumentErrorKind};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use signature::suite::VerificationRelation;
+use std::collections::HashMap;
+
+pub const CONTEXT_DID_V1: &str = "https://www.w3.org/ns/did/v1";
+
+/// A DID document as defined by DID Core.
+/// Properties that are not modelled explicitly are preserved in `property_set`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DidDocument {
+    #[serde(rename = "@context")]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[serde(with = "formatter_one_or_many")]
+    pub context: Vec<Value>,
+
+    pub id: String,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[serde(with = "formatter_one_or_many")]
+    pub controller: Vec<String>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub also_known_as: Vec<String>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub verification_method: Vec<VerificationMethod>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub authentication: Vec<VerificationRelationship>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub assertion_method: Vec<VerificationRelationship>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub key_agreement: Vec<VerificationRelationship>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub capability_invocation: Vec<VerificationRelationship>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub capability_delegation: Vec<VerificationRelationship>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub service: Vec<Service>,
+
+    #[serde(flatten)]
+    pub property_set: HashMap<String, Value>,
+}
+
+/// An entry of a verification relationship, which either references a verification method by its id or embeds it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum VerificationRelationship {
+    Reference(String),
+    Embedded(Box<VerificationMethod>),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VerificationMethod {
+    pub id: String,
+
+    #[serde(rename = "type")]
+    pub method_type: VerificationMethodType,
+
+    pub controller: String,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub public_key_multibase: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub public_key_base58: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub public_key_jwk: Option<Jwk>,
+
+    #[serde(flatten)]
+    pub property_set: HashMap<String, Value>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub enum VerificationMethodType {
+    Ed25519VerificationKey2018,
+    Ed25519VerificationKey2020,
+    Multikey,
+    JsonWebKey2020,
+    Other(String),
+}
+
+impl From<String> for VerificationMethodType {
+    fn from(s: String) -> Self {
+        match s.as_str() {
+            "Ed25519VerificationKey2018" => VerificationMethodType::Ed25519VerificationKey2018,
+            "Ed25519VerificationKey2020" => VerificationMethodType::Ed25519VerificationKey2020,
+            "Multikey" => VerificationMethodType::Multikey,
+            "JsonWebKey2020" => VerificationMethodType::JsonWebKey2020,
+            _ => VerificationMethodType::Other(s),
+        }
+    }
+}
+
+impl From<VerificationMethodType> for String {
+    fn from(t: VerificationMethodType) -> Self {
+        t.to_string()
+    }
+}
+
+impl std::fmt::Display for VerificationMethodType {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            VerificationMethodType::Other(s) => write!(f, "{s}"),
+            t => write!(f, "{:?}", t),
+        }
+    }
+}
+
+/// A JSON Web Key as used by the `publicKeyJwk` property.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Jwk {
+    pub kty: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub crv: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub x: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub y: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub d: Option<String>,
+    #[serde(rename = "use")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key_use: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kid: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alg: Option<String>,
+}
+
+impl From<signature::key::Ed25519Jwk> for Jwk {
+    fn from(jwk: signature::key::Ed25519Jwk) -> Self {
+        return Self {
+            kty: jwk.kty,
+            crv: Some(jwk.crv),
+            x: Some(jwk.x),
+            d: jwk.d,
+            ..Default::default()
+        };
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Service {
+    pub id: String,
+
+    #[serde(rename = "type")]
+    #[serde(with = "formatter_one_or_many")]
+    pub service_type: Vec<String>,
+
+    /// A URI, a map or a set composed of URIs and maps.
+    pub service_endpoint: Value,
+
+    #[serde(flatten)]
+    pub property_set: HashMap<String, Value>,
+}
+
+impl DidDocument {
+    pub fn new(id: impl Into<String>) -> DidDocument {
+        DidDocument {
+            context: vec![Value::from(CONTEXT_DID_V1)],
+            id: id.into(),
+            controller: vec![],
+            also_known_as: vec![],
+            verification_method: vec![],
+            authentication: vec![],
+            assertion_method: vec![],
+            key_agreement: vec![],
+            capability_invocation: vec![],
+            capability_delegation: vec![],
+            service: vec![],
+            property_set: HashMap::new(),
+        }
+    }
+
+    /// Parse a JSON DID document and validate it against the DID Core rules.
+    pub fn deserialize(contents: Value) -> Result<DidDocument, DocumentError> {
+        let doc: DidDocument = serde_json::from_value(contents)
+            .map_err(|e| DocumentError::new(e.to_string(), DocumentErrorKind::InvalidDocument))?;
+        doc.validate()?;
+        Ok(doc)
+    }
+
+    pub fn serialize(&self) -> Value {
+        return serde_json::to_value(self).unwrap();
+    }
+
+    pub fn relationship(&self, relation: VerificationRelation) -> &Vec<VerificationRelationship> {
+        match relation {
+            VerificationRelation::AssertionMethod => &self.assertion_method,
+            VerificationRelation::Authentication => &self.authentication,
+            VerificationRelation::CapabilityInvocation => &self.capability_invocation,
+            VerificationRelation::CapabilityDelegation => &self.capability_delegation,
+        }
+    }
+
+    /// Whether the verification method `id` is referenced or embedded in the `relation` relationship of this document.
+    pub fn has_relationship(&self, id: &str, relation: VerificationRelation) -> bool {
+        let id = self.absolute_id(id);
+        return self.relationship(relation).iter().any(|r| match r {
+            VerificationRelationship::Reference(reference) => self.absolute_id(reference) == id,
+            VerificationRelationship::Embedded(vm) => self.absolute_id(&vm.id) == id,
+        });
+    }
+
+    /// Expand a relative DID URL such as `#key-1` against the id of this document.
+    pub fn absolute_id(&self, id: &str) -> String {
+        match id.starts_with('#') {
+            true => format!("{}{id}", self.id),
+            false => id.to_string(),
+        }
+    }
+
+    /// All verification methods of the document, those listed under `verificationMethod` followed by the embedded ones.
+    pub fn verification_methods(&self) -> impl Iterator<Item = &VerificationMethod> {
+        return self
+            .verification_method
+            .iter()
+            .chain(self.embedded_verification_methods());
+    }
+
+    /// Find a verification method by id, whether it is listed under `verificationMethod` or embedded in a relationship.
+    pub fn find_verification_method(&self, id: &str) -> Option<&VerificationMethod> {
+        let id = self.absolute_id(id);
+        return self
+            .verification_methods()
+            .find(|vm| self.absolute_id(&vm.id) == id);
+    }
+
+    /// Replace the verification method `id` by `replacement` wherever it is listed or embedded,
+    /// references to `id` are pointed at the replacement. This is how keys are rotated in an update.
+    pub fn rotate_verification_method(
+        &mut self,
+        id: &str,
+        replacement: VerificationMethod,
+    ) -> Result<(), DocumentError> {
+        if self.find_verification_method(id).is_none() {
+            return Err(DocumentError::new(
+                format!("verification method {id} not found"),
+                DocumentErrorKind::InvalidReference,
+            ));
+        }
+
+        let doc_id = self.id.clone();
+        let absolute = |id: &str| match id.starts_with('#') {
+            true => format!("{doc_id}{id}"),
+            false => id.to_string(),
+        };
+        let target = absolute(id);
+
+        for vm in self.verification_method.iter_mut() {
+            if absolute(&vm.id) == target {
+                *vm = replacement.clone();
+            }
+        }
+        for relationship in [
+            &mut self.authentication,
+            &mut self.assertion_method,
+            &mut self.key_agreement,
+            &mut self.capability_invocation,
+            &mut self.capability_delegation,
+        ] {
+            for entry in relationship.iter_mut() {
+                let matches = match entry {
+                    VerificationRelationship::Reference(reference) => absolute(reference) == target,
+                    VerificationRelationship::Embedded(vm) => absolute(&vm.id) == target,
+                };
+                if !matches {
+                    continue;
+                }
+                *entry = match entry {
+                    VerificationRelationship::Reference(_) => {
+                        VerificationRelationship::Reference(replacement.id.clone())
+                    }
+                    VerificationRelationship::Embedded(_) => {
+                        VerificationRelationship::Embedded(Box::new(replacement.clone()))
+                    }
+                };
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Add `service`, replacing the service with the same id if there is one.
+    pub fn set_service(&mut self, service: Service) {
+        let id = self.absolute_id(&service.id);
+        match self
+            .service
+            .iter()
+            .position(|s| self.absolute_id(&s.id) == id)
+        {
+            Some(i) => self.service[i] = service,
+            None => self.service.push(service),
+        }
+    }
+
+    /// Remove the service with the given id, returning it if it was present.
+    pub fn remove_service(&mut self, id: &str) -> Option<Service> {
+        let id = self.absolute_id(id);
+        let i = self
+            .service
+            .iter()
+            .position(|s| self.absolute_id(&s.id) == id)?;
+        return Some(self.service.remove(i));
+    }
+
+    fn relationships(&self) -> impl Iterator<Item = &VerificationRelationship> {
+        self.authentication
+            .iter()
+            .chain(self.assertion_method.iter())
+            .chain(self.key_agreement.iter())
+            .chain(self.capability_invocation.iter())
+            .chain(self.capability_delegation.iter())
+    }
+
+    fn embedded_verification_methods(&self) -> impl Iterator<Item = &VerificationMethod> {
+        self.relationships().filter_map(|r| match r {
+            VerificationRelationship::Embedded(vm) => Some(vm.as_ref()),
+            VerificationRelationship::Reference(_) => None,
+        })
+    }
+
+    /// Check the document against the DID Core rules:
+    /// identifiers must be well-formed and unique, references must point to verification methods of this document
+    /// and key material must match the verification method type.
+    pub fn validate(&self) -> Result<(), DocumentError> {
+        Did::parse(&self.id)?;
+
+        for controller in &self.controller {
+            Did::parse(controller)?;
+        }
+
+        for aka in &self.also_known_as {
+            if !is_uri(aka) {
+                return Err(DocumentError::new(
+                    format!("alsoKnownAs entry {aka} is not a URI"),
+                    DocumentErrorKind::InvalidDocument,
+                ));
+            }
+        }
+
+        let mut seen: HashMap<String, &VerificationMethod> = HashMap::new();
+        for vm in self
+            .verification_method
+            .iter()
+            .chain(self.embedded_verification_methods())
+        {
+            vm.validate()?;
+            let id = self.absolute_id(&vm.id);
+            match seen.get(&id) {
+                // an embedded method may be repeated across relationships as long as it is identical
+                Some(existing) if *existing != vm => {
+                    return Err(DocumentError::new(
+                        format!("verification method id {id} is not unique"),
+                        DocumentErrorKind::DuplicateId,
+                    ))
+                }
+                _ => {
+                    seen.insert(id, vm);
+                }
+            }
+        }
+
+        for relationship in self.relationships() {
+            if let VerificationRelationship::Reference(reference) = relationship {
+                let id = self.absolute_id(reference);
+                DidUrl::parse(&id)?;
+                // methods of other DID documents cannot be checked without resolving them
+                if id.starts_with(&format!("{}#", self.id)) && !seen.contains_key(&id) {
+                    return Err(DocumentError::new(
+                        format!("reference {reference} does not match any verification method"),
+                        DocumentErrorKind::InvalidReference,
+                    ));
+                }
+            }
+        }
+
+        let mut service_ids: Vec<String> = vec![];
+        for service in &self.service {
+            let id = self.absolute_id(&service.id);
+            if !is_uri(&id) {
+                return Err(DocumentError::new(
+                    format!("service id {} is not a URI", service.id),
+                    DocumentErrorKind::InvalidService,
+                ));
+            }
+            if service_ids.contains(&id) || seen.contains_key(&id) {
+                return Err(DocumentError::new(
+                    format!("service id {id} is not unique"),
+                    DocumentErrorKind::DuplicateId,
+                ));
+            }
+            if service.service_type.is_empty() || !is_service_endpoint(&service.service_endpoint) {
+                return Err(DocumentError::new(
+                    format!("service {id} must have a type and a service endpoint"),
+                    DocumentErrorKind::InvalidService,
+                ));
+            }
+            service_ids.push(id);
+        }
+
+        Ok(())
+    }
+}
+
+impl VerificationMethod {
+    /// Check that the verification method is well-formed and that its key material matches its type.
+    pub fn validate(&self) -> Result<(), DocumentError> {
+        if !self.id.starts_with('#') {
+            DidUrl::parse(&self.id)?;
+        }
+        Did::parse(&self.controller)?;
+
+        let material = [
+            self.public_key_multibase.is_some(),
+            self.public_key_base58.is_some(),
+            self.public_key_jwk.is_some(),
+        ];
+        if material.iter().filter(|present| **present).count() > 1 {
+            return Err(self.key_error("must not express more than one public key"));
+        }
+
+        match self.method_type {
+            VerificationMethodType::Ed25519VerificationKey2018 => {
+                let encoded = self
+                    .public_key_base58
+                    .as_ref()
+                    .ok_or_else(|| self.key_error("requires publicKeyBase58"))?;
+                let (_, key) = multibase::decode(format!("z{encoded}"))
+                    .map_err(|e| self.key_error(&e.to_string()))?;
+                if key.len() != 32 {
+                    return Err(self.key_error("Ed25519 key must be 32 bytes"));
+                }
+            }
+            VerificationMethodType::Ed25519VerificationKey2020 => {
+                let (key_type, _) = decode_multikey(self.multibase()?)?;
+                if key_type != KeyType::Ed25519 {
+                    return Err(self.key_error("publicKeyMultibase is not an Ed25519 key"));
+                }
+            }
+            VerificationMethodType::Multikey => {
+                decode_multikey(self.multibase()?)?;
+            }
+            VerificationMethodType::JsonWebKey2020 => {
+                let jwk = self
+                    .public_key_jwk
+                    .as_ref()
+                    .ok_or_else(|| self.key_error("requires publicKeyJwk"))?;
+                jwk.validate_public()?;
+            }
+            VerificationMethodType::Other(_) => {}
+        }
+
+        Ok(())
+    }
+
+    /// The type of the public key, if its key material can be decoded.
+    pub fn key_type(&self) -> Option<KeyType> {
+        if let Some(multibase) = &self.public_key_multibase {
+            return decode_multikey(multibase)
+                .ok()
+                .map(|(key_type, _)| key_type);
+        }
+        if self.public_key_base58.is_some() {
+            return Some(KeyType::Ed25519);
+        }
+        match self
+            .public_key_jwk
+            .as_ref()
+            .and_then(|jwk| jwk.crv.as_deref())
+        {
+            Some("Ed25519") => Some(KeyType::Ed25519),
+            Some("X25519") => Some(KeyType::X25519),
+            Some("secp256k1") => Some(KeyType::Secp256k1),
+            Some("P-256") => Some(KeyType::P256),
+            Some("P-384") => Some(KeyType::P384),
+            _ => None,
+        }
+    }
+
+    /// Decode the public key of the verification method together with its type.
+    pub fn public_key(&self) -> Result<(KeyType, Vec<u8>), DocumentError> {
+        if let Some(multibase) = &self.public_key_multibase {
+            return decode_multikey(multibase);
+        }
+        if let Some(encoded) = &self.public_key_base58 {
+            let (_, key) = multibase::decode(format!("z{encoded}"))
+                .map_err(|e| self.key_error(&e.to_string()))?;
+            return Ok((KeyType::Ed25519, key));
+        }
+
+        let key_type = self
+            .key_type()
+            .ok_or_else(|| self.key_error("has no supported public key"))?;
+        let key = self
+            .public_key_jwk
+            .as_ref()
+            .and_then(|jwk| jwk.x.as_ref())
+            .and_then(|x| base64::decode_config(x, base64::URL_SAFE_NO_PAD).ok())
+            .ok_or_else(|| self.key_error("publicKeyJwk x is not base64url encoded"))?;
+        return match key_type {
+            KeyType::Ed25519 | KeyType::X25519 => Ok((key_type, key)),
+            _ => Err(self.key_error("only OKP keys can be decoded from publicKeyJwk")),
+        };
+    }
+
+    fn multibase(&self) -> Result<&str, DocumentError> {
+        return self
+            .public_key_multibase
+            .as_deref()
+            .ok_or_else(|| self.key_error("requires publicKeyMultibase"));
+    }
+
+    fn key_error(&self, message: &str) -> DocumentError {
+        DocumentError::new(
+            format!("{} {}: {message}", self.method_type, self.id),
+            DocumentErrorKind::InvalidKeyMaterial,
+        )
+    }
+}
+
+impl Jwk {
+    /// Check that the key is a well-formed public key, private key material must never be published.
+    pub fn validate_public(&self) -> Result<(), DocumentError> {
+        if self.d.is_some() {
+            return Err(DocumentError::new(
+                "publicKeyJwk must not contain private key material",
+                DocumentErrorKind::InvalidKeyMaterial,
+            ));
+        }
+
+        let coordinate = |name: &str, value: &Option<String>, length: usize| {
+            let decoded = value
+                .as_ref()
+                .and_then(|v| base64::decode_config(v, base64::URL_SAFE_NO_PAD).ok());
+            match decoded {
+                Some(bytes) if bytes.len() == length => Ok(()),
+                _ => Err(DocumentError::new(
+                    format!("publicKeyJwk {name} must be a base64url encoded {length} byte value"),
+                    DocumentErrorKind::InvalidKeyMaterial,
+                )),
+            }
+        };
+
+        match (self.kty.as_str(), self.crv.as_deref()) {
+            ("OKP", Some("Ed25519")) | ("OKP", Some("X25519")) => coordinate("x", &self.x, 32),
+            ("EC", Some("P-256")) | ("EC", Some("secp256k1")) => {
+                coordinate("x", &self.x, 32)?;
+                coordinate("y", &self.y, 32)
+            }
+            ("EC", Some("P-384")) => {
+                coordinate("x", &self.x, 48)?;
+                coordinate("y", &self.y, 48)
+            }
+            (kty, crv) => Err(DocumentError::new(
+                format!("unsupported publicKeyJwk kty {kty} with crv {crv:?}"),
+                DocumentErrorKind::InvalidKeyMaterial,
+            )),
+        }
+    }
+}
+
+fn is_uri(s: &str) -> bool {
+    match s.split_once(':') {
+        Some((scheme, rest)) => {
+            !rest.is_empty()
+                && matches!(scheme.chars().next(), Some(c) if c.is_ascii_alphabetic())
+                && scheme
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || "+-.".contains(c))
+        }
+        None => false,
+    }
+}
+
+fn is_service_endpoint(endpoint: &Value) -> bool {
+    match endpoint {
+        Value::String(uri) => is_uri(uri),
+        Value::Object(_) => true,
+        Value::Array(items) => !items.is_empty() && items.iter().all(is_service_endpoint),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DidDocument, Jwk, VerificationMethodType, VerificationRelationship};
+    use crate::did::multicodec::KeyType;
+    use crate::error::DocumentErrorKind;
+    use serde_json::json;
+    use signature::suite::VerificationRelation;
+
+    const DID: &str = "did:knox:z6MkfFmsob7fC3MmqU1JVfdBnMbnAw7xm1mrEtPvAoojLcRh";
+
+    fn create_did_doc() -> serde_json::Value {
+        json!({
+            "@context": ["https://www.w3.org/ns/did/v1", "https://w3id.org/security/suites/ed25519-2020/v1"],
+            "id": DID,
+            "alsoKnownAs": ["https://example.com/issuer"],
+            "verificationMethod": [{
+                "id": format!("{DID}#key-1"),
+                "type": "Ed25519VerificationKey2020",
+                "controller": DID,
+                "publicKeyMultibase": "z6MkfFmsob7fC3MmqU1JVfdBnMbnAw7xm1mrEtPvAoojLcRh"
+            }, {
+                "id": "#key-2",
+                "type": "JsonWebKey2020",
+                "controller": DID,
+                "publicKeyJwk": {"kty": "OKP", "crv": "Ed25519", "x": "VCpo2LMLhn6iWku8MKvSLg2ZAoC-nlOyPVQaO3FxVeQ"}
+            }],
+            "authentication": [format!("{DID}#key-1")],
+            "assertionMethod": ["#key-2"],
+            "capabilityInvocation": [{
+                "id": format!("{DID}#key-3"),
+                "type": "Multikey",
+                "controller": DID,
+                "publicKeyMultibase": "zQ3shokFTS3brHcDQrn82RUDfCZESWL1ZdCEJwekUDPQiYBme"
+            }],
+            "service": [{
+                "id": "#linked-domain",
+                "type": "LinkedDomains",
+                "serviceEndpoint": "https://example.com"
+            }]
+        })
+    }
+
+    fn modify(f: impl FnOnce(&mut serde_json::Value)) -> serde_json::Value {
+        let mut doc = create_did_doc();
+        f(&mut doc);
+        doc
+    }
+
+    #[rstest::rstest]
+    #[case::valid(create_did_doc(), None)]
+    #[case::invalid_id(modify(|d| d["id"] = json!("did:Knox:123")), Some(DocumentErrorKind::InvalidDid))]
+    #[case::missing_id(modify(|d| { d.as_object_mut().unwrap().remove("id"); }), Some(DocumentErrorKind::InvalidDocument))]
+    #[case::invalid_controller(modify(|d| d["controller"] = json!("example.com")), Some(DocumentErrorKind::InvalidDid))]
+    #[case::invalid_also_known_as(modify(|d| d["alsoKnownAs"] = json!(["issuer"])), Some(DocumentErrorKind::InvalidDocument))]
+    #[case::duplicate_id(
+        modify(|d| d["verificationMethod"][1]["id"] = json!("#key-1")),
+        Some(DocumentErrorKind::DuplicateId)
+    )]
+    #[case::duplicate_service_id(
+        modify(|d| d["service"][0]["id"] = json!("#key-1")),
+        Some(DocumentErrorKind::DuplicateId)
+    )]
+    #[case::dangling_reference(
+        modify(|d| d["authentication"] = json!(["#key-9"])),
+        Some(DocumentErrorKind::InvalidReference)
+    )]
+    #[case::external_reference(
+        modify(|d| d["authentication"] = json!(["did:example:123#key-1"])),
+        None
+    )]
+    #[case::wrong_multicodec(
+        modify(|d| d["verificationMethod"][0]["publicKeyMultibase"] = json!("zQ3shokFTS3brHcDQrn82RUDfCZESWL1ZdCEJwekUDPQiYBme")),
+        Some(DocumentErrorKind::InvalidKeyMaterial)
+    )]
+    #[case::missing_key_material(
+        modify(|d| { d["verificationMethod"][0].as_object_mut().unwrap().remove("publicKeyMultibase"); }),
+        Some(DocumentErrorKind::InvalidKeyMaterial)
+    )]
+    #[case::private_jwk(
+        modify(|d| d["verificationMethod"][1]["publicKeyJwk"]["d"] = json!("secret")),
+        Some(DocumentErrorKind::InvalidKeyMaterial)
+    )]
+    #[case::short_jwk(
+        modify(|d| d["verificationMethod"][1]["publicKeyJwk"]["x"] = json!("VCpo2LMLhn6iWku8")),
+        Some(DocumentErrorKind::InvalidKeyMaterial)
+    )]
+    #[case::ed25519_2018(
+        modify(|d| d["verificationMethod"][0] = json!({
+            "id": format!("{DID}#key-1"),
+            "type": "Ed25519VerificationKey2018",
+            "controller": DID,
+            "publicKeyBase58": "H3C2AVvLMv6gmMNam3uVAjZpfkcJCwDwnZn6z3wXmqPV"
+        })),
+        None
+    )]
+    #[case::invalid_service_endpoint(
+        modify(|d| d["service"][0]["serviceEndpoint"] = json!(42)),
+        Some(DocumentErrorKind::InvalidService)
+    )]
+    fn test_deserialize(
+        #[case] doc: serde_json::Value,
+        #[case] expect_error_kind: Option<DocumentErrorKind>,
+    ) {
+        let res = DidDocument::deserialize(doc);
+        match res.err() {
+            Some(e) => assert_eq!(Some(e.kind), expect_error_kind, "{}", e),
+            None => assert!(expect_error_kind.is_none()),
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let doc = DidDocument::deserialize(create_did_doc()).unwrap();
+        assert_eq!(
+            doc.verification_method[1].method_type,
+            VerificationMethodType::JsonWebKey2020
+        );
+        assert!(matches!(
+            doc.capability_invocation[0],
+            VerificationRelationship::Embedded(_)
+        ));
+        assert_json_diff::assert_json_eq!(doc.serialize(), create_did_doc());
+    }
+
+    #[test]
+    fn test_find_verification_method() {
+        let doc = DidDocument::deserialize(create_did_doc()).unwrap();
+        assert!(doc.find_verification_method("#key-1").is_some());
+        assert!(doc
+            .find_verification_method(&format!("{DID}#key-2"))
+            .is_some());
+        assert!(doc.find_verification_method("#key-3").is_some());
+        assert!(doc.find_verification_method("#key-4").is_none());
+    }
+
+    #[test]
+    fn test_has_relationship() {
+        let doc = DidDocument::deserialize(create_did_doc()).unwrap();
+        assert!(doc.has_relationship("#key-1", VerificationRelation::Authentication));
+        assert!(doc.has_relationship(
+            &format!("{DID}#key-2"),
+            VerificationRelation::AssertionMethod
+        ));
+        assert!(doc.has_relationship("#key-3", VerificationRelation::CapabilityInvocation));
+        assert!(!doc.has_relationship("#key-1", VerificationRelation::CapabilityInvocation));
+    }
+
+    #[rstest::rstest]
+    #[case::multibase("#key-1", Some(KeyType::Ed25519))]
+    #[case::jwk("#key-2", Some(KeyType::Ed25519))]
+    #[case::secp256k1_multibase("#key-3", Some(KeyType::Secp256k1))]
+    #[case::base58("#key-4", Some(KeyType::Ed25519))]
+    #[case::no_key_material("#key-5", None)]
+    fn test_public_key(#[case] id: &str, #[case] expect_key_type: Option<KeyType>) {
+        let mut doc = DidDocument::deserialize(create_did_doc()).unwrap();
+        let mut base58 = doc.verification_method[0].clone();
+        base58.id = "#key-4".to_string();
+        base58.public_key_multibase = None;
+        base58.public_key_base58 = Some("H3C2AVvLMv6gmMNam3uVAjZpfkcJCwDwnZn6z3wXmqPV".to_string());
+        let mut no_key_material = base58.clone();
+        no_key_material.id = "#key-5".to_string();
+        no_key_material.public_key_base58 = None;
+        doc.verification_method.extend([base58, no_key_material]);
+
+        let res = doc.find_verification_method(id).unwrap().public_key();
+        match expect_key_type {
+            Some(key_type) => {
+                let (actual, key) = res.unwrap();
+                assert_eq!(actual, key_type);
+                assert_eq!(key.len(), key_type.key_length());
+            }
+            None => assert_eq!(res.unwrap_err().kind, DocumentErrorKind::InvalidKeyMaterial),
+        }
+    }
+
+    #[rstest::rstest]
+    #[case::listed_absolute_id("#key-1")]
+    #[case::listed_relative_id("#key-2")]
+    #[case::embedded("#key-3")]
+    fn test_rotate_verification_method(#[case] id: &str) {
+        let mut doc = DidDocument::deserialize(create_did_doc()).unwrap();
+        let mut replacement = doc.find_verification_method(id).unwrap().clone();
+        replacement.id = format!("{DID}#key-4");
+        replacement.method_type = VerificationMethodType::Multikey;
+        replacement.public_key_jwk = None;
+        replacement.public_key_multibase =
+            Some("z6MkiTBz1ymuepAQ4HEHYSF1H8quG5GLVVQR3djdX3mDooWp".to_string());
+
+        doc.rotate_verification_method(id, replacement.clone())
+            .unwrap();
+        assert!(doc.validate().is_ok());
+        assert!(doc.find_verification_method(id).is_none());
+        assert_eq!(doc.find_verification_method("#key-4"), Some(&replacement));
+
+        let res = doc.rotate_verification_method("#key-9", replacement);
+        assert_eq!(res.unwrap_err().kind, DocumentErrorKind::InvalidReference);
+    }
+
+    #[test]
+    fn test_services() {
+        let mut doc = DidDocument::deserialize(create_did_doc()).unwrap();
+        let mut service = doc.service[0].clone();
+        service.service_endpoint = json!("https://example.org");
+        doc.set_service(service.clone());
+        assert_eq!(doc.service, vec![service.clone()]);
+
+        service.id = "#messaging".to_string();
+        doc.set_service(service);
+        assert_eq!(doc.service.len(), 2);
+
+        assert!(doc
+            .remove_service(&format!("{DID}#linked-domain"))
+            .is_some());
+        assert!(doc.remove_service("#linked-domain").is_none());
+        assert_eq!(doc.service.len(), 1);
+    }
+
+    #[test]
+    fn test_jwk_from_signer() {
+        let signer = signature::signer::Ed25519DidSigner::new();
+        assert!(Jwk::from(signer.public_key_jwk()).validate_public().is_ok());
+
+        let res = Jwk::from(signer.private_key_jwk()).validate_public();
+        assert_eq!(res.unwrap_err().kind, DocumentErrorKind::InvalidKeyMaterial);
+    }
+}
@@ -0,0 +1,86 @@
+use crate::error::{DocumentError, DocumentErrorKind};
+
+/// Public key types that can be identified by their multicodec prefix.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum KeyType {
+    Ed25519,
+    X25519,
+    Secp256k1,
+    P256,
+    P384,
+    Bls12381G1,
+    Bls12381G2,
+}
+
+pub const KEY_TYPES: [KeyType; 7] = [
+    KeyType::Ed25519,
+    KeyType::X25519,
+    KeyType::Secp256k1,
+    KeyType::P256,
+    KeyType::P384,
+    KeyType::Bls12381G1,
+    KeyType::Bls12381G2,
+];
+
+impl KeyType {
+    /// The unsigned varint encoding of the multicodec code of the public key.
+    pub fn prefix(&self) -> [u8; 2] {
+        match self {
+            KeyType::Ed25519 => [0xed, 0x01],
+            KeyType::X25519 => [0xec, 0x01],
+            KeyType::Secp256k1 => [0xe7, 0x01],
+            KeyType::P256 => [0x80, 0x24],
+            KeyType::P384 => [0x81, 0x24],
+            KeyType::Bls12381G1 => [0xea, 0x01],
+            KeyType::Bls12381G2 => [0xeb, 0x01],
+        }
+    }
+
+    /// Length of the public key in bytes, elliptic curve points are compressed.
+    pub fn key_length(&self) -> usize {
+        match self {
+            KeyType::Ed25519 | KeyType::X25519 => 32,
+            KeyType::Secp256k1 | KeyType::P256 => 33,
+            KeyType::P384 => 49,
+            KeyType::Bls12381G1 => 48,
+            KeyType::Bls12381G2 => 96,
+        }
+    }
+}
+
+/// Encode a public key as a base58btc multibase string with its multicodec prefix, e.g. `z6Mk…` for Ed25519.
+pub fn encode_multikey(key_type: KeyType, public_key: &[u8]) -> String {
+    let mut bytes = key_type.prefix().to_vec();
+    bytes.extend_from_slice(public_key);
+    return multibase::encode(multibase::Base::Base58Btc, bytes);
+}
+
+/// Decode a multibase encoded public key with a multicodec prefix.
+pub fn decode_multikey(encoded: &str) -> Result<(KeyType, Vec<u8>), DocumentError> {
+    let (_, bytes) = multibase::decode(encoded)
+        .map_err(|e| DocumentError::new(e.to_string(), DocumentErrorKind::InvalidKeyMaterial))?;
+
+    let key_type = KEY_TYPES
+        .into_iter()
+        .find(|key_type| bytes.starts_with(&key_type.prefix()))
+        .ok_or_else(|| {
+            DocumentError::new(
+                format!("{encoded} has an unsupported multicodec prefix"),
+                DocumentErrorKind::InvalidKeyMaterial,
+            )
+        })?;
+
+    let key = bytes[2..].to_vec();
+    if key.len() != key_type.key_length() {
+        return Err(DocumentError::new(
+            format!(
+                "{key_type:?} key must be {} bytes, got {}",
+                key_type.key_length(),
+                key.len()
+            ),
+            DocumentErrorKind::InvalidKeyMaterial,
+        ));
+    }
+
+    Ok((key_type, key))
+}
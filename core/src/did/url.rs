@@ -0,0 +1,493 @@
+use super::{DidDocument, Service, VerificationMethod};
+use crate::error::{DocumentError, DocumentErrorKind};
+
+/// A decentralized identifier of the form `did:<method>:<method-specific-id>`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Did {
+    method: String,
+    method_specific_id: String,
+}
+
+/// A DID URL of the form `did:<method>:<method-specific-id>/<path>?<query>#<fragment>`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DidUrl {
+    did: Did,
+    path: String,
+    query: Option<String>,
+    fragment: Option<String>,
+}
+
+/// The resource a DID URL dereferences to within a DID document.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DereferencedResource<'a> {
+    Document(&'a DidDocument),
+    VerificationMethod(&'a VerificationMethod),
+    Service(&'a Service),
+    /// The URL obtained by applying the `relativeRef` parameter to a service endpoint.
+    Url(String),
+}
+
+impl Did {
+    pub fn parse(did: &str) -> Result<Did, DocumentError> {
+        let invalid = |reason: &str| {
+            DocumentError::new(
+                format!("{did} is not a valid DID: {reason}"),
+                DocumentErrorKind::InvalidDid,
+            )
+        };
+
+        let rest = did
+            .strip_prefix("did:")
+            .ok_or_else(|| invalid("must start with did:"))?;
+        let (method, method_specific_id) = rest
+            .split_once(':')
+            .ok_or_else(|| invalid("missing method-specific-id"))?;
+
+        if method.is_empty()
+            || !method
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit())
+        {
+            return Err(invalid(
+                "method name must consist of lowercase letters and digits",
+            ));
+        }
+
+        // method-specific-id = *( *idchar ":" ) 1*idchar
+        if method_specific_id.is_empty() || method_specific_id.ends_with(':') {
+            return Err(invalid(
+                "method-specific-id must not be empty or end with :",
+            ));
+        }
+        let bytes = method_specific_id.as_bytes();
+        let mut i = 0;
+        while i < bytes.len() {
+            match bytes[i] {
+                b'%' => {
+                    if !is_pct_encoded(&bytes[i..]) {
+                        return Err(invalid("malformed percent-encoding"));
+                    }
+                    i += 3;
+                }
+                c if c.is_ascii_alphanumeric() || b".-_:".contains(&c) => i += 1,
+                c => {
+                    return Err(invalid(&format!(
+                        "character {} is not allowed in method-specific-id",
+                        c as char
+                    )))
+                }
+            }
+        }
+
+        Ok(Did {
+            method: method.to_string(),
+            method_specific_id: method_specific_id.to_string(),
+        })
+    }
+
+    pub fn method(&self) -> &str {
+        &self.method
+    }
+
+    pub fn method_specific_id(&self) -> &str {
+        &self.method_specific_id
+    }
+}
+
+impl std::str::FromStr for Did {
+    type Err = DocumentError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Did::parse(s)
+    }
+}
+
+impl std::fmt::Display for Did {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "did:{}:{}", self.method, self.method_specific_id)
+    }
+}
+
+impl DidUrl {
+    pub fn parse(url: &str) -> Result<DidUrl, DocumentError> {
+        let invalid = |reason: &str| {
+            DocumentError::new(
+                format!("{url} is not a valid DID URL: {reason}"),
+                DocumentErrorKind::InvalidDidUrl,
+            )
+        };
+
+        let (rest, fragment) = match url.split_once('#') {
+            Some((rest, fragment)) => (rest, Some(fragment)),
+            None => (url, None),
+        };
+        let (rest, query) = match rest.split_once('?') {
+            Some((rest, query)) => (rest, Some(query)),
+            None => (rest, None),
+        };
+        let (did, path) = match rest.find('/') {
+            Some(i) => rest.split_at(i),
+            None => (rest, ""),
+        };
+
+        let did = Did::parse(did)?;
+
+        // path segments only allow pchar, query and fragment additionally allow "/" and "?"
+        if !path.split('/').all(|segment| is_pchars(segment, "")) {
+            return Err(invalid("malformed path"));
+        }
+        if matches!(query, Some(q) if !is_pchars(q, "/?")) {
+            return Err(invalid("malformed query"));
+        }
+        if matches!(fragment, Some(f) if !is_pchars(f, "/?")) {
+            return Err(invalid("malformed fragment"));
+        }
+
+        let did_url = DidUrl {
+            did,
+            path: path.to_string(),
+            query: query.map(String::from),
+            fragment: fragment.map(String::from),
+        };
+
+        if let Some(version_time) = did_url.version_time() {
+            chrono::DateTime::parse_from_rfc3339(&version_time)
+                .map_err(|_| invalid("versionTime must be an XML datetime"))?;
+        }
+
+        Ok(did_url)
+    }
+
+    pub fn did(&self) -> &Did {
+        &self.did
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    pub fn query(&self) -> Option<&str> {
+        self.query.as_deref()
+    }
+
+    pub fn fragment(&self) -> Option<&str> {
+        self.fragment.as_deref()
+    }
+
+    /// The percent-decoded name/value pairs of the query.
+    pub fn parameters(&self) -> Vec<(String, String)> {
+        let query = match &self.query {
+            Some(query) => query,
+            None => return vec![],
+        };
+        return query
+            .split('&')
+            .filter(|pair| !pair.is_empty())
+            .map(|pair| match pair.split_once('=') {
+                Some((name, value)) => (percent_decode(name), percent_decode(value)),
+                None => (percent_decode(pair), String::new()),
+            })
+            .collect();
+    }
+
+    pub fn parameter(&self, name: &str) -> Option<String> {
+        return self
+            .parameters()
+            .into_iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v);
+    }
+
+    pub fn version_id(&self) -> Option<String> {
+        self.parameter("versionId")
+    }
+
+    pub fn version_time(&self) -> Option<String> {
+        self.parameter("versionTime")
+    }
+
+    pub fn service(&self) -> Option<String> {
+        self.parameter("service")
+    }
+
+    pub fn relative_ref(&self) -> Option<String> {
+        self.parameter("relativeRef")
+    }
+
+    /// Dereference this DID URL against the DID document of its DID.
+    /// Fragments select a verification method or service, the `service` parameter selects a service and,
+    /// combined with `relativeRef`, produces a URL relative to its service endpoint.
+    pub fn dereference<'a>(
+        &self,
+        doc: &'a DidDocument,
+    ) -> Result<DereferencedResource<'a>, DocumentError> {
+        let not_found = || {
+            DocumentError::new(
+                format!("{self} does not match any resource of {}", doc.id),
+                DocumentErrorKind::InvalidReference,
+            )
+        };
+
+        if doc.id != self.did.to_string() {
+            return Err(DocumentError::new(
+                format!("{self} cannot be dereferenced against {}", doc.id),
+                DocumentErrorKind::InvalidReference,
+            ));
+        }
+
+        if let Some(service_id) = self.service() {
+            let service = find_service(doc, &format!("#{service_id}")).ok_or_else(not_found)?;
+            return match (self.relative_ref(), &service.service_endpoint) {
+                (None, _) => Ok(DereferencedResource::Service(service)),
+                (Some(relative_ref), serde_json::Value::String(endpoint)) => {
+                    Ok(DereferencedResource::Url(format!(
+                        "{}{}",
+                        endpoint.trim_end_matches('/'),
+                        relative_ref
+                    )))
+                }
+                (Some(_), _) => Err(DocumentError::new(
+                    format!("service {service_id} does not have a URI endpoint"),
+                    DocumentErrorKind::InvalidService,
+                )),
+            };
+        }
+
+        match &self.fragment {
+            Some(fragment) => {
+                let id = format!("#{fragment}");
+                if let Some(vm) = doc.find_verification_method(&id) {
+                    return Ok(DereferencedResource::VerificationMethod(vm));
+                }
+                find_service(doc, &id)
+                    .map(DereferencedResource::Service)
+                    .ok_or_else(not_found)
+            }
+            None => Ok(DereferencedResource::Document(doc)),
+        }
+    }
+}
+
+impl std::str::FromStr for DidUrl {
+    type Err = DocumentError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        DidUrl::parse(s)
+    }
+}
+
+impl std::fmt::Display for DidUrl {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}{}", self.did, self.path)?;
+        if let Some(query) = &self.query {
+            write!(f, "?{query}")?;
+        }
+        if let Some(fragment) = &self.fragment {
+            write!(f, "#{fragment}")?;
+        }
+        Ok(())
+    }
+}
+
+impl From<Did> for DidUrl {
+    fn from(did: Did) -> Self {
+        DidUrl {
+            did,
+            path: String::new(),
+            query: None,
+            fragment: None,
+        }
+    }
+}
+
+fn find_service<'a>(doc: &'a DidDocument, id: &str) -> Option<&'a Service> {
+    let id = doc.absolute_id(id);
+    return doc.service.iter().find(|s| doc.absolute_id(&s.id) == id);
+}
+
+fn is_pct_encoded(bytes: &[u8]) -> bool {
+    bytes.len() >= 3 && bytes[1].is_ascii_hexdigit() && bytes[2].is_ascii_hexdigit()
+}
+
+/// Whether `s` only consists of RFC 3986 `pchar`s and the `extra` characters.
+fn is_pchars(s: &str, extra: &str) -> bool {
+    let bytes = s.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'%' if is_pct_encoded(&bytes[i..]) => i += 3,
+            b'%' => return false,
+            c if c.is_ascii_alphanumeric()
+                || b"-._~!$&'()*+,;=:@".contains(&c)
+                || extra.as_bytes().contains(&c) =>
+            {
+                i += 1
+            }
+            _ => return false,
+        }
+    }
+    true
+}
+
+/// Decode the percent-encoded octets of `s`, leaving malformed sequences untouched.
+pub fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' && is_pct_encoded(&bytes[i..]) {
+            let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).unwrap();
+            decoded.push(u8::from_str_radix(hex, 16).unwrap());
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+    return String::from_utf8_lossy(&decoded).into_owned();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DereferencedResource, Did, DidUrl};
+    use crate::did::DidDocument;
+    use crate::error::DocumentErrorKind;
+    use serde_json::json;
+
+    #[rstest::rstest]
+    #[case::simple("did:example:123456789abcdefghi", None)]
+    #[case::colons("did:web:example.com:user:alice", None)]
+    #[case::percent_encoded("did:web:example.com%3A3000", None)]
+    #[case::knox("did:knox:z6MkfFmsob7fC3MmqU1JVfdBnMbnAw7xm1mrEtPvAoojLcRh", None)]
+    #[case::missing_prefix("example:123", Some(DocumentErrorKind::InvalidDid))]
+    #[case::uppercase_method("did:Example:123", Some(DocumentErrorKind::InvalidDid))]
+    #[case::empty_method("did::123", Some(DocumentErrorKind::InvalidDid))]
+    #[case::empty_id("did:example:", Some(DocumentErrorKind::InvalidDid))]
+    #[case::trailing_colon("did:example:123:", Some(DocumentErrorKind::InvalidDid))]
+    #[case::bad_percent("did:example:12%3", Some(DocumentErrorKind::InvalidDid))]
+    #[case::bad_char("did:example:12!3", Some(DocumentErrorKind::InvalidDid))]
+    #[case::url("did:example:123#key-1", Some(DocumentErrorKind::InvalidDid))]
+    fn test_parse_did(#[case] did: &str, #[case] expect_error_kind: Option<DocumentErrorKind>) {
+        let res = Did::parse(did);
+        match res {
+            Ok(parsed) => {
+                assert!(expect_error_kind.is_none());
+                assert_eq!(parsed.to_string(), did);
+            }
+            Err(e) => assert_eq!(Some(e.kind), expect_error_kind, "{}", e),
+        }
+    }
+
+    #[rstest::rstest]
+    #[case::fragment("did:example:123#key-1", "", None, Some("key-1"))]
+    #[case::path("did:example:123/path/to/resource", "/path/to/resource", None, None)]
+    #[case::query(
+        "did:example:123?versionId=1&service=files",
+        "",
+        Some("versionId=1&service=files"),
+        None
+    )]
+    #[case::all(
+        "did:example:123/a?service=files&relativeRef=%2Fdoc#frag",
+        "/a",
+        Some("service=files&relativeRef=%2Fdoc"),
+        Some("frag")
+    )]
+    fn test_parse_did_url(
+        #[case] url: &str,
+        #[case] path: &str,
+        #[case] query: Option<&str>,
+        #[case] fragment: Option<&str>,
+    ) {
+        let parsed = DidUrl::parse(url).unwrap();
+        assert_eq!(parsed.did().to_string(), "did:example:123");
+        assert_eq!(parsed.path(), path);
+        assert_eq!(parsed.query(), query);
+        assert_eq!(parsed.fragment(), fragment);
+        assert_eq!(parsed.to_string(), url);
+    }
+
+    #[rstest::rstest]
+    #[case::bad_did("did:Example:123#key-1", DocumentErrorKind::InvalidDid)]
+    #[case::bad_fragment("did:example:123#key 1", DocumentErrorKind::InvalidDidUrl)]
+    #[case::bad_query("did:example:123?a=%zz", DocumentErrorKind::InvalidDidUrl)]
+    #[case::bad_version_time(
+        "did:example:123?versionTime=yesterday",
+        DocumentErrorKind::InvalidDidUrl
+    )]
+    fn test_parse_did_url_failure(#[case] url: &str, #[case] expect_error_kind: DocumentErrorKind) {
+        assert_eq!(DidUrl::parse(url).unwrap_err().kind, expect_error_kind);
+    }
+
+    #[test]
+    fn test_parameters() {
+        let url = DidUrl::parse(
+            "did:example:123?versionId=4&versionTime=2021-05-10T17:00:00Z&service=files&relativeRef=%2Fresume.pdf",
+        )
+        .unwrap();
+        assert_eq!(url.version_id(), Some("4".to_string()));
+        assert_eq!(url.version_time(), Some("2021-05-10T17:00:00Z".to_string()));
+        assert_eq!(url.service(), Some("files".to_string()));
+        assert_eq!(url.relative_ref(), Some("/resume.pdf".to_string()));
+        assert_eq!(url.parameter("hl"), None);
+    }
+
+    fn create_did_doc() -> DidDocument {
+        DidDocument::deserialize(json!({
+            "id": "did:example:123",
+            "verificationMethod": [{
+                "id": "did:example:123#key-1",
+                "type": "Ed25519VerificationKey2020",
+                "controller": "did:example:123",
+                "publicKeyMultibase": "z6MkfFmsob7fC3MmqU1JVfdBnMbnAw7xm1mrEtPvAoojLcRh"
+            }],
+            "service": [{
+                "id": "#files",
+                "type": "LinkedDomains",
+                "serviceEndpoint": "https://example.com/files/"
+            }]
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_dereference() {
+        let doc = create_did_doc();
+
+        let url = DidUrl::parse("did:example:123").unwrap();
+        assert_eq!(
+            url.dereference(&doc).unwrap(),
+            DereferencedResource::Document(&doc)
+        );
+
+        let url = DidUrl::parse("did:example:123#key-1").unwrap();
+        assert_eq!(
+            url.dereference(&doc).unwrap(),
+            DereferencedResource::VerificationMethod(&doc.verification_method[0])
+        );
+
+        let url = DidUrl::parse("did:example:123#files").unwrap();
+        assert_eq!(
+            url.dereference(&doc).unwrap(),
+            DereferencedResource::Service(&doc.service[0])
+        );
+
+        let url = DidUrl::parse("did:example:123?service=files&relativeRef=%2Fresume.pdf").unwrap();
+        assert_eq!(
+            url.dereference(&doc).unwrap(),
+            DereferencedResource::Url("https://example.com/files/resume.pdf".to_string())
+        );
+
+        let url = DidUrl::parse("did:example:123#key-2").unwrap();
+        assert_eq!(
+            url.dereference(&doc).unwrap_err().kind,
+            DocumentErrorKind::InvalidReference
+        );
+
+        let url = DidUrl::parse("did:example:456#key-1").unwrap();
+        assert_eq!(
+            url.dereference(&doc).unwrap_err().kind,
+            DocumentErrorKind::InvalidReference
+        );
+    }
+}
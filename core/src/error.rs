@@ -8,6 +8,12 @@ pub enum ErrorKind {
     InvalidData,
     Uncategorized,
     NetworkFailure,
+    MethodNotSupported,
+    InvalidDid,
+    Deactivated,
+    RepresentationNotSupported,
+    OperationNotSupported,
+    Unauthorized,
 }
 
 impl std::fmt::Display for ErrorKind {
@@ -16,6 +22,41 @@ impl std::fmt::Display for ErrorKind {
     }
 }
 
+impl ErrorKind {
+    /// The DID Resolution error code reported in `didResolutionMetadata.error`.
+    /// `invalidDidDocument`, `networkFailure`, `operationNotSupported` and `unauthorized` extend the codes of the DID Resolution specification.
+    pub fn code(&self) -> &'static str {
+        match self {
+            ErrorKind::DocumentNotFound => "notFound",
+            ErrorKind::InvalidData => "invalidDidDocument",
+            ErrorKind::Uncategorized => "internalError",
+            ErrorKind::NetworkFailure => "networkFailure",
+            ErrorKind::MethodNotSupported => "methodNotSupported",
+            ErrorKind::InvalidDid => "invalidDid",
+            ErrorKind::Deactivated => "deactivated",
+            ErrorKind::RepresentationNotSupported => "representationNotSupported",
+            ErrorKind::OperationNotSupported => "operationNotSupported",
+            ErrorKind::Unauthorized => "unauthorized",
+        }
+    }
+
+    /// The kind of a DID Resolution error code, unknown codes are `Uncategorized`.
+    pub fn from_code(code: &str) -> ErrorKind {
+        match code {
+            "notFound" => ErrorKind::DocumentNotFound,
+            "invalidDidDocument" => ErrorKind::InvalidData,
+            "networkFailure" => ErrorKind::NetworkFailure,
+            "methodNotSupported" => ErrorKind::MethodNotSupported,
+            "invalidDid" => ErrorKind::InvalidDid,
+            "deactivated" => ErrorKind::Deactivated,
+            "representationNotSupported" => ErrorKind::RepresentationNotSupported,
+            "operationNotSupported" => ErrorKind::OperationNotSupported,
+            "unauthorized" => ErrorKind::Unauthorized,
+            _ => ErrorKind::Uncategorized,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct ResolverError {
     pub message: String,
@@ -36,3 +77,83 @@ impl ResolverError {
         }
     }
 }
+
+impl std::error::Error for ResolverError {}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum ValidationErrorKind {
+    UndefinedTerm,
+    ProtectedTermRedefinition,
+    RelativeIri,
+    UnknownContext,
+    InvalidContext,
+}
+
+impl std::fmt::Display for ValidationErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ValidationError {
+    pub message: String,
+    pub kind: ValidationErrorKind,
+}
+
+impl std::fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}:{}", self.kind, self.message)
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+impl ValidationError {
+    pub fn new(message: impl Into<String>, kind: ValidationErrorKind) -> Self {
+        Self {
+            message: message.into(),
+            kind: kind,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum DocumentErrorKind {
+    InvalidDocument,
+    InvalidDid,
+    InvalidDidUrl,
+    DuplicateId,
+    InvalidReference,
+    InvalidKeyMaterial,
+    InvalidService,
+}
+
+impl std::fmt::Display for DocumentErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DocumentError {
+    pub message: String,
+    pub kind: DocumentErrorKind,
+}
+
+impl std::fmt::Display for DocumentError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}:{}", self.kind, self.message)
+    }
+}
+
+impl std::error::Error for DocumentError {}
+
+impl DocumentError {
+    pub fn new(message: impl Into<String>, kind: DocumentErrorKind) -> Self {
+        Self {
+            message: message.into(),
+            kind: kind,
+        }
+    }
+}
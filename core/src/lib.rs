@@ -1,30 +1,84 @@
 mod credential;
 
 use credential::*;
+use futures::StreamExt;
 use serde_json::{self, Value};
 use std::collections::HashMap;
 
+pub mod did;
 pub mod error;
 pub mod proof;
+pub mod resolver;
+#[cfg(any(test, feature = "test-utils"))]
+pub mod test_utils;
+pub mod validation;
 
 /// Verification of Data Integrity Proofs requires the resolution of the `verificationMethod` specified in the proof.
 /// The `verificationMethod` refers to a cryptographic key stored in some external source.
 /// The DIDResolver is responsible for resolving the `verificationMethod` to a key that can be used to verify the proof.
+/// Resolvers are shared by reference and usable as `dyn DIDResolver`, see `resolver::MultiResolver` to combine resolvers of several methods.
 #[async_trait::async_trait]
-pub trait DIDResolver {
+pub trait DIDResolver: Send + Sync {
+    /// Given a `did` and resolution `options`, resolve the DID document associated with that matching `did`.
+    /// Return the validated DID document along with the DID Resolution and DID document metadata,
+    /// failures are reported through the error code of the DID Resolution metadata.
+    async fn resolve(
+        &self,
+        did: &str,
+        options: &resolver::ResolutionOptions,
+    ) -> resolver::ResolutionResult;
     /// Given a `did`, resolve the full DID document associated with that matching `did`.
-    /// Return the JSON-LD document representing the DID.
-    async fn read(self, did: String) -> Result<serde_json::Value, error::ResolverError>;
+    /// Return the validated DID document representing the DID, deactivated DIDs are an error.
+    async fn read(&self, did: &str) -> Result<did::DidDocument, error::ResolverError> {
+        self.resolve(did, &resolver::ResolutionOptions::default())
+            .await
+            .into_document()
+    }
+    /// Read the documents of `dids`, resolving at most `concurrency` of them at a time, and return the result of each DID.
+    /// A failed read only fails the entry of its DID, duplicate DIDs are read once.
+    async fn read_many(
+        &self,
+        dids: &[String],
+        concurrency: usize,
+    ) -> HashMap<String, Result<did::DidDocument, error::ResolverError>> {
+        let mut unique: Vec<&String> = dids.iter().collect();
+        unique.sort_unstable();
+        unique.dedup();
+        let reads: Vec<_> = unique
+            .into_iter()
+            .map(|did| async move { (did.clone(), self.read(did).await) })
+            .collect();
+        return futures::stream::iter(reads)
+            .buffer_unordered(concurrency.max(1))
+            .collect()
+            .await;
+    }
     /// Given a `did` and the associated DID Document, register the DID Document with the external source used by the DIDResolver.
-    async fn create(self, did: String, doc: serde_json::Value) -> Result<(), error::ResolverError>;
+    async fn create(&self, did: &str, doc: did::DidDocument) -> Result<(), error::ResolverError>;
+    /// Given a `did` and its new DID Document, replace the registered DID Document, for example to rotate keys or change services.
+    /// Methods whose documents cannot change fail with `ErrorKind::OperationNotSupported`.
+    async fn update(&self, did: &str, _doc: did::DidDocument) -> Result<(), error::ResolverError> {
+        Err(error::ResolverError::new(
+            format!("did:{} does not support updating {did}", self.get_method()),
+            error::ErrorKind::OperationNotSupported,
+        ))
+    }
+    /// Given a `did`, deactivate it so that it no longer resolves to a usable DID Document.
+    /// Methods whose documents cannot change fail with `ErrorKind::OperationNotSupported`.
+    async fn deactivate(&self, did: &str) -> Result<(), error::ResolverError> {
+        Err(error::ResolverError::new(
+            format!(
+                "did:{} does not support deactivating {did}",
+                self.get_method()
+            ),
+            error::ErrorKind::OperationNotSupported,
+        ))
+    }
     // Returns the DID Method that the DID Resolver is compatible with. Each resolver can only be compatible with one.
-    fn get_method() -> &'static str;
+    fn get_method(&self) -> &'static str;
     // Given a `did` and `key` it will construct the proper `verificationMethod` to use as part of the data integrity proof creation process.
-    fn create_verification_method(public_key: String, key_id: String) -> String {
-        return format!(
-            "did:{}:{public_key}#{key_id}",
-            String::from(Self::get_method()),
-        );
+    fn create_verification_method(&self, public_key: String, key_id: String) -> String {
+        return format!("did:{}:{public_key}#{key_id}", self.get_method());
     }
 }
 
@@ -71,30 +125,291 @@ pub fn create_identity(
 /// Given a JSON-LD document and a DIDResolver, verify the data integrity proof for the document.
 /// This will by parsing the `verificationMethod` property of the data integrity proof and resolving it to a key that can be used to verify the proof.
 /// Currently only `Ed25519Signature2018` is supported for data integrity proof verification.
-pub fn verify_data_integrity_proof<S: signature::suite::Signature>(
-    _doc: serde_json::Value,
-    _resolver: &impl DIDResolver,
-    _verifier: &impl signature::verifier::DIDVerifier<S>,
+/// The document must pass strict JSON-LD validation, see `verify_data_integrity_proof_with_options` to opt out.
+pub async fn verify_data_integrity_proof<S: signature::suite::Signature>(
+    doc: serde_json::Value,
+    resolver: &dyn DIDResolver,
+    verifier: &impl signature::verifier::DIDVerifier<S>,
 ) -> Result<bool, Box<dyn std::error::Error>> {
-    unimplemented!();
+    return verify_data_integrity_proof_with_options(
+        doc,
+        resolver,
+        verifier,
+        &validation::ValidationOptions::default(),
+    )
+    .await;
+}
+
+/// Verify the data integrity proof of the document after validating the unsigned document according to `options`.
+/// The `verificationMethod` must be listed under the relationship of the proof's purpose in the controller's current DID
+/// document, so proofs made with a key rotated out since, or by a deactivated controller, are rejected whatever their
+/// `created` time, which the signer chooses and which must not be in the future. A `versionId` or `versionTime` parameter
+/// of the `verificationMethod` names the version of the DID document the method is read from, its key must be unchanged
+/// in the current document. The `issuer` of a verifiable credential must be the controller.
+/// The `verifier` must hold the key of the `verificationMethod`, otherwise the proof is rejected.
+pub async fn verify_data_integrity_proof_with_options<S: signature::suite::Signature>(
+    doc: serde_json::Value,
+    resolver: &dyn DIDResolver,
+    verifier: &impl signature::verifier::DIDVerifier<S>,
+    options: &validation::ValidationOptions,
+) -> Result<bool, Box<dyn std::error::Error>> {
+    let (doc, proof, vm) = resolve_proof(doc, resolver, options).await?;
+    let (_, public_key) = vm.public_key()?;
+    if verifier.public_key() != public_key {
+        return Err(format!(
+            "the verifier does not hold the key of {}",
+            proof.verification_method
+        )
+        .into());
+    }
+    proof::verify_proof_value(doc, &proof, verifier)?;
+
+    Ok(true)
+}
+
+/// Verify the data integrity proof of the document with the suite named by the proof's `type` or `cryptosuite`, looked up
+/// in `registry`, and the key of its `verificationMethod` in the controller's DID document.
+/// The controller's DID document is resolved and checked as in `verify_data_integrity_proof_with_options`.
+pub async fn verify_data_integrity_proof_with_registry(
+    doc: serde_json::Value,
+    resolver: &dyn DIDResolver,
+    registry: &proof::SuiteRegistry,
+    options: &validation::ValidationOptions,
+) -> Result<bool, Box<dyn std::error::Error>> {
+    let (doc, proof, vm) = resolve_proof(doc, resolver, options).await?;
+    let (key_type, public_key) = vm.public_key()?;
+    let verifier = registry.verifier(proof.suite(), key_type, &public_key)?;
+    proof::verify_proof_value_dyn(doc, &proof, verifier.as_ref())?;
+
+    Ok(true)
+}
+
+/// Split the proof from the document, validate the unsigned document and find the proof's verification method in the
+/// controller's current DID document, or in the version named by the `verificationMethod` as long as the current
+/// document has the same key. The document must list the method under the relationship of the proof purpose, so that,
+/// e.g., an `authentication` key cannot issue credentials.
+async fn resolve_proof(
+    mut doc: serde_json::Value,
+    resolver: &dyn DIDResolver,
+    options: &validation::ValidationOptions,
+) -> Result<
+    (
+        serde_json::Value,
+        proof::DataIntegrityProof,
+        did::VerificationMethod,
+    ),
+    Box<dyn std::error::Error>,
+> {
+    let proof = doc
+        .as_object_mut()
+        .and_then(|doc| doc.remove("proof"))
+        .ok_or("document has no proof")?;
+    let proof: proof::DataIntegrityProof = serde_json::from_value(proof)?;
+
+    validation::validate(&doc, options)?;
+    if chrono::DateTime::parse_from_rfc3339(&proof.created)? > chrono::Utc::now() {
+        return Err(format!("proof created at {} is in the future", proof.created).into());
+    }
+
+    let vm_url = did::DidUrl::parse(&proof.verification_method)?;
+    let did = vm_url.did().to_string();
+    if has_type(&doc, "VerifiableCredential") {
+        let issuer = doc.get("issuer").and_then(claimed_id);
+        if issuer != Some(did.as_str()) {
+            return Err(format!(
+                "issuer {} is not the signer {did}",
+                issuer.unwrap_or("none")
+            )
+            .into());
+        }
+    }
+    let vm_id = match vm_url.fragment() {
+        Some(fragment) => format!("{did}#{fragment}"),
+        None => did.clone(),
+    };
+    let relation = proof
+        .proof_purpose
+        .parse::<signature::suite::VerificationRelation>()?;
+
+    let current = resolver
+        .resolve(&did, &resolver::ResolutionOptions::default())
+        .await
+        .into_document()?;
+    let vm = authorized_method(&current, &vm_id, relation)?;
+    let resolution_options = resolver::ResolutionOptions::from_did_url(&vm_url);
+    if resolution_options.is_historical() {
+        let version = resolver
+            .resolve(&did, &resolution_options)
+            .await
+            .into_document()?;
+        if authorized_method(&version, &vm_id, relation)?.public_key()? != vm.public_key()? {
+            return Err(format!(
+                "the key of verification method {vm_id} has changed since {}",
+                proof.verification_method
+            )
+            .into());
+        }
+    }
+
+    return Ok((doc, proof, vm));
+}
+
+/// Whether `type` of `doc`, a single type or a list of them, includes `name`.
+fn has_type(doc: &Value, name: &str) -> bool {
+    return match &doc["type"] {
+        Value::Array(types) => types.iter().any(|t| t.as_str() == Some(name)),
+        t => t.as_str() == Some(name),
+    };
+}
+
+/// The DID claimed by a `holder` or `issuer`, either the DID itself or an object with the DID as its `id`.
+fn claimed_id(claimed: &Value) -> Option<&str> {
+    return match claimed {
+        Value::Object(claimed) => claimed.get("id").and_then(Value::as_str),
+        claimed => claimed.as_str(),
+    };
+}
+
+/// The verification method `vm_id` of the controller's DID document, which must list it under `relation`.
+fn authorized_method(
+    controller: &did::DidDocument,
+    vm_id: &str,
+    relation: signature::suite::VerificationRelation,
+) -> Result<did::VerificationMethod, Box<dyn std::error::Error>> {
+    let vm = controller.find_verification_method(vm_id).ok_or_else(|| {
+        format!(
+            "verification method {vm_id} not found in the DID document of {}",
+            controller.id
+        )
+    })?;
+    if !controller.has_relationship(vm_id, relation) {
+        return Err(format!("verification method {vm_id} is not authorized for {relation}").into());
+    }
+    return Ok(vm.clone());
 }
 
 /// Given a JSON-LD document and a DIDResolver, verify the data integrity proof for the Verifiable Presentation.
 /// Then each claimed Verifiable Credential must be verified for validity and ownership of the credential by the subject.
-pub fn verify_presentation<S: signature::suite::Signature>(
-    _doc: serde_json::Value,
-    _resolver: &impl DIDResolver,
-    _verifier: &impl signature::verifier::DIDVerifier<S>,
+/// The documents must pass strict JSON-LD validation, see `verify_presentation_with_options` to opt out.
+pub async fn verify_presentation(
+    doc: serde_json::Value,
+    resolver: &dyn DIDResolver,
 ) -> Result<bool, Box<dyn std::error::Error>> {
-    unimplemented!();
+    return verify_presentation_with_options(
+        doc,
+        resolver,
+        &validation::ValidationOptions::default(),
+    )
+    .await;
+}
+
+/// Verify the presentation's `authentication` proof and the `assertionMethod` proof of each of its credentials, whose
+/// subjects must be the holder that signed the presentation. The presentation's `holder`, when set, must be that DID too.
+/// Bearer credentials, with a subject without an `id`, are rejected since nothing binds them to the holder. The `Ed25519Signature2018` proofs are verified as one batch,
+/// proofs of other suites one by one with the suites of `proof::SuiteRegistry::default()`.
+/// Errors name the failing documents, `presentation` or `verifiableCredential[i]`.
+pub async fn verify_presentation_with_options(
+    doc: serde_json::Value,
+    resolver: &dyn DIDResolver,
+    options: &validation::ValidationOptions,
+) -> Result<bool, Box<dyn std::error::Error>> {
+    let credentials: Vec<Value> = match doc.get("verifiableCredential") {
+        Some(Value::Array(credentials)) => credentials.clone(),
+        Some(credential @ Value::Object(_)) => vec![credential.clone()],
+        None => vec![],
+        Some(_) => return Err("verifiableCredential must be an object or an array".into()),
+    };
+    let mut names = vec!["presentation".to_string()];
+    names.extend((0..credentials.len()).map(|i| format!("verifiableCredential[{i}]")));
+
+    let resolved = futures::future::join_all(
+        std::iter::once(doc)
+            .chain(credentials)
+            .map(|doc| resolve_proof(doc, resolver, options)),
+    )
+    .await;
+
+    let registry = proof::SuiteRegistry::default();
+    let mut batch = signature::verifier::Ed25519BatchVerifier::new();
+    let mut batched: Vec<&str> = vec![];
+    let mut holder = String::new();
+    for (index, (name, res)) in names.iter().zip(resolved).enumerate() {
+        let (doc, proof, vm) = res.map_err(|e| format!("{name}: {e}"))?;
+        let relation = proof
+            .proof_purpose
+            .parse::<signature::suite::VerificationRelation>()?;
+        if index == 0 {
+            if relation != signature::suite::VerificationRelation::Authentication {
+                return Err(format!("{name}: proof purpose must be authentication").into());
+            }
+            holder = did::DidUrl::parse(&proof.verification_method)?
+                .did()
+                .to_string();
+            if let Some(claimed) = doc.get("holder") {
+                if claimed_id(claimed) != Some(holder.as_str()) {
+                    return Err(format!("{name}: holder is not the signer {holder}").into());
+                }
+            }
+        } else {
+            if relation != signature::suite::VerificationRelation::AssertionMethod {
+                return Err(format!("{name}: proof purpose must be assertionMethod").into());
+            }
+            let subjects = match &doc["credentialSubject"] {
+                Value::Array(subjects) => subjects.iter().collect(),
+                subject => vec![subject],
+            };
+            for subject in subjects {
+                let id = subject["id"]
+                    .as_str()
+                    .ok_or_else(|| format!("{name}: subject has no id"))?;
+                if id != holder {
+                    return Err(format!("{name}: subject {id} is not the holder {holder}").into());
+                }
+            }
+        }
+
+        let (key_type, public_key) = vm.public_key()?;
+        if proof.suite() == signature::suite::PROOF_TYPE
+            && key_type == did::multicodec::KeyType::Ed25519
+        {
+            let verifier =
+                signature::verifier::Ed25519DidVerifier::try_from(public_key.as_slice())?;
+            batch
+                .queue_encoded(
+                    &verifier,
+                    &proof::signing_input(doc, &proof),
+                    &proof.proof_value,
+                    relation,
+                )
+                .map_err(|e| format!("{name}: {e}"))?;
+            batched.push(name);
+        } else {
+            let verifier = registry.verifier(proof.suite(), key_type, &public_key)?;
+            proof::verify_proof_value_dyn(doc, &proof, verifier.as_ref())
+                .map_err(|e| format!("{name}: {e}"))?;
+        }
+    }
+
+    batch.verify().map_err(|failures| {
+        return failures
+            .iter()
+            .map(|(index, e)| format!("{}: {e}", batched[*index]))
+            .collect::<Vec<String>>()
+            .join(", ");
+    })?;
+
+    Ok(true)
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::proof::create_data_integrity_proof;
+    use crate::proof::{create_data_integrity_proof, create_data_integrity_proof_with_options};
+    use crate::resolver::InMemoryResolver;
     use crate::serde_json::json;
-    use crate::DocumentBuilder;
+    use crate::validation::ValidationOptions;
+    use crate::{DIDResolver, DocumentBuilder};
     use assert_json_diff::assert_json_eq;
+    use signature::signer::DIDSigner;
     use std::{collections::HashMap, vec};
 
     use serde_json::Value;
@@ -270,10 +585,15 @@ mod tests {
 
         assert!(vc.is_ok());
         let credential = vc.unwrap();
-        let proof = create_data_integrity_proof(
+        // the permanent resident card terms are not defined by the credential contexts
+        let proof = create_data_integrity_proof_with_options(
             &signer,
             credential.serialize(),
             signature::suite::VerificationRelation::AssertionMethod,
+            &ValidationOptions {
+                strict: false,
+                ..Default::default()
+            },
         );
 
         assert!(proof.is_ok());
@@ -293,4 +613,701 @@ mod tests {
         assert_json_eq!(expect_presentation, presentation_json);
         Ok(())
     }
+
+    fn get_alumni_credential() -> Value {
+        json!({
+            "@context": [
+                "https://www.w3.org/2018/credentials/v1",
+                "https://www.w3.org/2018/credentials/examples/v1"
+            ],
+            "id": "http://example.edu/credentials/1872",
+            "type": ["VerifiableCredential", "AlumniCredential"],
+            "issuer": "https://example.edu/issuers/565049",
+            "issuanceDate": "2010-01-01T19:23:24Z",
+            "credentialSubject": {
+                "id": "did:example:ebfeb1f712ebc6f1c276e12ec21",
+                "alumniOf": "Example University"
+            }
+        })
+    }
+
+    /// `credential` with the DID of `signer` as its issuer.
+    fn issued_by<S: signature::suite::Signature>(
+        mut credential: Value,
+        signer: &impl DIDSigner<S>,
+    ) -> Value {
+        let vm =
+            signer.get_verification_method(signature::suite::VerificationRelation::AssertionMethod);
+        credential["issuer"] = json!(crate::did::DidUrl::parse(&vm).unwrap().did().to_string());
+        return credential;
+    }
+
+    macro_rules! aw {
+        ($e:expr) => {
+            tokio_test::block_on($e)
+        };
+    }
+
+    fn create_resolver(signer: &signature::signer::Ed25519DidSigner) -> InMemoryResolver {
+        let doc = crate::test_utils::signer_document(
+            signer,
+            &[signature::suite::VerificationRelation::AssertionMethod],
+        );
+        let resolver = InMemoryResolver::new("knox");
+        resolver
+            .insert_version(
+                &doc.id.clone(),
+                doc,
+                chrono::Utc::now() - chrono::Duration::hours(1),
+            )
+            .unwrap();
+        return resolver;
+    }
+
+    #[rstest::rstest]
+    #[case::success(
+        get_alumni_credential(),
+        None,
+        ValidationOptions::default(),
+        true,
+        true
+    )]
+    #[case::tampered(
+        get_alumni_credential(),
+        Some(("alumniOf", json!("Another University"))),
+        ValidationOptions::default(),
+        true,
+        false
+    )]
+    #[case::undefined_term(
+        get_alumni_credential(),
+        Some(("lprNumber", json!("999-999-999"))),
+        ValidationOptions::default(),
+        true,
+        false
+    )]
+    #[case::undefined_term_opt_out(
+        { let mut c = get_alumni_credential(); c["credentialSubject"]["lprNumber"] = json!("999-999-999"); c },
+        None,
+        ValidationOptions { strict: false, ..Default::default() },
+        true,
+        true
+    )]
+    #[case::unresolvable_controller(
+        get_alumni_credential(),
+        None,
+        ValidationOptions::default(),
+        false,
+        false
+    )]
+    fn test_verify_data_integrity_proof(
+        #[case] credential: Value,
+        #[case] tamper: Option<(&str, Value)>,
+        #[case] options: ValidationOptions,
+        #[case] resolvable: bool,
+        #[case] expect_ok: bool,
+    ) {
+        let signer = signature::signer::Ed25519DidSigner::new();
+        let verifier = signature::verifier::Ed25519DidVerifier::from(&signer);
+        let credential = issued_by(credential, &signer);
+        let proof = create_data_integrity_proof_with_options(
+            &signer,
+            credential.clone(),
+            signature::suite::VerificationRelation::AssertionMethod,
+            &ValidationOptions {
+                strict: false,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        let mut signed = credential;
+        signed["proof"] = serde_json::to_value(proof).unwrap();
+        if let Some((key, value)) = tamper {
+            signed["credentialSubject"][key] = value;
+        }
+
+        let resolver = match resolvable {
+            true => create_resolver(&signer),
+            false => InMemoryResolver::new("knox"),
+        };
+        let res = aw!(crate::verify_data_integrity_proof_with_options(
+            signed, &resolver, &verifier, &options,
+        ));
+        assert_eq!(res.is_ok(), expect_ok);
+    }
+
+    #[test]
+    fn test_verify_data_integrity_proof_other_method() {
+        // the proof names the method of `other` but is signed with the key of `signer`
+        let signer = signature::signer::Ed25519DidSigner::new();
+        let other = signature::signer::Ed25519DidSigner::new();
+        let other_vm =
+            other.get_verification_method(signature::suite::VerificationRelation::AssertionMethod);
+        let forger = signature::keystore::KeyStoreSigner::new(
+            other_vm,
+            signature::signer::Ed25519DidSigner::from_seed(signer.seed()),
+        );
+        let options = ValidationOptions {
+            strict: false,
+            ..Default::default()
+        };
+        let proof = create_data_integrity_proof_with_options(
+            &forger,
+            issued_by(get_alumni_credential(), &other),
+            signature::suite::VerificationRelation::AssertionMethod,
+            &options,
+        )
+        .unwrap();
+        let mut signed = issued_by(get_alumni_credential(), &other);
+        signed["proof"] = serde_json::to_value(proof).unwrap();
+
+        let resolver = create_resolver(&other);
+        let verifier = signature::verifier::Ed25519DidVerifier::from(&signer);
+        let res = aw!(crate::verify_data_integrity_proof_with_options(
+            signed, &resolver, &verifier, &options,
+        ));
+        assert!(res.is_err());
+    }
+
+    #[rstest::rstest]
+    #[case::issuer_is_signer(json!("did:knox:signer"), true)]
+    #[case::issuer_object(json!({ "id": "did:knox:signer", "name": "Example University" }), true)]
+    #[case::other_issuer(json!("did:knox:other"), false)]
+    #[case::web_issuer(json!("https://example.edu/issuers/565049"), false)]
+    fn test_verify_data_integrity_proof_issuer(#[case] issuer: Value, #[case] expect_ok: bool) {
+        let signer = signature::signer::Ed25519RelationalSigner::generate("did:knox:signer");
+        let resolver = InMemoryResolver::new("knox");
+        resolver
+            .insert_version(
+                signer.did(),
+                create_relational_doc(&signer),
+                chrono::Utc::now() - chrono::Duration::hours(1),
+            )
+            .unwrap();
+        let options = ValidationOptions {
+            strict: false,
+            ..Default::default()
+        };
+        let mut credential = get_alumni_credential();
+        credential["issuer"] = issuer;
+        let proof = create_data_integrity_proof_with_options(
+            &signer,
+            credential.clone(),
+            signature::suite::VerificationRelation::AssertionMethod,
+            &options,
+        )
+        .unwrap();
+        credential["proof"] = serde_json::to_value(proof).unwrap();
+
+        let res = aw!(crate::verify_data_integrity_proof_with_registry(
+            credential,
+            &resolver,
+            &crate::proof::SuiteRegistry::default(),
+            &options,
+        ));
+        assert_eq!(res.is_ok(), expect_ok);
+    }
+
+    #[rstest::rstest]
+    #[case::valid(|_: &mut Value| {}, true)]
+    #[case::unknown_suite(|signed: &mut Value| signed["proof"]["type"] = json!("BbsBlsSignature2020"), false)]
+    #[case::cryptosuite_mismatch(
+        |signed: &mut Value| signed["proof"]["cryptosuite"] = json!("eddsa-2022"),
+        false
+    )]
+    #[case::tampered(|signed: &mut Value| signed["credentialSubject"]["alumniOf"] = json!("Other"), false)]
+    fn test_verify_data_integrity_proof_with_registry(
+        #[case] modify: fn(&mut Value),
+        #[case] expect_ok: bool,
+    ) {
+        let signer = signature::signer::Ed25519DidSigner::new();
+        let options = ValidationOptions {
+            strict: false,
+            ..Default::default()
+        };
+        let proof = create_data_integrity_proof_with_options(
+            &signer,
+            issued_by(get_alumni_credential(), &signer),
+            signature::suite::VerificationRelation::AssertionMethod,
+            &options,
+        )
+        .unwrap();
+        let mut signed = issued_by(get_alumni_credential(), &signer);
+        signed["proof"] = serde_json::to_value(proof).unwrap();
+        modify(&mut signed);
+
+        let res = aw!(crate::verify_data_integrity_proof_with_registry(
+            signed,
+            &create_resolver(&signer),
+            &crate::proof::SuiteRegistry::default(),
+            &options,
+        ));
+        assert_eq!(res.is_ok(), expect_ok);
+    }
+
+    /// DID document listing each key of `signer` under its own relationship.
+    fn create_relational_doc(
+        signer: &signature::signer::Ed25519RelationalSigner,
+    ) -> crate::did::DidDocument {
+        let mut doc = crate::did::DidDocument::new(signer.did().to_string());
+        for relation in signature::suite::VERIFICATION_RELATIONS {
+            let id = signer.get_verification_method(relation);
+            doc.verification_method
+                .push(crate::did::VerificationMethod {
+                    id: id.clone(),
+                    method_type: crate::did::VerificationMethodType::Multikey,
+                    controller: signer.did().to_string(),
+                    public_key_multibase: Some(id.split('#').nth(1).unwrap().to_string()),
+                    public_key_base58: None,
+                    public_key_jwk: None,
+                    property_set: HashMap::new(),
+                });
+            match relation {
+                signature::suite::VerificationRelation::AssertionMethod => {
+                    &mut doc.assertion_method
+                }
+                signature::suite::VerificationRelation::Authentication => &mut doc.authentication,
+                signature::suite::VerificationRelation::CapabilityInvocation => {
+                    &mut doc.capability_invocation
+                }
+                signature::suite::VerificationRelation::CapabilityDelegation => {
+                    &mut doc.capability_delegation
+                }
+            }
+            .push(crate::did::VerificationRelationship::Reference(id));
+        }
+        return doc;
+    }
+
+    #[rstest::rstest]
+    #[case::assertion_method(signature::suite::VerificationRelation::AssertionMethod, None, true)]
+    #[case::authentication(signature::suite::VerificationRelation::Authentication, None, true)]
+    #[case::authentication_key_issuing(
+        signature::suite::VerificationRelation::Authentication,
+        Some("assertionMethod"),
+        false
+    )]
+    #[case::assertion_key_authenticating(
+        signature::suite::VerificationRelation::AssertionMethod,
+        Some("authentication"),
+        false
+    )]
+    fn test_verify_data_integrity_proof_relationship(
+        #[case] relation: signature::suite::VerificationRelation,
+        #[case] claimed_purpose: Option<&str>,
+        #[case] expect_ok: bool,
+    ) {
+        let did = "did:knox:z6MkfFmsob7fC3MmqU1JVfdBnMbnAw7xm1mrEtPvAoojLcRh";
+        let signer = signature::signer::Ed25519RelationalSigner::generate(did);
+        let resolver = InMemoryResolver::new("knox");
+        resolver
+            .insert_version(
+                did,
+                create_relational_doc(&signer),
+                chrono::Utc::now() - chrono::Duration::hours(1),
+            )
+            .unwrap();
+
+        let options = ValidationOptions {
+            strict: false,
+            ..Default::default()
+        };
+        let proof = create_data_integrity_proof_with_options(
+            &signer,
+            issued_by(get_alumni_credential(), &signer),
+            relation,
+            &options,
+        )
+        .unwrap();
+        let mut signed = issued_by(get_alumni_credential(), &signer);
+        signed["proof"] = serde_json::to_value(proof).unwrap();
+        if let Some(purpose) = claimed_purpose {
+            signed["proof"]["verificationPurpose"] = json!(purpose);
+        }
+
+        let verifier = signature::verifier::Ed25519DidVerifier::from(signer.key(relation));
+        let res = aw!(crate::verify_data_integrity_proof_with_options(
+            signed.clone(),
+            &resolver,
+            &verifier,
+            &options,
+        ));
+        assert_eq!(res.is_ok(), expect_ok);
+        let res = aw!(crate::verify_data_integrity_proof_with_registry(
+            signed,
+            &resolver,
+            &crate::proof::SuiteRegistry::default(),
+            &options,
+        ));
+        assert_eq!(res.is_ok(), expect_ok);
+    }
+
+    /// Sign `credential` with `signer`, with the options of `proof` in place of those of a proof made now.
+    fn sign_with_options(
+        signer: &signature::signer::Ed25519DidSigner,
+        credential: Value,
+        mut proof: crate::proof::DataIntegrityProof,
+    ) -> Value {
+        proof.proof_value = signer
+            .try_encoded_relational_sign(
+                &crate::proof::signing_input(credential.clone(), &proof),
+                signature::suite::VerificationRelation::AssertionMethod,
+            )
+            .unwrap();
+        let mut signed = credential;
+        signed["proof"] = serde_json::to_value(proof).unwrap();
+        return signed;
+    }
+
+    /// Record a new version of the DID document of `signer` in `resolver`, with its key replaced by a new one when
+    /// `rotate` is set.
+    fn update_signer_document(
+        resolver: &InMemoryResolver,
+        signer: &signature::signer::Ed25519DidSigner,
+        rotate: bool,
+    ) {
+        let relations = [signature::suite::VerificationRelation::AssertionMethod];
+        let mut doc = crate::test_utils::signer_document(signer, &relations);
+        let did = doc.id.clone();
+        if rotate {
+            let rotated = signature::signer::Ed25519DidSigner::new();
+            doc = crate::test_utils::signer_document(&rotated, &relations);
+            let rotated = rotated
+                .get_verification_method(signature::suite::VerificationRelation::AssertionMethod);
+            let fragment = rotated.split('#').nth(1).unwrap();
+            doc.id = did.clone();
+            doc.verification_method[0].id = format!("{did}#{fragment}");
+            doc.verification_method[0].controller = did.clone();
+            doc.assertion_method = vec![crate::did::VerificationRelationship::Reference(format!(
+                "{did}#{fragment}"
+            ))];
+        } else {
+            doc.also_known_as = vec!["https://example.com/issuer".to_string()];
+        }
+        resolver
+            .insert_version(&did, doc, chrono::Utc::now())
+            .unwrap();
+    }
+
+    #[test]
+    fn test_verify_data_integrity_proof_after_key_rotation() {
+        let signer = signature::signer::Ed25519DidSigner::new();
+        let verifier = signature::verifier::Ed25519DidVerifier::from(&signer);
+        let resolver = create_resolver(&signer);
+        let sign = || {
+            let proof = create_data_integrity_proof(
+                &signer,
+                issued_by(get_alumni_credential(), &signer),
+                signature::suite::VerificationRelation::AssertionMethod,
+            )
+            .unwrap();
+            let mut signed = issued_by(get_alumni_credential(), &signer);
+            signed["proof"] = serde_json::to_value(proof).unwrap();
+            signed
+        };
+        let issued_before_rotation = sign();
+        assert!(aw!(crate::verify_data_integrity_proof(
+            issued_before_rotation.clone(),
+            &resolver,
+            &verifier
+        ))
+        .is_ok());
+
+        // the controller replaces its key, keeping the same DID, proofs of the old key are rejected whenever made
+        update_signer_document(&resolver, &signer, true);
+        assert!(aw!(crate::verify_data_integrity_proof(
+            issued_before_rotation,
+            &resolver,
+            &verifier
+        ))
+        .is_err());
+        assert!(aw!(crate::verify_data_integrity_proof(
+            sign(),
+            &resolver,
+            &verifier
+        ))
+        .is_err());
+    }
+
+    #[rstest::rstest]
+    #[case::unchanged_key(false, true)]
+    #[case::rotated_key(true, false)]
+    fn test_verify_data_integrity_proof_version(#[case] rotate: bool, #[case] expect_ok: bool) {
+        let signer = signature::signer::Ed25519DidSigner::new();
+        let verifier = signature::verifier::Ed25519DidVerifier::from(&signer);
+        let resolver = create_resolver(&signer);
+        let mut proof = create_data_integrity_proof(
+            &signer,
+            issued_by(get_alumni_credential(), &signer),
+            signature::suite::VerificationRelation::AssertionMethod,
+        )
+        .unwrap();
+        // the verification method names the version of the document that lists its key
+        proof.verification_method = proof.verification_method.replace('#', "?versionId=1#");
+        let signed = sign_with_options(&signer, issued_by(get_alumni_credential(), &signer), proof);
+        update_signer_document(&resolver, &signer, rotate);
+
+        let res = aw!(crate::verify_data_integrity_proof(
+            signed, &resolver, &verifier
+        ));
+        assert_eq!(res.is_ok(), expect_ok);
+    }
+
+    #[test]
+    fn test_verify_data_integrity_proof_created_in_future() {
+        let signer = signature::signer::Ed25519DidSigner::new();
+        let verifier = signature::verifier::Ed25519DidVerifier::from(&signer);
+        let mut proof = create_data_integrity_proof(
+            &signer,
+            issued_by(get_alumni_credential(), &signer),
+            signature::suite::VerificationRelation::AssertionMethod,
+        )
+        .unwrap();
+        proof.created = (chrono::Utc::now() + chrono::Duration::hours(1)).to_rfc3339();
+        let signed = sign_with_options(&signer, issued_by(get_alumni_credential(), &signer), proof);
+
+        let res = aw!(crate::verify_data_integrity_proof(
+            signed,
+            &create_resolver(&signer),
+            &verifier
+        ));
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn test_verify_data_integrity_proof_deactivated() {
+        let signer = signature::signer::Ed25519DidSigner::new();
+        let verifier = signature::verifier::Ed25519DidVerifier::from(&signer);
+        let resolver = create_resolver(&signer);
+        let proof = create_data_integrity_proof(
+            &signer,
+            issued_by(get_alumni_credential(), &signer),
+            signature::suite::VerificationRelation::AssertionMethod,
+        )
+        .unwrap();
+        let mut signed = issued_by(get_alumni_credential(), &signer);
+        signed["proof"] = serde_json::to_value(&proof).unwrap();
+
+        let did = crate::did::DidUrl::parse(&proof.verification_method)
+            .unwrap()
+            .did()
+            .to_string();
+        aw!(resolver.deactivate(&did)).unwrap();
+        let res = aw!(crate::verify_data_integrity_proof(
+            signed, &resolver, &verifier
+        ));
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn test_create_data_integrity_proof_rejects_undefined_terms() {
+        let signer = signature::signer::Ed25519DidSigner::new();
+        let (kv_body, kv_subject) = get_body_subject();
+        let credential = TestObj::new()
+            .create_credential(
+                vec![crate::CRED_TYPE_PERMANENT_RESIDENT_CARD.to_string()],
+                kv_subject,
+                kv_body,
+                "https://issuer.oidp.uscis.gov/credentials/83627465",
+            )
+            .unwrap();
+
+        let proof = create_data_integrity_proof(
+            &signer,
+            credential.serialize(),
+            signature::suite::VerificationRelation::AssertionMethod,
+        );
+        assert!(proof.is_err());
+    }
+
+    /// Presentation by `did:knox:holder` of alumni credentials issued to `subject` by `issuers` different issuers,
+    /// with the `tamper`ed credential modified after issuance, and the resolver of all their DID documents.
+    /// The presentation names `holder` as its `holder` when set.
+    fn create_signed_presentation(
+        issuers: usize,
+        subject: Option<&str>,
+        tamper: Option<usize>,
+        holder_relation: signature::suite::VerificationRelation,
+        holder: Option<&str>,
+    ) -> (Value, InMemoryResolver) {
+        let options = ValidationOptions {
+            strict: false,
+            ..Default::default()
+        };
+        let resolver = InMemoryResolver::new("knox");
+        let created = chrono::Utc::now() - chrono::Duration::hours(1);
+
+        let mut credentials = vec![];
+        for i in 0..issuers {
+            let issuer =
+                signature::signer::Ed25519RelationalSigner::generate(format!("did:knox:issuer{i}"));
+            resolver
+                .insert_version(issuer.did(), create_relational_doc(&issuer), created)
+                .unwrap();
+            let mut credential = get_alumni_credential();
+            credential["issuer"] = json!(issuer.did());
+            match subject {
+                Some(subject) => credential["credentialSubject"]["id"] = json!(subject),
+                None => {
+                    credential["credentialSubject"]
+                        .as_object_mut()
+                        .unwrap()
+                        .remove("id");
+                }
+            }
+            let proof = create_data_integrity_proof_with_options(
+                &issuer,
+                credential.clone(),
+                signature::suite::VerificationRelation::AssertionMethod,
+                &options,
+            )
+            .unwrap();
+            credential["proof"] = serde_json::to_value(proof).unwrap();
+            if tamper == Some(i) {
+                credential["credentialSubject"]["alumniOf"] = json!("Another University");
+            }
+            credentials.push(credential);
+        }
+
+        let signer = signature::signer::Ed25519RelationalSigner::generate("did:knox:holder");
+        resolver
+            .insert_version(signer.did(), create_relational_doc(&signer), created)
+            .unwrap();
+        let mut presentation = json!({
+            "@context": ["https://www.w3.org/2018/credentials/v1"],
+            "type": ["VerifiablePresentation"],
+            "verifiableCredential": credentials,
+        });
+        if let Some(holder) = holder {
+            presentation["holder"] = json!(holder);
+        }
+        let proof = create_data_integrity_proof_with_options(
+            &signer,
+            presentation.clone(),
+            holder_relation,
+            &options,
+        )
+        .unwrap();
+        presentation["proof"] = serde_json::to_value(proof).unwrap();
+        return (presentation, resolver);
+    }
+
+    #[rstest::rstest]
+    #[case::valid(
+        3,
+        Some("did:knox:holder"),
+        None,
+        signature::suite::VerificationRelation::Authentication,
+        None,
+        None
+    )]
+    #[case::no_credential(
+        0,
+        Some("did:knox:holder"),
+        None,
+        signature::suite::VerificationRelation::Authentication,
+        None,
+        None
+    )]
+    #[case::tampered_credential(
+        3,
+        Some("did:knox:holder"),
+        Some(1),
+        signature::suite::VerificationRelation::Authentication,
+        None,
+        Some("verifiableCredential[1]")
+    )]
+    #[case::credential_of_another_subject(
+        2,
+        Some("did:knox:other"),
+        None,
+        signature::suite::VerificationRelation::Authentication,
+        None,
+        Some("verifiableCredential[0]")
+    )]
+    #[case::bearer_credential(
+        2,
+        None,
+        None,
+        signature::suite::VerificationRelation::Authentication,
+        None,
+        Some("verifiableCredential[0]")
+    )]
+    #[case::holder_is_signer(
+        2,
+        Some("did:knox:holder"),
+        None,
+        signature::suite::VerificationRelation::Authentication,
+        Some("did:knox:holder"),
+        None
+    )]
+    #[case::holder_is_not_signer(
+        2,
+        Some("did:knox:holder"),
+        None,
+        signature::suite::VerificationRelation::Authentication,
+        Some("did:knox:other"),
+        Some("presentation")
+    )]
+    #[case::presentation_not_authenticated(
+        2,
+        Some("did:knox:holder"),
+        None,
+        signature::suite::VerificationRelation::AssertionMethod,
+        None,
+        Some("presentation")
+    )]
+    fn test_verify_presentation(
+        #[case] issuers: usize,
+        #[case] subject: Option<&str>,
+        #[case] tamper: Option<usize>,
+        #[case] holder_relation: signature::suite::VerificationRelation,
+        #[case] holder: Option<&str>,
+        #[case] expect_failing: Option<&str>,
+    ) {
+        let (presentation, resolver) =
+            create_signed_presentation(issuers, subject, tamper, holder_relation, holder);
+        let options = ValidationOptions {
+            strict: false,
+            ..Default::default()
+        };
+
+        let res = aw!(crate::verify_presentation_with_options(
+            presentation,
+            &resolver,
+            &options
+        ));
+        match expect_failing {
+            None => assert!(res.unwrap()),
+            Some(name) => {
+                let message = res.err().unwrap().to_string();
+                assert!(message.starts_with(&format!("{name}: ")), "{message}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_verify_presentation_tampered() {
+        let (mut presentation, resolver) = create_signed_presentation(
+            2,
+            Some("did:knox:holder"),
+            None,
+            signature::suite::VerificationRelation::Authentication,
+            None,
+        );
+        presentation["type"] = json!(["VerifiablePresentation", "Tampered"]);
+        let options = ValidationOptions {
+            strict: false,
+            ..Default::default()
+        };
+
+        let res = aw!(crate::verify_presentation_with_options(
+            presentation,
+            &resolver,
+            &options
+        ));
+        let message = res.err().unwrap().to_string();
+        assert!(message.starts_with("presentation: "), "{message}");
+    }
 }
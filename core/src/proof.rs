@@ -1,11 +1,17 @@
 use sha2::{Digest, Sha512};
 
 mod normalization;
+pub mod registry;
+
+pub use registry::{Suite, SuiteRegistry};
 
 #[derive(Debug, serde::Serialize, serde::Deserialize, Clone)]
 pub struct DataIntegrityProof {
     #[serde(rename = "type")]
     pub proof_type: String,
+    /// Name of the cryptographic suite of `DataIntegrityProof` proofs, whose `type` does not identify the algorithm.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cryptosuite: Option<String>,
     #[serde(rename = "issuanceDate")]
     pub created: String,
     #[serde(rename = "verificationMethod")]
@@ -16,32 +22,188 @@ pub struct DataIntegrityProof {
     pub proof_value: String,
 }
 
+impl DataIntegrityProof {
+    /// Proof options for a proof made now, whose `proofValue` is yet to be computed over `signing_input`.
+    fn unsigned(
+        proof_type: String,
+        verification_method: String,
+        relation: signature::suite::VerificationRelation,
+    ) -> Self {
+        return Self {
+            proof_type,
+            cryptosuite: None,
+            created: chrono::Utc::now().to_rfc3339(),
+            verification_method,
+            proof_purpose: relation.to_string(),
+            proof_value: String::new(),
+        };
+    }
+
+    /// Name of the suite the proof was made with, its `cryptosuite` if it has one and its `type` otherwise.
+    pub fn suite(&self) -> &str {
+        return self.cryptosuite.as_deref().unwrap_or(&self.proof_type);
+    }
+}
+
 /// Given a JSON-LD document, create a data integrity proof for the document.
 /// Currently, only `Ed25519Signature2018` data integrity proofs in the JSON-LD format can be created.
+/// The document must pass strict JSON-LD validation, see `create_data_integrity_proof_with_options` to opt out.
 pub fn create_data_integrity_proof<S: signature::suite::Signature>(
-    signer: &impl signature::signer::DIDSigner<S>,
+    signer: &(impl signature::signer::DIDSigner<S> + ?Sized),
+    doc: serde_json::Value,
+    relation: signature::suite::VerificationRelation,
+) -> Result<DataIntegrityProof, Box<dyn std::error::Error>> {
+    return create_data_integrity_proof_with_options(
+        signer,
+        doc,
+        relation,
+        &crate::validation::ValidationOptions::default(),
+    );
+}
+
+/// Create a data integrity proof for the document after validating it according to `options`.
+pub fn create_data_integrity_proof_with_options<S: signature::suite::Signature>(
+    signer: &(impl signature::signer::DIDSigner<S> + ?Sized),
+    doc: serde_json::Value,
+    relation: signature::suite::VerificationRelation,
+    options: &crate::validation::ValidationOptions,
+) -> Result<DataIntegrityProof, Box<dyn std::error::Error>> {
+    crate::validation::validate(&doc, options)?;
+
+    let mut proof = DataIntegrityProof::unsigned(
+        signer.get_proof_type(),
+        signer.get_verification_method(relation),
+        relation,
+    );
+    proof.proof_value =
+        signer.try_encoded_relational_sign(&signing_input(doc, &proof), relation)?;
+    return Ok(proof);
+}
+
+/// Create a data integrity proof with a signer whose key is not in process memory, such as a KMS or a signing service.
+/// The document must pass strict JSON-LD validation, see `create_data_integrity_proof_async_with_options` to opt out.
+pub async fn create_data_integrity_proof_async<S: signature::suite::Signature + Send>(
+    signer: &(impl signature::signer::AsyncDIDSigner<S> + ?Sized),
+    doc: serde_json::Value,
+    relation: signature::suite::VerificationRelation,
+) -> Result<DataIntegrityProof, Box<dyn std::error::Error + Send + Sync>> {
+    return create_data_integrity_proof_async_with_options(
+        signer,
+        doc,
+        relation,
+        &crate::validation::ValidationOptions::default(),
+    )
+    .await;
+}
+
+/// Create a data integrity proof with an `AsyncDIDSigner` after validating the document according to `options`.
+pub async fn create_data_integrity_proof_async_with_options<
+    S: signature::suite::Signature + Send,
+>(
+    signer: &(impl signature::signer::AsyncDIDSigner<S> + ?Sized),
+    doc: serde_json::Value,
+    relation: signature::suite::VerificationRelation,
+    options: &crate::validation::ValidationOptions,
+) -> Result<DataIntegrityProof, Box<dyn std::error::Error + Send + Sync>> {
+    crate::validation::validate(&doc, options)?;
+
+    let mut proof = DataIntegrityProof::unsigned(
+        signer.get_proof_type(),
+        signer.get_verification_method(relation),
+        relation,
+    );
+    proof.proof_value = signer
+        .try_encoded_relational_sign(&signing_input(doc, &proof), relation)
+        .await?;
+    return Ok(proof);
+}
+
+/// Create a data integrity proof with a signer of a suite chosen at runtime, e.g. built by a `SuiteRegistry`,
+/// after validating the document according to `options`.
+pub fn create_data_integrity_proof_dyn(
+    signer: &dyn signature::signer::DynDIDSigner,
     doc: serde_json::Value,
     relation: signature::suite::VerificationRelation,
+    options: &crate::validation::ValidationOptions,
 ) -> Result<DataIntegrityProof, Box<dyn std::error::Error>> {
+    crate::validation::validate(&doc, options)?;
+
+    let mut proof = DataIntegrityProof::unsigned(
+        signer.proof_type(),
+        signer.verification_method(relation),
+        relation,
+    );
+    proof.proof_value = signer.sign_encoded(&signing_input(doc, &proof), relation)?;
+    return Ok(proof);
+}
+
+/// Verify the `proofValue` of `proof` over the unsigned document with the key of `verifier`.
+/// Resolving the verification method and checking that its controller authorized it for the proof purpose is up to the caller.
+pub fn verify_proof_value<S: signature::suite::Signature>(
+    doc: serde_json::Value,
+    proof: &DataIntegrityProof,
+    verifier: &impl signature::verifier::DIDVerifier<S>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let relation = proof
+        .proof_purpose
+        .parse::<signature::suite::VerificationRelation>()?;
+    verifier.decoded_relational_verify(
+        &signing_input(doc, proof),
+        proof.proof_value.clone(),
+        relation,
+    )?;
+    Ok(())
+}
+
+/// Verify the `proofValue` of `proof` with a verifier of a suite chosen at runtime, which must be the suite of the proof.
+pub fn verify_proof_value_dyn(
+    doc: serde_json::Value,
+    proof: &DataIntegrityProof,
+    verifier: &dyn signature::verifier::DynDIDVerifier,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if verifier.proof_type() != proof.suite() {
+        return Err(format!(
+            "{} proof cannot be verified by a {} verifier",
+            proof.suite(),
+            verifier.proof_type()
+        )
+        .into());
+    }
+    let relation = proof
+        .proof_purpose
+        .parse::<signature::suite::VerificationRelation>()?;
+    verifier.verify_encoded(&signing_input(doc, proof), &proof.proof_value, relation)?;
+    Ok(())
+}
+
+/// Payload that a data integrity proof signs: the hash of its proof options, every property of `proof` but its value,
+/// followed by the hash of the document. Signing the options means that `created`, `verificationMethod`, `proofPurpose`
+/// and `type` cannot be changed without invalidating the proof.
+pub(crate) fn signing_input(doc: serde_json::Value, proof: &DataIntegrityProof) -> Vec<u8> {
+    let mut options = serde_json::to_value(proof).unwrap();
+    if let Some(options) = options.as_object_mut() {
+        options.remove("verificationValue");
+    }
+    let mut input = digest(options).as_ref().to_vec();
+    input.extend_from_slice(digest(doc).as_ref());
+    return input;
+}
+
+/// Hash of the normalized document.
+fn digest(doc: serde_json::Value) -> impl AsRef<[u8]> {
     let mut hasher = Sha512::new();
     hasher.update(normalization::normalize(doc));
-    let result = hasher.finalize();
-
-    let encoded_sig = signer.try_encoded_sign(&result)?;
-    return Ok(DataIntegrityProof {
-        proof_type: signer.get_proof_type(),
-        created: chrono::Utc::now().to_rfc3339(),
-        verification_method: signer.get_verification_method(relation),
-        proof_purpose: relation.to_string(),
-        proof_value: encoded_sig,
-    });
+    return hasher.finalize();
 }
 
 #[cfg(test)]
 mod tests {
     use sha2::Digest;
 
-    use super::create_data_integrity_proof;
+    use super::{
+        create_data_integrity_proof, create_data_integrity_proof_async,
+        create_data_integrity_proof_with_options, verify_proof_value,
+    };
     use signature::signer::DIDSigner;
     use signature::verifier::DIDVerifier;
 
@@ -68,11 +230,11 @@ mod tests {
                 );
                 assert_eq!(proof.proof_purpose, relation.to_string());
 
-                let mut hasher = sha2::Sha512::new();
-                let encoded = doc.to_string();
-                let result = encoded.into_bytes();
-                hasher.update(result);
-                let comparison = hasher.finalize();
+                let mut options = serde_json::to_value(&proof).unwrap();
+                options.as_object_mut().unwrap().remove("verificationValue");
+                let mut comparison =
+                    sha2::Sha512::digest(options.to_string().into_bytes()).to_vec();
+                comparison.extend(sha2::Sha512::digest(doc.to_string().into_bytes()));
 
                 assert!(verifier
                     .decoded_relational_verify(&comparison, proof.proof_value, relation)
@@ -81,4 +243,54 @@ mod tests {
             Err(e) => panic!("{:?}", e),
         }
     }
+
+    #[test]
+    fn test_create_data_integrity_proof_async() {
+        let signer = signature::signer::Ed25519DidSigner::new();
+        let relation = signature::suite::VerificationRelation::AssertionMethod;
+        let doc = serde_json::Value::default();
+        let proof = tokio_test::block_on(create_data_integrity_proof_async(
+            &signer,
+            doc.clone(),
+            relation,
+        ))
+        .unwrap();
+
+        assert_eq!(
+            proof.verification_method,
+            signer.get_verification_method(relation)
+        );
+        let verifier = signature::verifier::Ed25519DidVerifier::from(&signer);
+        assert!(verify_proof_value(doc, &proof, &verifier).is_ok());
+    }
+
+    #[rstest::rstest]
+    #[case::created("issuanceDate", "2000-01-01T00:00:00+00:00")]
+    #[case::verification_method(
+        "verificationMethod",
+        "did:knox:z6MkfFmsob7fC3MmqU1JVfdBnMbnAw7xm1mrEtPvAoojLcRh#key-2"
+    )]
+    #[case::proof_purpose("verificationPurpose", "authentication")]
+    #[case::proof_type("type", "Ed25519Signature2020")]
+    fn test_verify_proof_value_tampered_options(#[case] key: &str, #[case] value: &str) {
+        let signer = signature::signer::Ed25519DidSigner::new();
+        let verifier = signature::verifier::Ed25519DidVerifier::from(&signer);
+        let doc = serde_json::json!({ "name": "document" });
+        let proof = create_data_integrity_proof_with_options(
+            &signer,
+            doc.clone(),
+            signature::suite::VerificationRelation::AssertionMethod,
+            &crate::validation::ValidationOptions {
+                strict: false,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert!(verify_proof_value(doc.clone(), &proof, &verifier).is_ok());
+
+        let mut tampered = serde_json::to_value(&proof).unwrap();
+        tampered[key] = serde_json::json!(value);
+        let tampered = serde_json::from_value(tampered).unwrap();
+        assert!(verify_proof_value(doc, &tampered, &verifier).is_err());
+    }
 }
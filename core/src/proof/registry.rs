@@ -0,0 +1,297 @@
+use crate::did::multicodec::KeyType;
+use signature::error::{ErrorKind, SignatureError};
+use signature::signer::DynDIDSigner;
+use signature::verifier::DynDIDVerifier;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Build a signer from the bytes of a private key.
+pub type SignerFactory =
+    Arc<dyn Fn(&[u8]) -> Result<Box<dyn DynDIDSigner>, SignatureError> + Send + Sync>;
+/// Build a verifier from the bytes of a public key.
+pub type VerifierFactory =
+    Arc<dyn Fn(&[u8]) -> Result<Box<dyn DynDIDVerifier>, SignatureError> + Send + Sync>;
+
+/// A proof suite, identified by the proof `type` or `cryptosuite` it produces.
+#[derive(Clone)]
+pub struct Suite {
+    pub name: String,
+    /// Types of the keys the suite signs with.
+    pub key_types: Vec<KeyType>,
+    pub signer: SignerFactory,
+    pub verifier: VerifierFactory,
+}
+
+impl Suite {
+    pub fn new(
+        name: impl Into<String>,
+        key_types: Vec<KeyType>,
+        signer: impl Fn(&[u8]) -> Result<Box<dyn DynDIDSigner>, SignatureError> + Send + Sync + 'static,
+        verifier: impl Fn(&[u8]) -> Result<Box<dyn DynDIDVerifier>, SignatureError>
+            + Send
+            + Sync
+            + 'static,
+    ) -> Self {
+        return Self {
+            name: name.into(),
+            key_types,
+            signer: Arc::new(signer),
+            verifier: Arc::new(verifier),
+        };
+    }
+
+    /// `Ed25519Signature2018`, signing with the 32-byte seed of an Ed25519 key.
+    pub fn ed25519_signature_2018() -> Self {
+        return Self::new(
+            signature::suite::PROOF_TYPE,
+            vec![KeyType::Ed25519],
+            |private_key| {
+                let seed: [u8; 32] = private_key.try_into().map_err(|_| SignatureError {
+                    message: format!("expected a 32 byte seed, got {}", private_key.len()),
+                    ..SignatureError::new(ErrorKind::InvalidKey)
+                })?;
+                return Ok(Box::new(signature::signer::Ed25519DidSigner::from_seed(
+                    seed,
+                )));
+            },
+            |public_key| {
+                return Ok(Box::new(signature::verifier::Ed25519DidVerifier::try_from(
+                    public_key,
+                )?));
+            },
+        );
+    }
+}
+
+impl std::fmt::Debug for Suite {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("Suite")
+            .field("name", &self.name)
+            .field("key_types", &self.key_types)
+            .finish()
+    }
+}
+
+/// Suites known at runtime, so that proofs can be created and verified with the algorithm named by the proof.
+/// The default registry knows the suites of this crate, callers can register their own.
+#[derive(Debug, Clone)]
+pub struct SuiteRegistry {
+    suites: HashMap<String, Suite>,
+}
+
+impl Default for SuiteRegistry {
+    fn default() -> Self {
+        let mut registry = Self::new();
+        registry.register(Suite::ed25519_signature_2018());
+        return registry;
+    }
+}
+
+impl SuiteRegistry {
+    /// A registry without any suite.
+    pub fn new() -> Self {
+        return Self {
+            suites: HashMap::new(),
+        };
+    }
+
+    /// Register `suite` and return the suite of the same name it replaces, if any.
+    pub fn register(&mut self, suite: Suite) -> Option<Suite> {
+        return self.suites.insert(suite.name.clone(), suite);
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Suite> {
+        return self.suites.get(name);
+    }
+
+    /// Names of the registered suites, sorted.
+    pub fn names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.suites.keys().map(String::as_str).collect();
+        names.sort_unstable();
+        return names;
+    }
+
+    fn suite(&self, name: &str) -> Result<&Suite, SignatureError> {
+        return self.get(name).ok_or_else(|| SignatureError {
+            message: format!("unsupported proof suite {name}"),
+            ..SignatureError::new(ErrorKind::UnsupportedSuite)
+        });
+    }
+
+    /// Signer of the `name` suite for the bytes of a private key.
+    pub fn signer(
+        &self,
+        name: &str,
+        private_key: &[u8],
+    ) -> Result<Box<dyn DynDIDSigner>, SignatureError> {
+        return (self.suite(name)?.signer)(private_key);
+    }
+
+    /// Verifier of the `name` suite for a public key, which must be of a type the suite signs with.
+    pub fn verifier(
+        &self,
+        name: &str,
+        key_type: KeyType,
+        public_key: &[u8],
+    ) -> Result<Box<dyn DynDIDVerifier>, SignatureError> {
+        let suite = self.suite(name)?;
+        if !suite.key_types.contains(&key_type) {
+            return Err(SignatureError {
+                message: format!("{name} does not support {key_type:?} keys"),
+                ..SignatureError::new(ErrorKind::InvalidKey)
+            });
+        }
+        return (suite.verifier)(public_key);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Suite, SuiteRegistry};
+    use crate::did::multicodec::KeyType;
+    use crate::proof::{create_data_integrity_proof_dyn, verify_proof_value_dyn};
+    use crate::validation::ValidationOptions;
+    use signature::error::{ErrorKind, SignatureError};
+    use signature::signer::DynDIDSigner;
+    use signature::suite::VerificationRelation;
+    use signature::verifier::DynDIDVerifier;
+
+    /// Suite "signing" with the reversed message, to show that callers can plug their own algorithms.
+    struct ReversedSigner;
+
+    impl DynDIDSigner for ReversedSigner {
+        fn proof_type(&self) -> String {
+            return "Reversed2022".to_string();
+        }
+
+        fn verification_method(&self, _relation: VerificationRelation) -> String {
+            return "did:example:123#key-1".to_string();
+        }
+
+        fn sign_encoded(
+            &self,
+            data: &[u8],
+            _relation: VerificationRelation,
+        ) -> Result<String, SignatureError> {
+            return Ok(base64::encode(
+                data.iter().rev().copied().collect::<Vec<u8>>(),
+            ));
+        }
+    }
+
+    impl DynDIDVerifier for ReversedSigner {
+        fn proof_type(&self) -> String {
+            return "Reversed2022".to_string();
+        }
+
+        fn verify_encoded(
+            &self,
+            msg: &[u8],
+            encoded_sig: &str,
+            _relation: VerificationRelation,
+        ) -> Result<(), SignatureError> {
+            match self.sign_encoded(msg, VerificationRelation::AssertionMethod)? == encoded_sig {
+                true => Ok(()),
+                false => Err(SignatureError::new(ErrorKind::Uncategorized)),
+            }
+        }
+    }
+
+    fn create_registry() -> SuiteRegistry {
+        let mut registry = SuiteRegistry::default();
+        registry.register(Suite::new(
+            "Reversed2022",
+            vec![KeyType::X25519],
+            |_| Ok(Box::new(ReversedSigner)),
+            |_| Ok(Box::new(ReversedSigner)),
+        ));
+        return registry;
+    }
+
+    fn options() -> ValidationOptions {
+        return ValidationOptions {
+            strict: false,
+            ..Default::default()
+        };
+    }
+
+    #[rstest::rstest]
+    #[case::ed25519(signature::suite::PROOF_TYPE, KeyType::Ed25519)]
+    #[case::custom("Reversed2022", KeyType::X25519)]
+    fn test_sign_and_verify(#[case] name: &str, #[case] key_type: KeyType) {
+        let registry = create_registry();
+        assert_eq!(
+            registry.names(),
+            vec![signature::suite::PROOF_TYPE, "Reversed2022"]
+        );
+        let seed = [7u8; 32];
+        let signer = registry.signer(name, &seed).unwrap();
+        let public_key = signature::signer::Ed25519DidSigner::from_seed(seed)
+            .public_key_jwk()
+            .public_key()
+            .unwrap();
+
+        let doc = serde_json::json!({ "name": "document" });
+        let relation = VerificationRelation::AssertionMethod;
+        let proof =
+            create_data_integrity_proof_dyn(signer.as_ref(), doc.clone(), relation, &options())
+                .unwrap();
+        assert_eq!(proof.suite(), name);
+
+        let verifier = registry
+            .verifier(proof.suite(), key_type, &public_key)
+            .unwrap();
+        assert!(verify_proof_value_dyn(doc.clone(), &proof, verifier.as_ref()).is_ok());
+        let tampered = serde_json::json!({ "name": "tampered" });
+        assert!(verify_proof_value_dyn(tampered, &proof, verifier.as_ref()).is_err());
+    }
+
+    #[test]
+    fn test_verifier_of_another_suite() {
+        let registry = create_registry();
+        let signer = registry
+            .signer(signature::suite::PROOF_TYPE, &[7u8; 32])
+            .unwrap();
+        let doc = serde_json::json!({ "name": "document" });
+        let proof = create_data_integrity_proof_dyn(
+            signer.as_ref(),
+            doc.clone(),
+            VerificationRelation::AssertionMethod,
+            &options(),
+        )
+        .unwrap();
+
+        let verifier = registry
+            .verifier("Reversed2022", KeyType::X25519, &[])
+            .unwrap();
+        assert!(verify_proof_value_dyn(doc, &proof, verifier.as_ref()).is_err());
+    }
+
+    #[rstest::rstest]
+    #[case::unknown_suite(
+        "BbsBlsSignature2020",
+        KeyType::Bls12381G2,
+        ErrorKind::UnsupportedSuite
+    )]
+    #[case::unsupported_key_type(
+        signature::suite::PROOF_TYPE,
+        KeyType::Secp256k1,
+        ErrorKind::InvalidKey
+    )]
+    fn test_verifier_errors(
+        #[case] name: &str,
+        #[case] key_type: KeyType,
+        #[case] expect_kind: ErrorKind,
+    ) {
+        let res = SuiteRegistry::default().verifier(name, key_type, &[0u8; 32]);
+        assert_eq!(res.err().unwrap().kind, expect_kind);
+    }
+
+    #[test]
+    fn test_signer_invalid_key() {
+        let res = SuiteRegistry::default().signer(signature::suite::PROOF_TYPE, &[0u8; 16]);
+        assert_eq!(res.err().unwrap().kind, ErrorKind::InvalidKey);
+        let res = SuiteRegistry::new().signer(signature::suite::PROOF_TYPE, &[0u8; 32]);
+        assert_eq!(res.err().unwrap().kind, ErrorKind::UnsupportedSuite);
+    }
+}
@@ -0,0 +1,547 @@
+use crate::did::{Did, DidDocument, DidUrl};
+use crate::error::{ErrorKind, ResolverError};
+use crate::DIDResolver;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+mod cache;
+mod memory;
+pub use cache::CachingResolver;
+pub use memory::InMemoryResolver;
+
+pub const CONTENT_TYPE_DID_JSON: &str = "application/did+json";
+pub const CONTENT_TYPE_DID_LD_JSON: &str = "application/did+ld+json";
+
+/// Options of a DID Resolution request.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResolutionOptions {
+    /// The media type of the requested representation, `application/did+ld+json` when not set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub accept: Option<String>,
+    /// Resolve the version of the document with this `versionId` instead of the latest version.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version_id: Option<String>,
+    /// Resolve the version of the document that was current at this RFC 3339 time instead of the latest version.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version_time: Option<String>,
+    #[serde(flatten)]
+    pub property_set: HashMap<String, Value>,
+}
+
+impl ResolutionOptions {
+    /// Options selecting the version named by the `versionId` and `versionTime` parameters of a DID URL.
+    pub fn from_did_url(url: &DidUrl) -> Self {
+        return Self {
+            version_id: url.version_id(),
+            version_time: url.version_time(),
+            ..Default::default()
+        };
+    }
+
+    /// Whether the options select a version other than the latest.
+    pub fn is_historical(&self) -> bool {
+        return self.version_id.is_some() || self.version_time.is_some();
+    }
+
+    /// The content type of the representation to return for the `accept` option.
+    pub fn content_type(&self) -> Result<&'static str, ResolverError> {
+        match self.accept.as_deref() {
+            None | Some("*/*") | Some(CONTENT_TYPE_DID_LD_JSON) => Ok(CONTENT_TYPE_DID_LD_JSON),
+            Some(CONTENT_TYPE_DID_JSON) | Some("application/json") => Ok(CONTENT_TYPE_DID_JSON),
+            Some(accept) => Err(ResolverError::new(
+                format!("representation {accept} is not supported"),
+                ErrorKind::RepresentationNotSupported,
+            )),
+        }
+    }
+}
+
+/// `didResolutionMetadata` of a DID Resolution result.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResolutionMetadata {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_type: Option<String>,
+    /// DID Resolution error code such as `notFound`, `invalidDid` or `deactivated`, see `ErrorKind::code`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error_message: Option<String>,
+    #[serde(flatten)]
+    pub property_set: HashMap<String, Value>,
+}
+
+/// `didDocumentMetadata` of a DID Resolution result.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DocumentMetadata {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deactivated: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_update: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_version_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub equivalent_id: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub canonical_id: Option<String>,
+    #[serde(flatten)]
+    pub property_set: HashMap<String, Value>,
+}
+
+/// The result of resolving a DID: the document along with the resolution and document metadata.
+/// Failed resolutions carry no document and report the error code in `did_resolution_metadata.error`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResolutionResult {
+    pub did_resolution_metadata: ResolutionMetadata,
+    pub did_document: Option<DidDocument>,
+    pub did_document_metadata: DocumentMetadata,
+}
+
+impl ResolutionResult {
+    /// Successful resolution of `document` in the representation requested by `options`.
+    pub fn from_document(document: DidDocument, options: &ResolutionOptions) -> Self {
+        return match options.content_type() {
+            Ok(content_type) => Self {
+                did_resolution_metadata: ResolutionMetadata {
+                    content_type: Some(content_type.to_string()),
+                    ..Default::default()
+                },
+                did_document: Some(document),
+                did_document_metadata: DocumentMetadata::default(),
+            },
+            Err(e) => Self::from_error(e),
+        };
+    }
+
+    pub fn from_error(error: ResolverError) -> Self {
+        return Self {
+            did_resolution_metadata: ResolutionMetadata {
+                error: Some(error.kind.code().to_string()),
+                error_message: Some(error.message),
+                ..Default::default()
+            },
+            did_document: None,
+            did_document_metadata: DocumentMetadata::default(),
+        };
+    }
+
+    pub fn from_result(
+        result: Result<DidDocument, ResolverError>,
+        options: &ResolutionOptions,
+    ) -> Self {
+        return match result {
+            Ok(document) => Self::from_document(document, options),
+            Err(e) => Self::from_error(e),
+        };
+    }
+
+    pub fn with_document_metadata(mut self, metadata: DocumentMetadata) -> Self {
+        self.did_document_metadata = metadata;
+        return self;
+    }
+
+    /// The resolution error, if any. A deactivated DID is reported as `ErrorKind::Deactivated`.
+    pub fn error(&self) -> Option<ResolverError> {
+        if let Some(code) = &self.did_resolution_metadata.error {
+            let message = self
+                .did_resolution_metadata
+                .error_message
+                .clone()
+                .unwrap_or_else(|| code.clone());
+            return Some(ResolverError::new(message, ErrorKind::from_code(code)));
+        }
+        if self.did_document_metadata.deactivated == Some(true) {
+            return Some(ResolverError::new(
+                "DID has been deactivated",
+                ErrorKind::Deactivated,
+            ));
+        }
+        return None;
+    }
+
+    /// The resolved document, failing on resolution errors and deactivated DIDs.
+    pub fn into_document(self) -> Result<DidDocument, ResolverError> {
+        if let Some(e) = self.error() {
+            return Err(e);
+        }
+        return self
+            .did_document
+            .ok_or_else(|| ResolverError::new("Document not found", ErrorKind::DocumentNotFound));
+    }
+}
+
+/// Universal resolver dispatching each DID to the resolver registered for its method.
+/// DIDs of methods without a registered resolver fail with `ErrorKind::MethodNotSupported`,
+/// the `methodNotSupported` error of DID Resolution.
+#[derive(Clone, Default)]
+pub struct MultiResolver {
+    resolvers: HashMap<String, Arc<dyn DIDResolver>>,
+}
+
+impl MultiResolver {
+    pub fn new() -> Self {
+        return Self::default();
+    }
+
+    /// Register `resolver` for the method returned by its `get_method`, replacing any resolver registered for that method.
+    pub fn register(&mut self, resolver: impl DIDResolver + 'static) {
+        self.register_method(resolver.get_method(), resolver);
+    }
+
+    /// Register `resolver` for `method`, for resolvers able to handle more than the method returned by their `get_method`.
+    pub fn register_method(
+        &mut self,
+        method: impl Into<String>,
+        resolver: impl DIDResolver + 'static,
+    ) {
+        self.resolvers.insert(method.into(), Arc::new(resolver));
+    }
+
+    pub fn with_resolver(mut self, resolver: impl DIDResolver + 'static) -> Self {
+        self.register(resolver);
+        return self;
+    }
+
+    /// The methods with a registered resolver, sorted by name.
+    pub fn methods(&self) -> Vec<&str> {
+        let mut methods: Vec<&str> = self.resolvers.keys().map(String::as_str).collect();
+        methods.sort_unstable();
+        return methods;
+    }
+
+    pub fn supports(&self, method: &str) -> bool {
+        return self.resolvers.contains_key(method);
+    }
+
+    fn resolver(&self, did: &str) -> Result<&Arc<dyn DIDResolver>, ResolverError> {
+        let parsed = Did::parse(did)
+            .map_err(|e| ResolverError::new(e.to_string(), ErrorKind::InvalidDid))?;
+        return self.resolvers.get(parsed.method()).ok_or_else(|| {
+            ResolverError::new(
+                format!("no resolver registered for did:{}", parsed.method()),
+                ErrorKind::MethodNotSupported,
+            )
+        });
+    }
+}
+
+impl std::fmt::Debug for MultiResolver {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("MultiResolver")
+            .field("methods", &self.methods())
+            .finish()
+    }
+}
+
+#[async_trait::async_trait]
+impl DIDResolver for MultiResolver {
+    async fn resolve(&self, did: &str, options: &ResolutionOptions) -> ResolutionResult {
+        match self.resolver(did) {
+            Ok(resolver) => resolver.resolve(did, options).await,
+            Err(e) => ResolutionResult::from_error(e),
+        }
+    }
+
+    async fn create(&self, did: &str, doc: DidDocument) -> Result<(), ResolverError> {
+        self.resolver(did)?.create(did, doc).await
+    }
+
+    async fn update(&self, did: &str, doc: DidDocument) -> Result<(), ResolverError> {
+        self.resolver(did)?.update(did, doc).await
+    }
+
+    async fn deactivate(&self, did: &str) -> Result<(), ResolverError> {
+        self.resolver(did)?.deactivate(did).await
+    }
+
+    /// A `MultiResolver` is not bound to a single method, the method of each DID selects the resolver.
+    fn get_method(&self) -> &'static str {
+        return "*";
+    }
+
+    /// Having no method of its own, the multiplexer needs `public_key` as a DID, e.g. `did:knox:z6Mk…`, whose method
+    /// selects the resolver building the verification method. DIDs of methods without a registered resolver, and
+    /// anything else, are used as given.
+    fn create_verification_method(&self, public_key: String, key_id: String) -> String {
+        return match Did::parse(&public_key) {
+            Ok(did) => match self.resolvers.get(did.method()) {
+                Some(resolver) => resolver
+                    .create_verification_method(did.method_specific_id().to_string(), key_id),
+                None => format!("{public_key}#{key_id}"),
+            },
+            Err(_) => format!("{public_key}#{key_id}"),
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::did::DidDocument;
+    use crate::error::{ErrorKind, ResolverError};
+    use crate::resolver::{
+        DocumentMetadata, MultiResolver, ResolutionOptions, ResolutionResult,
+        CONTENT_TYPE_DID_JSON, CONTENT_TYPE_DID_LD_JSON,
+    };
+    use crate::DIDResolver;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::task::Poll;
+
+    macro_rules! aw {
+        ($e:expr) => {
+            tokio_test::block_on($e)
+        };
+    }
+
+    struct ExampleResolver {}
+
+    #[async_trait::async_trait]
+    impl DIDResolver for ExampleResolver {
+        async fn resolve(&self, did: &str, options: &ResolutionOptions) -> ResolutionResult {
+            ResolutionResult::from_document(DidDocument::new(did), options)
+        }
+
+        async fn create(&self, _did: &str, _doc: DidDocument) -> Result<(), ResolverError> {
+            Ok(())
+        }
+
+        fn get_method(&self) -> &'static str {
+            return "example";
+        }
+    }
+
+    struct FailingResolver {}
+
+    #[async_trait::async_trait]
+    impl DIDResolver for FailingResolver {
+        async fn resolve(&self, did: &str, _options: &ResolutionOptions) -> ResolutionResult {
+            ResolutionResult::from_error(ResolverError::new(did, ErrorKind::DocumentNotFound))
+        }
+
+        async fn create(&self, did: &str, _doc: DidDocument) -> Result<(), ResolverError> {
+            Err(ResolverError::new(did, ErrorKind::NetworkFailure))
+        }
+
+        fn get_method(&self) -> &'static str {
+            return "failing";
+        }
+
+        fn create_verification_method(&self, public_key: String, key_id: String) -> String {
+            return format!("did:failing:{public_key}#failing-{key_id}");
+        }
+    }
+
+    fn create_resolver() -> MultiResolver {
+        let mut resolver = MultiResolver::new().with_resolver(ExampleResolver {});
+        resolver.register(FailingResolver {});
+        resolver.register_method("alias", ExampleResolver {});
+        return resolver;
+    }
+
+    #[rstest::rstest]
+    #[case::registered("did:example:123", None)]
+    #[case::registered_under_alias("did:alias:123", None)]
+    #[case::resolver_failure("did:failing:123", Some(ErrorKind::DocumentNotFound))]
+    #[case::unsupported_method("did:web:example.com", Some(ErrorKind::MethodNotSupported))]
+    #[case::invalid_did("not-a-did", Some(ErrorKind::InvalidDid))]
+    fn test_read(#[case] did: &str, #[case] expect_err: Option<ErrorKind>) {
+        let res = aw!(create_resolver().read(did));
+        match expect_err {
+            Some(kind) => assert_eq!(res.unwrap_err().kind, kind),
+            None => assert_eq!(res.unwrap().id, did),
+        }
+    }
+
+    #[test]
+    fn test_read_many() {
+        let dids = [
+            "did:example:1",
+            "did:failing:2",
+            "not-a-did",
+            "did:example:1",
+        ]
+        .map(String::from);
+        let res = aw!(create_resolver().read_many(&dids, 2));
+        assert_eq!(res.len(), 3);
+        assert_eq!(res["did:example:1"].as_ref().unwrap().id, "did:example:1");
+        assert_eq!(
+            res["did:failing:2"].as_ref().unwrap_err().kind,
+            ErrorKind::DocumentNotFound
+        );
+        assert_eq!(
+            res["not-a-did"].as_ref().unwrap_err().kind,
+            ErrorKind::InvalidDid
+        );
+    }
+
+    /// Resolver recording the highest number of resolutions in progress at the same time.
+    #[derive(Default)]
+    struct ConcurrencyResolver {
+        in_progress: AtomicUsize,
+        max_in_progress: AtomicUsize,
+    }
+
+    #[async_trait::async_trait]
+    impl DIDResolver for ConcurrencyResolver {
+        async fn resolve(&self, did: &str, options: &ResolutionOptions) -> ResolutionResult {
+            let in_progress = self.in_progress.fetch_add(1, Ordering::SeqCst) + 1;
+            self.max_in_progress
+                .fetch_max(in_progress, Ordering::SeqCst);
+
+            // let the other resolutions start before this one completes
+            let mut yielded = false;
+            futures::future::poll_fn(|cx| match yielded {
+                true => Poll::Ready(()),
+                false => {
+                    yielded = true;
+                    cx.waker().wake_by_ref();
+                    Poll::Pending
+                }
+            })
+            .await;
+
+            self.in_progress.fetch_sub(1, Ordering::SeqCst);
+            return ResolutionResult::from_document(DidDocument::new(did), options);
+        }
+
+        async fn create(&self, _did: &str, _doc: DidDocument) -> Result<(), ResolverError> {
+            Ok(())
+        }
+
+        fn get_method(&self) -> &'static str {
+            return "example";
+        }
+    }
+
+    #[rstest::rstest]
+    #[case::limited(3, 3)]
+    #[case::unlimited(100, 10)]
+    #[case::zero(0, 1)]
+    fn test_read_many_concurrency(#[case] concurrency: usize, #[case] expect_max: usize) {
+        let resolver = ConcurrencyResolver::default();
+        let dids: Vec<String> = (0..10).map(|i| format!("did:example:{i}")).collect();
+        let res = aw!(resolver.read_many(&dids, concurrency));
+        assert_eq!(res.len(), 10);
+        assert!(res.values().all(Result::is_ok));
+        assert_eq!(resolver.max_in_progress.load(Ordering::SeqCst), expect_max);
+    }
+
+    #[rstest::rstest]
+    #[case::registered("did:example:123", "did:example:123#key-1")]
+    #[case::routed("did:failing:123", "did:failing:123#failing-key-1")]
+    #[case::unsupported_method("did:web:example.com", "did:web:example.com#key-1")]
+    fn test_create_verification_method(#[case] did: &str, #[case] expect: &str) {
+        let resolver = create_resolver();
+        assert_eq!(
+            resolver.create_verification_method(did.to_string(), "key-1".to_string()),
+            expect
+        );
+    }
+
+    #[rstest::rstest]
+    #[case::registered("did:example:123", None)]
+    #[case::resolver_failure("did:failing:123", Some(ErrorKind::NetworkFailure))]
+    #[case::unsupported_method("did:web:example.com", Some(ErrorKind::MethodNotSupported))]
+    fn test_create(#[case] did: &str, #[case] expect_err: Option<ErrorKind>) {
+        let res = aw!(create_resolver().create(did, DidDocument::new(did)));
+        assert_eq!(res.err().map(|e| e.kind), expect_err);
+    }
+
+    #[rstest::rstest]
+    #[case::unsupported_operation("did:example:123", ErrorKind::OperationNotSupported)]
+    #[case::unsupported_method("did:web:example.com", ErrorKind::MethodNotSupported)]
+    fn test_update_deactivate(#[case] did: &str, #[case] expect_err: ErrorKind) {
+        let resolver = create_resolver();
+        let res = aw!(resolver.update(did, DidDocument::new(did)));
+        assert_eq!(res.unwrap_err().kind, expect_err);
+        let res = aw!(resolver.deactivate(did));
+        assert_eq!(res.unwrap_err().kind, expect_err);
+    }
+
+    #[test]
+    fn test_methods() {
+        let resolver = create_resolver();
+        assert_eq!(resolver.methods(), vec!["alias", "example", "failing"]);
+        assert!(resolver.supports("example"));
+        assert!(!resolver.supports("web"));
+    }
+
+    #[test]
+    fn test_dyn_resolver() {
+        let resolvers: Vec<Box<dyn DIDResolver>> =
+            vec![Box::new(ExampleResolver {}), Box::new(create_resolver())];
+        for resolver in resolvers {
+            assert!(aw!(resolver.read("did:example:123")).is_ok());
+        }
+    }
+
+    #[rstest::rstest]
+    #[case::default(None, Some(CONTENT_TYPE_DID_LD_JSON), None)]
+    #[case::any(Some("*/*"), Some(CONTENT_TYPE_DID_LD_JSON), None)]
+    #[case::json(Some(CONTENT_TYPE_DID_JSON), Some(CONTENT_TYPE_DID_JSON), None)]
+    #[case::unsupported(Some("text/html"), None, Some("representationNotSupported"))]
+    fn test_resolve_content_type(
+        #[case] accept: Option<&str>,
+        #[case] expect_content_type: Option<&str>,
+        #[case] expect_error: Option<&str>,
+    ) {
+        let options = ResolutionOptions {
+            accept: accept.map(String::from),
+            ..Default::default()
+        };
+        let res = aw!(create_resolver().resolve("did:example:123", &options));
+        assert_eq!(
+            res.did_resolution_metadata.content_type.as_deref(),
+            expect_content_type
+        );
+        assert_eq!(res.did_resolution_metadata.error.as_deref(), expect_error);
+        assert_eq!(res.did_document.is_some(), expect_error.is_none());
+    }
+
+    #[test]
+    fn test_resolution_result_serialization() {
+        let res = ResolutionResult::from_error(ResolverError::new(
+            "did:example:123 not found",
+            ErrorKind::DocumentNotFound,
+        ));
+        assert_eq!(
+            serde_json::to_value(&res).unwrap(),
+            serde_json::json!({
+                "didResolutionMetadata": {"error": "notFound", "errorMessage": "did:example:123 not found"},
+                "didDocument": null,
+                "didDocumentMetadata": {}
+            })
+        );
+        assert_eq!(
+            res.into_document().unwrap_err().kind,
+            ErrorKind::DocumentNotFound
+        );
+    }
+
+    #[test]
+    fn test_deactivated() {
+        let res = ResolutionResult::from_document(
+            DidDocument::new("did:example:123"),
+            &ResolutionOptions::default(),
+        )
+        .with_document_metadata(DocumentMetadata {
+            deactivated: Some(true),
+            ..Default::default()
+        });
+        assert_eq!(res.error().unwrap().kind, ErrorKind::Deactivated);
+        assert_eq!(
+            res.into_document().unwrap_err().kind,
+            ErrorKind::Deactivated
+        );
+    }
+}
@@ -0,0 +1,680 @@
+use crate::did::DidDocument;
+use crate::error::{ErrorKind, ResolverError};
+use crate::resolver::{DocumentMetadata, ResolutionOptions, ResolutionResult};
+use crate::DIDResolver;
+use futures::future::{BoxFuture, FutureExt, Shared};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+const DEFAULT_CAPACITY: usize = 1024;
+const DEFAULT_TTL: Duration = Duration::from_secs(300);
+const DEFAULT_NEGATIVE_TTL: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone)]
+struct CacheEntry {
+    did: String,
+    result: ResolutionResult,
+    expires: Instant,
+    last_used: u64,
+}
+
+#[derive(Default)]
+struct CacheState {
+    entries: HashMap<String, CacheEntry>,
+    /// Lookups in flight, keyed like `entries` and tagged with the id of the lookup.
+    pending: HashMap<String, (u64, Shared<BoxFuture<'static, ResolutionResult>>)>,
+    tick: u64,
+}
+
+/// Resolver caching the results of another resolver in a least recently used cache.
+/// Successful resolutions are kept for the TTL, or until the `nextUpdate` of their document metadata if that is sooner,
+/// `notFound` results for the negative TTL and other errors are not cached.
+/// Concurrent lookups of the same DID share a single call to the wrapped resolver.
+/// Creating, updating or deactivating a DID through the cache invalidates its entries, clones share the same cache.
+pub struct CachingResolver<R: DIDResolver> {
+    resolver: Arc<R>,
+    capacity: usize,
+    ttl: Duration,
+    negative_ttl: Duration,
+    state: Arc<Mutex<CacheState>>,
+}
+
+impl<R: DIDResolver + 'static> CachingResolver<R> {
+    pub fn new(resolver: R) -> Self {
+        return Self {
+            resolver: Arc::new(resolver),
+            capacity: DEFAULT_CAPACITY,
+            ttl: DEFAULT_TTL,
+            negative_ttl: DEFAULT_NEGATIVE_TTL,
+            state: Arc::new(Mutex::new(CacheState::default())),
+        };
+    }
+
+    /// The maximum number of cached results, the least recently used result is evicted first.
+    pub fn with_capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity;
+        return self;
+    }
+
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        return self;
+    }
+
+    /// How long a `notFound` result is cached.
+    pub fn with_negative_ttl(mut self, negative_ttl: Duration) -> Self {
+        self.negative_ttl = negative_ttl;
+        return self;
+    }
+
+    pub fn inner(&self) -> &R {
+        return &self.resolver;
+    }
+
+    /// Drop the cached results of `did` for all resolution options. Lookups of `did` in flight are still answered
+    /// but their results are not cached, as they may predate the change that invalidated them.
+    pub fn invalidate(&self, did: &str) {
+        let mut state = self.state.lock().unwrap();
+        state.entries.retain(|_, entry| entry.did != did);
+        let prefix = format!("{did} ");
+        state.pending.retain(|key, _| !key.starts_with(&prefix));
+    }
+
+    pub fn clear(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.entries.clear();
+        state.pending.clear();
+    }
+
+    /// The number of cached results, including expired results that have not been evicted yet.
+    pub fn len(&self) -> usize {
+        return self.state.lock().unwrap().entries.len();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.len() == 0;
+    }
+
+    /// How long `result` may be cached, `None` if it must not be cached.
+    fn entry_ttl(&self, result: &ResolutionResult) -> Option<Duration> {
+        match result.did_resolution_metadata.error.as_deref() {
+            None => {}
+            Some(code) if ErrorKind::from_code(code) == ErrorKind::DocumentNotFound => {
+                return Some(self.negative_ttl)
+            }
+            Some(_) => return None,
+        }
+
+        let next_update = result
+            .did_document_metadata
+            .next_update
+            .as_deref()
+            .and_then(|time| chrono::DateTime::parse_from_rfc3339(time).ok())
+            .and_then(|time| {
+                (time.with_timezone(&chrono::Utc) - chrono::Utc::now())
+                    .to_std()
+                    .ok()
+            });
+        return match next_update {
+            Some(next_update) => Some(next_update.min(self.ttl)),
+            None => Some(self.ttl),
+        };
+    }
+
+    /// A cached version of `did` that was current at the `versionTime` of `options`, cached either by its `versionId`
+    /// or as the latest version, so that lookups at different times of the same version share one result.
+    fn find_version(
+        state: &mut CacheState,
+        did: &str,
+        options: &ResolutionOptions,
+    ) -> Option<ResolutionResult> {
+        let time = options
+            .version_time
+            .as_deref()
+            .and_then(|time| chrono::DateTime::parse_from_rfc3339(time).ok())?;
+        let latest = cache_key(did, &version_options(options, None));
+        let now = Instant::now();
+        let key = state
+            .entries
+            .iter()
+            .find(|(key, entry)| {
+                let metadata = &entry.result.did_document_metadata;
+                let version = version_options(options, metadata.version_id.clone());
+                return entry.did == did
+                    && entry.expires > now
+                    && entry.result.did_document.is_some()
+                    && (**key == latest || **key == cache_key(did, &version))
+                    && is_current_at(metadata, time);
+            })
+            .map(|(key, _)| key.clone())?;
+
+        state.tick += 1;
+        let tick = state.tick;
+        let entry = state.entries.get_mut(&key)?;
+        entry.last_used = tick;
+        return Some(entry.result.clone());
+    }
+
+    fn store(&self, state: &mut CacheState, key: String, did: &str, result: &ResolutionResult) {
+        let ttl = match self.entry_ttl(result) {
+            Some(ttl) if !ttl.is_zero() && self.capacity > 0 => ttl,
+            _ => return,
+        };
+
+        let now = Instant::now();
+        state.entries.retain(|_, entry| entry.expires > now);
+        if state.entries.len() >= self.capacity && !state.entries.contains_key(&key) {
+            let lru = state
+                .entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(key, _)| key.clone());
+            if let Some(lru) = lru {
+                state.entries.remove(&lru);
+            }
+        }
+
+        state.tick += 1;
+        let entry = CacheEntry {
+            did: did.to_string(),
+            result: result.clone(),
+            expires: now + ttl,
+            last_used: state.tick,
+        };
+        state.entries.insert(key, entry);
+    }
+}
+
+/// Results are cached per DID and resolution options. Lookups by `versionTime` alone are cached under the
+/// `versionId` they resolved to, see `find_version`.
+fn cache_key(did: &str, options: &ResolutionOptions) -> String {
+    let options = serde_json::to_value(options).unwrap_or_default();
+    return format!("{did} {options}");
+}
+
+/// The options resolving version `version_id` of the document, or the latest version if `None`, instead of the
+/// version at the `versionTime` of `options`.
+fn version_options(options: &ResolutionOptions, version_id: Option<String>) -> ResolutionOptions {
+    return ResolutionOptions {
+        version_id,
+        version_time: None,
+        ..options.clone()
+    };
+}
+
+/// Whether the version of the document described by `metadata` was current at `time`.
+fn is_current_at(metadata: &DocumentMetadata, time: chrono::DateTime<chrono::FixedOffset>) -> bool {
+    let parse = |time: &Option<String>| {
+        time.as_deref()
+            .and_then(|time| chrono::DateTime::parse_from_rfc3339(time).ok())
+    };
+    let since = match parse(&metadata.updated).or_else(|| parse(&metadata.created)) {
+        Some(since) => since,
+        None => return false,
+    };
+    return since <= time && !matches!(parse(&metadata.next_update), Some(until) if until <= time);
+}
+
+impl<R: DIDResolver> Clone for CachingResolver<R> {
+    fn clone(&self) -> Self {
+        return Self {
+            resolver: self.resolver.clone(),
+            capacity: self.capacity,
+            ttl: self.ttl,
+            negative_ttl: self.negative_ttl,
+            state: self.state.clone(),
+        };
+    }
+}
+
+impl<R: DIDResolver> std::fmt::Debug for CachingResolver<R> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("CachingResolver")
+            .field("method", &self.resolver.get_method())
+            .field("capacity", &self.capacity)
+            .field("ttl", &self.ttl)
+            .field("negative_ttl", &self.negative_ttl)
+            .finish()
+    }
+}
+
+#[async_trait::async_trait]
+impl<R: DIDResolver + 'static> DIDResolver for CachingResolver<R> {
+    async fn resolve(&self, did: &str, options: &ResolutionOptions) -> ResolutionResult {
+        let key = cache_key(did, options);
+        let by_time = options.version_id.is_none() && options.version_time.is_some();
+        let (id, lookup) = {
+            let mut state = self.state.lock().unwrap();
+            if by_time {
+                if let Some(result) = Self::find_version(&mut state, did, options) {
+                    return result;
+                }
+            }
+            state.tick += 1;
+            let tick = state.tick;
+            match state.entries.get_mut(&key) {
+                Some(entry) if entry.expires > Instant::now() => {
+                    entry.last_used = tick;
+                    return entry.result.clone();
+                }
+                Some(_) => {
+                    state.entries.remove(&key);
+                }
+                None => {}
+            }
+
+            match state.pending.get(&key) {
+                Some(pending) => pending.clone(),
+                None => {
+                    let resolver = self.resolver.clone();
+                    let (did, options) = (did.to_string(), options.clone());
+                    let lookup = async move { resolver.resolve(&did, &options).await }
+                        .boxed()
+                        .shared();
+                    state.pending.insert(key.clone(), (tick, lookup.clone()));
+                    (tick, lookup)
+                }
+            }
+        };
+
+        let result = lookup.await;
+
+        let mut state = self.state.lock().unwrap();
+        if state.pending.get(&key).map(|(pending, _)| *pending) == Some(id) {
+            state.pending.remove(&key);
+            let version_id = result.did_document_metadata.version_id.clone();
+            match (by_time, version_id) {
+                (false, _) => self.store(&mut state, key, did, &result),
+                (true, Some(version_id)) if result.did_document.is_some() => {
+                    let key = cache_key(did, &version_options(options, Some(version_id)));
+                    self.store(&mut state, key, did, &result);
+                }
+                // the errors of lookups by time are not cached, the time may be before the DID was created
+                (true, _) => {}
+            }
+        }
+        return result;
+    }
+
+    async fn create(&self, did: &str, doc: DidDocument) -> Result<(), ResolverError> {
+        let res = self.resolver.create(did, doc).await;
+        self.invalidate(did);
+        return res;
+    }
+
+    async fn update(&self, did: &str, doc: DidDocument) -> Result<(), ResolverError> {
+        let res = self.resolver.update(did, doc).await;
+        self.invalidate(did);
+        return res;
+    }
+
+    async fn deactivate(&self, did: &str) -> Result<(), ResolverError> {
+        let res = self.resolver.deactivate(did).await;
+        self.invalidate(did);
+        return res;
+    }
+
+    fn get_method(&self) -> &'static str {
+        return self.resolver.get_method();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CachingResolver;
+    use crate::did::DidDocument;
+    use crate::error::{ErrorKind, ResolverError};
+    use crate::resolver::{InMemoryResolver, ResolutionOptions, ResolutionResult};
+    use crate::DIDResolver;
+    use signature::signer::DIDSigner;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    macro_rules! aw {
+        ($e:expr) => {
+            tokio_test::block_on($e)
+        };
+    }
+
+    const DID: &str = "did:example:123";
+
+    /// Resolver counting its lookups, which yield once before completing so that concurrent lookups overlap
+    /// and changes made meanwhile are not seen by the lookup.
+    #[derive(Clone)]
+    struct CountingResolver {
+        inner: InMemoryResolver,
+        lookups: Arc<AtomicUsize>,
+        next_update: Option<String>,
+    }
+
+    impl CountingResolver {
+        fn new() -> Self {
+            return Self {
+                inner: InMemoryResolver::new("example"),
+                lookups: Arc::new(AtomicUsize::new(0)),
+                next_update: None,
+            };
+        }
+
+        fn lookups(&self) -> usize {
+            return self.lookups.load(Ordering::SeqCst);
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl DIDResolver for CountingResolver {
+        async fn resolve(&self, did: &str, options: &ResolutionOptions) -> ResolutionResult {
+            self.lookups.fetch_add(1, Ordering::SeqCst);
+            let mut res = self.inner.resolve(did, options).await;
+            let mut yielded = false;
+            futures::future::poll_fn(|cx| match yielded {
+                true => std::task::Poll::Ready(()),
+                false => {
+                    yielded = true;
+                    cx.waker().wake_by_ref();
+                    std::task::Poll::Pending
+                }
+            })
+            .await;
+
+            if res.did_document.is_some() && self.next_update.is_some() {
+                res.did_document_metadata.next_update = self.next_update.clone();
+            }
+            return res;
+        }
+
+        async fn create(&self, did: &str, doc: DidDocument) -> Result<(), ResolverError> {
+            self.inner.create(did, doc).await
+        }
+
+        async fn update(&self, did: &str, doc: DidDocument) -> Result<(), ResolverError> {
+            self.inner.update(did, doc).await
+        }
+
+        fn get_method(&self) -> &'static str {
+            return self.inner.get_method();
+        }
+    }
+
+    fn create_resolver() -> CountingResolver {
+        let resolver = CountingResolver::new();
+        aw!(resolver.create(DID, DidDocument::new(DID))).unwrap();
+        aw!(resolver.create("did:example:456", DidDocument::new("did:example:456"))).unwrap();
+        return resolver;
+    }
+
+    #[rstest::rstest]
+    #[case::cached(Duration::from_secs(60), None, 1)]
+    #[case::expired(Duration::ZERO, None, 3)]
+    #[case::next_update_in_the_past(
+        Duration::from_secs(60),
+        Some("2022-06-01T00:00:00Z".to_string()),
+        1
+    )]
+    #[case::next_update_in_the_future(
+        Duration::from_secs(60),
+        Some((chrono::Utc::now() + chrono::Duration::hours(1)).to_rfc3339()),
+        1
+    )]
+    fn test_ttl(
+        #[case] ttl: Duration,
+        #[case] next_update: Option<String>,
+        #[case] expect_lookups: usize,
+    ) {
+        let mut resolver = create_resolver();
+        resolver.next_update = next_update;
+        let cache = CachingResolver::new(resolver.clone()).with_ttl(ttl);
+
+        for _ in 0..3 {
+            assert_eq!(aw!(cache.read(DID)).unwrap().id, DID);
+            std::thread::sleep(Duration::from_millis(2));
+        }
+        assert_eq!(resolver.lookups(), expect_lookups);
+    }
+
+    #[rstest::rstest]
+    #[case::document(None, None, Some(Duration::from_secs(7200)))]
+    #[case::next_update_caps_ttl(
+        None,
+        Some((chrono::Utc::now() + chrono::Duration::hours(1)).to_rfc3339()),
+        Some(Duration::from_secs(3600))
+    )]
+    #[case::superseded_version(None, Some("2022-06-01T00:00:00Z".to_string()), Some(Duration::from_secs(7200)))]
+    #[case::not_found(Some(ErrorKind::DocumentNotFound), None, Some(Duration::from_secs(60)))]
+    #[case::network_failure(Some(ErrorKind::NetworkFailure), None, None)]
+    fn test_entry_ttl(
+        #[case] error: Option<ErrorKind>,
+        #[case] next_update: Option<String>,
+        #[case] expect_ttl: Option<Duration>,
+    ) {
+        let cache = CachingResolver::new(CountingResolver::new())
+            .with_ttl(Duration::from_secs(7200))
+            .with_negative_ttl(Duration::from_secs(60));
+        let res = match error {
+            Some(kind) => ResolutionResult::from_error(ResolverError::new("message", kind)),
+            None => {
+                let mut res =
+                    ResolutionResult::from_document(DidDocument::new(DID), &Default::default());
+                res.did_document_metadata.next_update = next_update;
+                res
+            }
+        };
+
+        // the TTL derived from nextUpdate is slightly shorter than the hour it is set ahead
+        match (cache.entry_ttl(&res), expect_ttl) {
+            (Some(ttl), Some(expect_ttl)) => {
+                assert!(ttl <= expect_ttl && ttl + Duration::from_secs(60) > expect_ttl)
+            }
+            (ttl, expect_ttl) => assert_eq!(ttl, expect_ttl),
+        }
+    }
+
+    #[test]
+    fn test_options_are_cached_separately() {
+        let resolver = create_resolver();
+        let cache = CachingResolver::new(resolver.clone());
+
+        let options = ResolutionOptions {
+            accept: Some("text/html".to_string()),
+            ..Default::default()
+        };
+        let res = aw!(cache.resolve(DID, &options));
+        assert_eq!(
+            res.error().unwrap().kind,
+            ErrorKind::RepresentationNotSupported
+        );
+        assert!(aw!(cache.read(DID)).is_ok());
+        assert!(aw!(cache.read(DID)).is_ok());
+        assert_eq!(resolver.lookups(), 2);
+    }
+
+    #[rstest::rstest]
+    #[case::negative_cache(Duration::from_secs(60), 1)]
+    #[case::no_negative_cache(Duration::ZERO, 2)]
+    fn test_negative_cache(#[case] negative_ttl: Duration, #[case] expect_lookups: usize) {
+        let resolver = create_resolver();
+        let cache = CachingResolver::new(resolver.clone()).with_negative_ttl(negative_ttl);
+
+        for _ in 0..2 {
+            let res = aw!(cache.read("did:example:789"));
+            assert_eq!(res.unwrap_err().kind, ErrorKind::DocumentNotFound);
+        }
+        assert_eq!(resolver.lookups(), expect_lookups);
+
+        // creating the DID through the cache replaces the cached notFound
+        aw!(cache.create("did:example:789", DidDocument::new("did:example:789"))).unwrap();
+        assert!(aw!(cache.read("did:example:789")).is_ok());
+    }
+
+    #[test]
+    fn test_lru_eviction() {
+        let resolver = create_resolver();
+        let cache = CachingResolver::new(resolver.clone()).with_capacity(1);
+
+        assert!(aw!(cache.read(DID)).is_ok());
+        assert!(aw!(cache.read("did:example:456")).is_ok());
+        assert_eq!(cache.len(), 1);
+        assert!(aw!(cache.read("did:example:456")).is_ok());
+        assert_eq!(resolver.lookups(), 2);
+        assert!(aw!(cache.read(DID)).is_ok());
+        assert_eq!(resolver.lookups(), 3);
+    }
+
+    #[test]
+    fn test_single_flight() {
+        let resolver = create_resolver();
+        let cache = CachingResolver::new(resolver.clone()).with_ttl(Duration::ZERO);
+
+        let (first, second, other) = aw!(async {
+            futures::join!(
+                cache.read(DID),
+                cache.read(DID),
+                cache.read("did:example:456")
+            )
+        });
+        assert_eq!(first.unwrap(), second.unwrap());
+        assert!(other.is_ok());
+        assert_eq!(resolver.lookups(), 2);
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn test_invalidate() {
+        let resolver = create_resolver();
+        let cache = CachingResolver::new(resolver.clone());
+
+        assert!(aw!(cache.read(DID)).is_ok());
+        cache.invalidate(DID);
+        assert!(aw!(cache.read(DID)).is_ok());
+        assert_eq!(resolver.lookups(), 2);
+
+        // updating through the wrapped resolver is only visible once the entry is invalidated
+        let mut doc = DidDocument::new(DID);
+        doc.also_known_as = vec!["https://example.com".to_string()];
+        aw!(resolver.update(DID, doc.clone())).unwrap();
+        assert!(aw!(cache.read(DID)).unwrap().also_known_as.is_empty());
+        aw!(cache.update(DID, doc.clone())).unwrap();
+        assert_eq!(aw!(cache.read(DID)).unwrap(), doc);
+
+        cache.clear();
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn test_version_time() {
+        let resolver = CountingResolver::new();
+        let created = chrono::Utc::now() - chrono::Duration::hours(2);
+        let updated = chrono::Utc::now() - chrono::Duration::hours(1);
+        resolver
+            .inner
+            .insert_version(DID, DidDocument::new(DID), created)
+            .unwrap();
+        let mut doc = DidDocument::new(DID);
+        doc.also_known_as = vec!["https://example.com".to_string()];
+        resolver.inner.insert_version(DID, doc, updated).unwrap();
+        let cache = CachingResolver::new(resolver.clone());
+        let at = |time: chrono::DateTime<chrono::Utc>| ResolutionOptions {
+            version_time: Some(time.to_rfc3339()),
+            ..Default::default()
+        };
+
+        // times within the first version share the result cached under its versionId
+        let res = aw!(cache.resolve(DID, &at(created + chrono::Duration::minutes(10))));
+        assert_eq!(res.did_document_metadata.version_id.as_deref(), Some("1"));
+        let res = aw!(cache.resolve(DID, &at(created + chrono::Duration::minutes(50))));
+        assert_eq!(res.did_document_metadata.version_id.as_deref(), Some("1"));
+        let version = ResolutionOptions {
+            version_id: Some("1".to_string()),
+            ..Default::default()
+        };
+        assert!(aw!(cache.resolve(DID, &version)).did_document.is_some());
+        assert_eq!(resolver.lookups(), 1);
+
+        // times since the last update are answered by the latest version
+        assert!(aw!(cache.read(DID)).is_ok());
+        let res = aw!(cache.resolve(DID, &at(updated + chrono::Duration::minutes(10))));
+        assert_eq!(res.did_document_metadata.version_id.as_deref(), Some("2"));
+        assert_eq!(resolver.lookups(), 2);
+
+        // times before the DID was created are looked up, and their errors not cached
+        for _ in 0..2 {
+            let res = aw!(cache.resolve(DID, &at(created - chrono::Duration::hours(1))));
+            assert!(res.did_document.is_none());
+        }
+        assert_eq!(resolver.lookups(), 4);
+    }
+
+    #[test]
+    fn test_proofs_share_lookup() {
+        let signer = signature::signer::Ed25519DidSigner::new();
+        let verifier = signature::verifier::Ed25519DidVerifier::from(&signer);
+        let relation = signature::suite::VerificationRelation::AssertionMethod;
+        let doc = crate::test_utils::signer_document(&signer, &[relation]);
+        let did = doc.id.clone();
+        let resolver = CountingResolver {
+            inner: InMemoryResolver::new("knox"),
+            ..CountingResolver::new()
+        };
+        let created = chrono::Utc::now() - chrono::Duration::hours(1);
+        resolver.inner.insert_version(&did, doc, created).unwrap();
+        let cache = CachingResolver::new(resolver.clone());
+
+        // each proof names the version of the document current when it was made
+        let credential = serde_json::json!({
+            "@context": ["https://www.w3.org/2018/credentials/v1"],
+            "type": ["VerifiableCredential"],
+            "issuer": did,
+            "issuanceDate": "2010-01-01T19:23:24Z",
+            "credentialSubject": { "id": did }
+        });
+        let sign = |minutes: i64| {
+            let mut proof =
+                crate::proof::create_data_integrity_proof(&signer, credential.clone(), relation)
+                    .unwrap();
+            proof.created = (created + chrono::Duration::minutes(minutes))
+                .to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
+            proof.verification_method = proof
+                .verification_method
+                .replace('#', &format!("?versionTime={}#", proof.created));
+            proof.proof_value = signer
+                .try_encoded_relational_sign(
+                    &crate::proof::signing_input(credential.clone(), &proof),
+                    relation,
+                )
+                .unwrap();
+            let mut signed = credential.clone();
+            signed["proof"] = serde_json::to_value(proof).unwrap();
+            return signed;
+        };
+
+        for minutes in [10, 20] {
+            assert!(aw!(crate::verify_data_integrity_proof(
+                sign(minutes),
+                &cache,
+                &verifier
+            ))
+            .is_ok());
+        }
+        assert_eq!(resolver.lookups(), 1);
+    }
+
+    #[test]
+    fn test_update_during_lookup() {
+        let resolver = create_resolver();
+        let cache = CachingResolver::new(resolver.clone());
+
+        let mut doc = DidDocument::new(DID);
+        doc.also_known_as = vec!["https://example.com".to_string()];
+        let (read, update) =
+            aw!(async { futures::join!(cache.read(DID), cache.update(DID, doc.clone())) });
+        assert!(read.unwrap().also_known_as.is_empty());
+        assert!(update.is_ok());
+
+        // the result of the lookup that started before the update is not cached
+        assert!(cache.is_empty());
+        assert_eq!(aw!(cache.read(DID)).unwrap(), doc);
+        assert_eq!(resolver.lookups(), 2);
+    }
+}
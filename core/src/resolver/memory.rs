@@ -0,0 +1,376 @@
+use crate::did::DidDocument;
+use crate::error::{ErrorKind, ResolverError};
+use crate::resolver::{DocumentMetadata, ResolutionOptions, ResolutionResult};
+use crate::DIDResolver;
+use chrono::{DateTime, SecondsFormat, Utc};
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+#[derive(Debug, Clone)]
+struct DocumentVersion {
+    document: DidDocument,
+    time: DateTime<Utc>,
+    deactivated: bool,
+}
+
+/// Resolver keeping DID documents and their version history in memory, intended for tests and local development.
+/// Versions are numbered from `1` and can be resolved by `versionId` or `versionTime`. Every version of a deactivated DID
+/// is reported as `deactivated`.
+/// Clones of a resolver share the same documents.
+#[derive(Debug, Clone)]
+pub struct InMemoryResolver {
+    method: &'static str,
+    documents: Arc<RwLock<HashMap<String, Vec<DocumentVersion>>>>,
+}
+
+impl InMemoryResolver {
+    pub fn new(method: &'static str) -> Self {
+        return Self {
+            method,
+            documents: Arc::new(RwLock::new(HashMap::new())),
+        };
+    }
+
+    /// Record `document` as a new version of `did` that became current at `time`, creating the DID if needed.
+    /// Versions must be recorded in chronological order.
+    pub fn insert_version(
+        &self,
+        did: &str,
+        document: DidDocument,
+        time: DateTime<Utc>,
+    ) -> Result<(), ResolverError> {
+        check_document(did, &document)?;
+
+        let mut documents = self.documents.write().unwrap();
+        let versions = documents.entry(did.to_string()).or_default();
+        if matches!(versions.last(), Some(last) if last.time > time) {
+            return Err(ResolverError::new(
+                format!("{did} already has a version newer than {time}"),
+                ErrorKind::InvalidData,
+            ));
+        }
+        check_active(did, versions)?;
+        versions.push(DocumentVersion {
+            document,
+            time,
+            deactivated: false,
+        });
+
+        Ok(())
+    }
+
+    /// Append a version to the history of an existing, active DID.
+    fn push_version(
+        &self,
+        did: &str,
+        document: Option<DidDocument>,
+        deactivated: bool,
+    ) -> Result<(), ResolverError> {
+        let mut documents = self.documents.write().unwrap();
+        let versions = documents.get_mut(did).ok_or_else(|| {
+            ResolverError::new(format!("{did} not found"), ErrorKind::DocumentNotFound)
+        })?;
+        check_active(did, versions)?;
+
+        let document = match document {
+            Some(document) => document,
+            None => versions[versions.len() - 1].document.clone(),
+        };
+        versions.push(DocumentVersion {
+            document,
+            time: Utc::now(),
+            deactivated,
+        });
+
+        Ok(())
+    }
+
+    fn resolve_version(
+        &self,
+        did: &str,
+        options: &ResolutionOptions,
+    ) -> Result<(DidDocument, DocumentMetadata), ResolverError> {
+        let documents = self.documents.read().unwrap();
+        let versions = documents.get(did).ok_or_else(|| {
+            ResolverError::new(format!("{did} not found"), ErrorKind::DocumentNotFound)
+        })?;
+        let index = select_version(versions, options)?;
+
+        let metadata = DocumentMetadata {
+            created: Some(format_time(versions[0].time)),
+            updated: (index > 0).then(|| format_time(versions[index].time)),
+            version_id: Some((index + 1).to_string()),
+            next_update: versions.get(index + 1).map(|next| format_time(next.time)),
+            next_version_id: versions.get(index + 1).map(|_| (index + 2).to_string()),
+            deactivated: versions[versions.len() - 1].deactivated.then_some(true),
+            ..Default::default()
+        };
+        return Ok((versions[index].document.clone(), metadata));
+    }
+}
+
+fn check_document(did: &str, document: &DidDocument) -> Result<(), ResolverError> {
+    if document.id != did {
+        return Err(ResolverError::new(
+            format!("document id {} does not match {did}", document.id),
+            ErrorKind::InvalidData,
+        ));
+    }
+    document
+        .validate()
+        .map_err(|e| ResolverError::new(e.to_string(), ErrorKind::InvalidData))?;
+    Ok(())
+}
+
+fn check_active(did: &str, versions: &[DocumentVersion]) -> Result<(), ResolverError> {
+    if matches!(versions.last(), Some(last) if last.deactivated) {
+        return Err(ResolverError::new(
+            format!("{did} has been deactivated"),
+            ErrorKind::Deactivated,
+        ));
+    }
+    Ok(())
+}
+
+/// Index of the version selected by `versionId`, or else by `versionTime`, defaulting to the latest version.
+fn select_version(
+    versions: &[DocumentVersion],
+    options: &ResolutionOptions,
+) -> Result<usize, ResolverError> {
+    if let Some(version_id) = &options.version_id {
+        return version_id
+            .parse::<usize>()
+            .ok()
+            .filter(|id| *id >= 1 && *id <= versions.len())
+            .map(|id| id - 1)
+            .ok_or_else(|| {
+                ResolverError::new(
+                    format!("version {version_id} not found"),
+                    ErrorKind::DocumentNotFound,
+                )
+            });
+    }
+
+    if let Some(version_time) = &options.version_time {
+        let time = DateTime::parse_from_rfc3339(version_time)
+            .map_err(|e| ResolverError::new(e.to_string(), ErrorKind::InvalidData))?;
+        return versions
+            .iter()
+            .rposition(|version| version.time <= time)
+            .ok_or_else(|| {
+                ResolverError::new(
+                    format!("no version existed at {version_time}"),
+                    ErrorKind::DocumentNotFound,
+                )
+            });
+    }
+
+    return Ok(versions.len() - 1);
+}
+
+fn format_time(time: DateTime<Utc>) -> String {
+    return time.to_rfc3339_opts(SecondsFormat::AutoSi, true);
+}
+
+#[async_trait::async_trait]
+impl DIDResolver for InMemoryResolver {
+    async fn resolve(&self, did: &str, options: &ResolutionOptions) -> ResolutionResult {
+        match self.resolve_version(did, options) {
+            Ok((document, metadata)) => {
+                ResolutionResult::from_document(document, options).with_document_metadata(metadata)
+            }
+            Err(e) => ResolutionResult::from_error(e),
+        }
+    }
+
+    async fn create(&self, did: &str, doc: DidDocument) -> Result<(), ResolverError> {
+        check_document(did, &doc)?;
+
+        let mut documents = self.documents.write().unwrap();
+        if documents.contains_key(did) {
+            return Err(ResolverError::new(
+                format!("{did} already exists"),
+                ErrorKind::InvalidData,
+            ));
+        }
+        documents.insert(
+            did.to_string(),
+            vec![DocumentVersion {
+                document: doc,
+                time: Utc::now(),
+                deactivated: false,
+            }],
+        );
+
+        Ok(())
+    }
+
+    /// Record `doc` as the new latest version of `did`.
+    async fn update(&self, did: &str, doc: DidDocument) -> Result<(), ResolverError> {
+        check_document(did, &doc)?;
+        return self.push_version(did, Some(doc), false);
+    }
+
+    /// Record a final version of `did` marked as deactivated. Earlier versions remain resolvable by version, with their
+    /// document but marked as deactivated too.
+    async fn deactivate(&self, did: &str) -> Result<(), ResolverError> {
+        self.push_version(did, None, true)
+    }
+
+    fn get_method(&self) -> &'static str {
+        return self.method;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::did::DidDocument;
+    use crate::error::ErrorKind;
+    use crate::resolver::{InMemoryResolver, ResolutionOptions};
+    use crate::DIDResolver;
+    use chrono::{DateTime, Utc};
+
+    macro_rules! aw {
+        ($e:expr) => {
+            tokio_test::block_on($e)
+        };
+    }
+
+    const DID: &str = "did:example:123";
+
+    fn time(rfc3339: &str) -> DateTime<Utc> {
+        return DateTime::parse_from_rfc3339(rfc3339)
+            .unwrap()
+            .with_timezone(&Utc);
+    }
+
+    fn create_resolver() -> InMemoryResolver {
+        let resolver = InMemoryResolver::new("example");
+        for (i, year) in [2020, 2021, 2022].iter().enumerate() {
+            let mut doc = DidDocument::new(DID);
+            doc.also_known_as = vec![format!("https://example.com/v{}", i + 1)];
+            resolver
+                .insert_version(DID, doc, time(&format!("{year}-01-01T00:00:00Z")))
+                .unwrap();
+        }
+        return resolver;
+    }
+
+    #[rstest::rstest]
+    #[case::latest(None, None, Ok("3"))]
+    #[case::version_id(Some("1"), None, Ok("1"))]
+    #[case::version_time(None, Some("2021-06-01T00:00:00Z"), Ok("2"))]
+    #[case::version_time_exact(None, Some("2022-01-01T00:00:00Z"), Ok("3"))]
+    #[case::version_time_offset(None, Some("2021-12-31T23:00:00-02:00"), Ok("3"))]
+    #[case::version_id_precedence(Some("2"), Some("2020-06-01T00:00:00Z"), Ok("2"))]
+    #[case::unknown_version_id(Some("4"), None, Err(ErrorKind::DocumentNotFound))]
+    #[case::before_creation(None, Some("2019-01-01T00:00:00Z"), Err(ErrorKind::DocumentNotFound))]
+    #[case::invalid_version_time(None, Some("yesterday"), Err(ErrorKind::InvalidData))]
+    fn test_resolve_version(
+        #[case] version_id: Option<&str>,
+        #[case] version_time: Option<&str>,
+        #[case] expect: Result<&str, ErrorKind>,
+    ) {
+        let options = ResolutionOptions {
+            version_id: version_id.map(String::from),
+            version_time: version_time.map(String::from),
+            ..Default::default()
+        };
+        let res = aw!(create_resolver().resolve(DID, &options));
+        match expect {
+            Ok(version) => {
+                let doc = res.did_document.unwrap();
+                assert_eq!(
+                    doc.also_known_as,
+                    vec![format!("https://example.com/v{version}")]
+                );
+                assert_eq!(
+                    res.did_document_metadata.version_id.as_deref(),
+                    Some(version)
+                );
+            }
+            Err(kind) => assert_eq!(res.error().unwrap().kind, kind),
+        }
+    }
+
+    #[test]
+    fn test_version_metadata() {
+        let options = ResolutionOptions {
+            version_id: Some("2".to_string()),
+            ..Default::default()
+        };
+        let metadata = aw!(create_resolver().resolve(DID, &options)).did_document_metadata;
+        assert_eq!(metadata.created.as_deref(), Some("2020-01-01T00:00:00Z"));
+        assert_eq!(metadata.updated.as_deref(), Some("2021-01-01T00:00:00Z"));
+        assert_eq!(
+            metadata.next_update.as_deref(),
+            Some("2022-01-01T00:00:00Z")
+        );
+        assert_eq!(metadata.next_version_id.as_deref(), Some("3"));
+    }
+
+    #[test]
+    fn test_create() {
+        let resolver = InMemoryResolver::new("example");
+        assert!(aw!(resolver.create(DID, DidDocument::new(DID))).is_ok());
+        assert_eq!(
+            aw!(resolver.clone().read(DID)).unwrap(),
+            DidDocument::new(DID)
+        );
+
+        let res = aw!(resolver.create(DID, DidDocument::new(DID)));
+        assert_eq!(res.unwrap_err().kind, ErrorKind::InvalidData);
+        let res = aw!(resolver.create("did:example:456", DidDocument::new(DID)));
+        assert_eq!(res.unwrap_err().kind, ErrorKind::InvalidData);
+        let res = aw!(resolver.read("did:example:456"));
+        assert_eq!(res.unwrap_err().kind, ErrorKind::DocumentNotFound);
+    }
+
+    #[test]
+    fn test_update_deactivate() {
+        let resolver = create_resolver();
+        let mut doc = DidDocument::new(DID);
+        doc.also_known_as = vec!["https://example.com/v4".to_string()];
+        assert!(aw!(resolver.update(DID, doc.clone())).is_ok());
+        assert_eq!(aw!(resolver.read(DID)).unwrap(), doc);
+
+        let res = aw!(resolver.update("did:example:456", DidDocument::new("did:example:456")));
+        assert_eq!(res.unwrap_err().kind, ErrorKind::DocumentNotFound);
+        let res = aw!(resolver.update(DID, DidDocument::new("did:example:456")));
+        assert_eq!(res.unwrap_err().kind, ErrorKind::InvalidData);
+
+        assert!(aw!(resolver.deactivate(DID)).is_ok());
+        let res = aw!(resolver.resolve(DID, &ResolutionOptions::default()));
+        assert_eq!(res.did_document_metadata.deactivated, Some(true));
+        assert_eq!(res.did_document_metadata.version_id.as_deref(), Some("5"));
+        assert_eq!(
+            aw!(resolver.read(DID)).unwrap_err().kind,
+            ErrorKind::Deactivated
+        );
+
+        // history before the deactivation stays resolvable, but is reported as deactivated
+        let options = ResolutionOptions {
+            version_id: Some("4".to_string()),
+            ..Default::default()
+        };
+        let res = aw!(resolver.resolve(DID, &options));
+        assert_eq!(res.did_document, Some(doc.clone()));
+        assert_eq!(res.did_document_metadata.deactivated, Some(true));
+        assert_eq!(
+            res.into_document().unwrap_err().kind,
+            ErrorKind::Deactivated
+        );
+
+        let res = aw!(resolver.update(DID, doc));
+        assert_eq!(res.unwrap_err().kind, ErrorKind::Deactivated);
+        let res = aw!(resolver.deactivate(DID));
+        assert_eq!(res.unwrap_err().kind, ErrorKind::Deactivated);
+    }
+
+    #[test]
+    fn test_insert_version_out_of_order() {
+        let resolver = create_resolver();
+        let res = resolver.insert_version(DID, DidDocument::new(DID), time("2021-06-01T00:00:00Z"));
+        assert_eq!(res.unwrap_err().kind, ErrorKind::InvalidData);
+    }
+}
@@ -0,0 +1,38 @@
+use crate::did::{
+    DidDocument, DidUrl, VerificationMethod, VerificationMethodType, VerificationRelationship,
+};
+use signature::signer::{DIDSigner, Ed25519DidSigner};
+use signature::suite::VerificationRelation;
+use std::collections::HashMap;
+
+/// DID document of `signer`'s `did:knox` identifier, holding its key as the verification method named by its proofs,
+/// listed under each of `relations`.
+pub fn signer_document(
+    signer: &Ed25519DidSigner,
+    relations: &[VerificationRelation],
+) -> DidDocument {
+    let vm_id = signer.get_verification_method(VerificationRelation::AssertionMethod);
+    let url = DidUrl::parse(&vm_id).unwrap();
+    let did = url.did().to_string();
+
+    let mut doc = DidDocument::new(did.clone());
+    doc.verification_method.push(VerificationMethod {
+        id: vm_id.clone(),
+        method_type: VerificationMethodType::Ed25519VerificationKey2018,
+        controller: did,
+        public_key_multibase: None,
+        public_key_base58: Some(url.fragment().unwrap()[1..].to_string()),
+        public_key_jwk: None,
+        property_set: HashMap::new(),
+    });
+    for relation in relations {
+        let reference = VerificationRelationship::Reference(vm_id.clone());
+        match relation {
+            VerificationRelation::AssertionMethod => doc.assertion_method.push(reference),
+            VerificationRelation::Authentication => doc.authentication.push(reference),
+            VerificationRelation::CapabilityInvocation => doc.capability_invocation.push(reference),
+            VerificationRelation::CapabilityDelegation => doc.capability_delegation.push(reference),
+        }
+    }
+    return doc;
+}
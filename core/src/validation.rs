@@ -0,0 +1,468 @@
+use crate::error::{ValidationError, ValidationErrorKind};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+
+mod contexts;
+
+/// Options controlling the JSON-LD validation pass that runs before a document is signed or verified.
+/// Because claims are held in flattened maps, a term that the active `@context` does not define would otherwise be
+/// silently dropped during canonicalization and left outside of the signature.
+#[derive(Debug, Clone)]
+pub struct ValidationOptions {
+    /// Reject documents with undefined terms, protected term redefinitions or relative IRIs. Enabled by default.
+    pub strict: bool,
+    /// Additional context documents, keyed by URL, that may be referenced from `@context` besides the bundled ones.
+    pub contexts: HashMap<String, Value>,
+}
+
+impl Default for ValidationOptions {
+    fn default() -> Self {
+        Self {
+            strict: true,
+            contexts: HashMap::new(),
+        }
+    }
+}
+
+impl ValidationOptions {
+    /// Register a context document so that documents referencing `url` can be validated offline.
+    pub fn with_context(mut self, url: impl Into<String>, context: Value) -> Self {
+        self.contexts.insert(url.into(), context);
+        self
+    }
+
+    fn load(&self, url: &str) -> Result<Value, ValidationError> {
+        match self.contexts.get(url) {
+            Some(context) => Ok(context.clone()),
+            None => contexts::builtin(url).ok_or_else(|| {
+                ValidationError::new(
+                    format!("context {url} is not known"),
+                    ValidationErrorKind::UnknownContext,
+                )
+            }),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct TermDefinition {
+    iri: Option<String>,
+    value_type: Option<String>,
+    protected: bool,
+    source: Option<String>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct ActiveContext {
+    terms: HashMap<String, TermDefinition>,
+    vocab: bool,
+}
+
+impl ActiveContext {
+    /// Whether `key` expands to an IRI under this context.
+    fn defines(&self, key: &str) -> bool {
+        if key.starts_with('@') || self.vocab || self.terms.contains_key(key) {
+            return true;
+        }
+        match key.split_once(':') {
+            Some((prefix, suffix))
+                if !suffix.starts_with("//") && self.terms.contains_key(prefix) =>
+            {
+                true
+            }
+            _ => is_absolute_iri(key),
+        }
+    }
+
+    fn is_iri_valued(&self, key: &str) -> bool {
+        if key == "id" || key == "@id" {
+            return true;
+        }
+        match self.terms.get(key) {
+            Some(def) => {
+                def.value_type.as_deref() == Some("@id") || def.iri.as_deref() == Some("@id")
+            }
+            None => false,
+        }
+    }
+
+    fn is_type_key(&self, key: &str) -> bool {
+        if key == "@type" {
+            return true;
+        }
+        match self.terms.get(key) {
+            Some(def) => def.iri.as_deref() == Some("@type"),
+            None => false,
+        }
+    }
+}
+
+/// Validate a JSON-LD document against the terms defined by its `@context`.
+/// The `proof` of the document, and of each credential embedded in a presentation, is not part of the signed payload and
+/// is therefore not inspected. Claims named `proof` anywhere else are validated like any other term.
+/// Type-scoped contexts are merged into the active context rather than applied per node.
+pub fn validate(doc: &Value, options: &ValidationOptions) -> Result<(), ValidationError> {
+    if !options.strict {
+        return Ok(());
+    }
+
+    return validate_value(doc, &ActiveContext::default(), options, "$", true);
+}
+
+/// `document` is set for the signed document and the credentials embedded in it, whose `proof` is skipped.
+fn validate_value(
+    value: &Value,
+    active: &ActiveContext,
+    options: &ValidationOptions,
+    path: &str,
+    document: bool,
+) -> Result<(), ValidationError> {
+    match value {
+        Value::Object(node) => validate_node(node, active, options, path, document),
+        Value::Array(items) => {
+            for (i, item) in items.iter().enumerate() {
+                validate_value(item, active, options, &format!("{path}[{i}]"), document)?;
+            }
+            Ok(())
+        }
+        _ => Ok(()),
+    }
+}
+
+fn validate_node(
+    node: &Map<String, Value>,
+    active: &ActiveContext,
+    options: &ValidationOptions,
+    path: &str,
+    document: bool,
+) -> Result<(), ValidationError> {
+    let mut local = active.clone();
+    if let Some(context) = node.get("@context") {
+        process_context(&mut local, context, options, None)?;
+    }
+
+    for (key, value) in node {
+        if key == "@context" || (document && key == "proof") {
+            continue;
+        }
+        let key_path = format!("{path}.{key}");
+
+        if !local.defines(key) {
+            return Err(ValidationError::new(
+                format!("term {key_path} is not defined by the active context"),
+                ValidationErrorKind::UndefinedTerm,
+            ));
+        }
+
+        if local.is_type_key(key) {
+            for t in strings(value) {
+                if !local.defines(t) {
+                    return Err(ValidationError::new(
+                        format!("type {t} at {key_path} is not defined by the active context"),
+                        ValidationErrorKind::UndefinedTerm,
+                    ));
+                }
+            }
+            continue;
+        }
+
+        if local.is_iri_valued(key) {
+            for iri in strings(value) {
+                if !is_absolute_iri(iri) && !iri.starts_with("_:") {
+                    return Err(ValidationError::new(
+                        format!("{key_path} has relative IRI {iri}"),
+                        ValidationErrorKind::RelativeIri,
+                    ));
+                }
+            }
+        }
+
+        let embedded = document && key == "verifiableCredential";
+        validate_value(value, &local, options, &key_path, embedded)?;
+    }
+
+    Ok(())
+}
+
+fn process_context(
+    active: &mut ActiveContext,
+    context: &Value,
+    options: &ValidationOptions,
+    source: Option<&str>,
+) -> Result<(), ValidationError> {
+    match context {
+        Value::Null => {
+            if active.terms.values().any(|def| def.protected) {
+                return Err(ValidationError::new(
+                    "context nullification would clear protected terms",
+                    ValidationErrorKind::ProtectedTermRedefinition,
+                ));
+            }
+            *active = ActiveContext::default();
+            Ok(())
+        }
+        Value::String(url) => {
+            let document = options.load(url)?;
+            match document.get("@context") {
+                Some(inner) => process_context(active, inner, options, Some(url)),
+                None => Err(ValidationError::new(
+                    format!("context document {url} has no @context"),
+                    ValidationErrorKind::InvalidContext,
+                )),
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                process_context(active, item, options, source)?;
+            }
+            Ok(())
+        }
+        Value::Object(definitions) => process_definitions(active, definitions, options, source),
+        _ => Err(ValidationError::new(
+            format!("invalid @context value {context}"),
+            ValidationErrorKind::InvalidContext,
+        )),
+    }
+}
+
+/// Apply the term definitions of a single context object.
+/// A context document may repeat its own protected terms across scoped contexts,
+/// but no other context, embedded or loaded, may change their definition.
+fn process_definitions(
+    active: &mut ActiveContext,
+    definitions: &Map<String, Value>,
+    options: &ValidationOptions,
+    source: Option<&str>,
+) -> Result<(), ValidationError> {
+    let protected = definitions
+        .get("@protected")
+        .and_then(Value::as_bool)
+        .unwrap_or(false);
+
+    if matches!(definitions.get("@vocab"), Some(Value::String(_))) {
+        active.vocab = true;
+    }
+
+    for (term, definition) in definitions {
+        if term.starts_with('@') {
+            continue;
+        }
+
+        let new_def = match definition {
+            Value::Null => TermDefinition {
+                iri: None,
+                value_type: None,
+                protected,
+                source: source.map(String::from),
+            },
+            Value::String(iri) => TermDefinition {
+                iri: Some(iri.clone()),
+                value_type: None,
+                protected,
+                source: source.map(String::from),
+            },
+            Value::Object(expanded) => TermDefinition {
+                iri: expanded
+                    .get("@id")
+                    .and_then(Value::as_str)
+                    .map(String::from),
+                value_type: expanded
+                    .get("@type")
+                    .and_then(Value::as_str)
+                    .map(String::from),
+                protected: expanded
+                    .get("@protected")
+                    .and_then(Value::as_bool)
+                    .unwrap_or(protected),
+                source: source.map(String::from),
+            },
+            _ => {
+                return Err(ValidationError::new(
+                    format!("invalid definition for term {term}"),
+                    ValidationErrorKind::InvalidContext,
+                ))
+            }
+        };
+
+        if let Some(existing) = active.terms.get(term) {
+            let unchanged =
+                existing.iri == new_def.iri && existing.value_type == new_def.value_type;
+            let same_source = source.is_some() && existing.source.as_deref() == source;
+            if existing.protected && !unchanged && !same_source {
+                return Err(ValidationError::new(
+                    format!("protected term {term} cannot be redefined"),
+                    ValidationErrorKind::ProtectedTermRedefinition,
+                ));
+            }
+        }
+
+        let scoped = definition.get("@context").cloned();
+        active.terms.insert(term.clone(), new_def);
+
+        if let Some(scoped) = scoped {
+            process_context(active, &scoped, options, source)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn strings(value: &Value) -> Vec<&str> {
+    match value {
+        Value::String(s) => vec![s.as_str()],
+        Value::Array(items) => items.iter().filter_map(Value::as_str).collect(),
+        _ => vec![],
+    }
+}
+
+/// An IRI is absolute when it starts with a scheme as defined by RFC 3986.
+fn is_absolute_iri(iri: &str) -> bool {
+    match iri.split_once(':') {
+        Some((scheme, _)) => {
+            let mut chars = scheme.chars();
+            matches!(chars.next(), Some(c) if c.is_ascii_alphabetic())
+                && chars.all(|c| c.is_ascii_alphanumeric() || c == '+' || c == '-' || c == '.')
+        }
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{validate, ValidationOptions};
+    use crate::error::ValidationErrorKind;
+    use serde_json::json;
+
+    fn credential() -> serde_json::Value {
+        json!({
+            "@context": [
+                "https://www.w3.org/2018/credentials/v1",
+                "https://www.w3.org/2018/credentials/examples/v1"
+            ],
+            "id": "http://example.edu/credentials/1872",
+            "type": ["VerifiableCredential", "AlumniCredential"],
+            "issuer": "https://example.edu/issuers/565049",
+            "issuanceDate": "2010-01-01T19:23:24Z",
+            "credentialSubject": {
+                "id": "did:example:ebfeb1f712ebc6f1c276e12ec21",
+                "alumniOf": "Example University",
+                "givenName": "JOHN"
+            },
+            "proof": {"anything": "goes"}
+        })
+    }
+
+    #[rstest::rstest]
+    #[case::valid(credential(), ValidationOptions::default(), None)]
+    #[case::undefined_term(
+        { let mut c = credential(); c["credentialSubject"]["lprNumber"] = json!("999-999-999"); c },
+        ValidationOptions::default(),
+        Some(ValidationErrorKind::UndefinedTerm)
+    )]
+    #[case::undefined_type(
+        { let mut c = credential(); c["type"] = json!(["VerifiableCredential", "PermanentResidentCard"]); c },
+        ValidationOptions::default(),
+        Some(ValidationErrorKind::UndefinedTerm)
+    )]
+    #[case::compact_iri(
+        { let mut c = credential(); c["credentialSubject"]["schema:birthDate"] = json!("1958-07-17"); c },
+        ValidationOptions::default(),
+        None
+    )]
+    #[case::absolute_iri_term(
+        { let mut c = credential(); c["credentialSubject"]["https://schema.org/birthDate"] = json!("1958-07-17"); c },
+        ValidationOptions::default(),
+        None
+    )]
+    #[case::protected_redefinition(
+        { let mut c = credential(); c["@context"].as_array_mut().unwrap().push(json!({"issuer": "https://evil.example/issuer"})); c },
+        ValidationOptions::default(),
+        Some(ValidationErrorKind::ProtectedTermRedefinition)
+    )]
+    #[case::identical_redefinition(
+        { let mut c = credential(); c["@context"].as_array_mut().unwrap().push(json!({"issuer": {"@id": "cred:issuer", "@type": "@id"}})); c },
+        ValidationOptions::default(),
+        None
+    )]
+    #[case::new_inline_term(
+        { let mut c = credential(); c["@context"].as_array_mut().unwrap().push(json!({"lprNumber": "https://w3id.org/citizenship#lprNumber"})); c["credentialSubject"]["lprNumber"] = json!("999-999-999"); c },
+        ValidationOptions::default(),
+        None
+    )]
+    #[case::relative_id(
+        { let mut c = credential(); c["id"] = json!("credentials/1872"); c },
+        ValidationOptions::default(),
+        Some(ValidationErrorKind::RelativeIri)
+    )]
+    #[case::relative_issuer(
+        { let mut c = credential(); c["issuer"] = json!("issuers/565049"); c },
+        ValidationOptions::default(),
+        Some(ValidationErrorKind::RelativeIri)
+    )]
+    #[case::unknown_context(
+        { let mut c = credential(); c["@context"].as_array_mut().unwrap().push(json!("https://w3id.org/citizenship/v1")); c },
+        ValidationOptions::default(),
+        Some(ValidationErrorKind::UnknownContext)
+    )]
+    #[case::registered_context(
+        { let mut c = credential(); c["@context"].as_array_mut().unwrap().push(json!("https://w3id.org/citizenship/v1")); c["credentialSubject"]["lprNumber"] = json!("999-999-999"); c },
+        ValidationOptions::default().with_context(
+            "https://w3id.org/citizenship/v1",
+            json!({"@context": {"@protected": true, "lprNumber": "https://w3id.org/citizenship#lprNumber"}})
+        ),
+        None
+    )]
+    #[case::registered_context_redefinition(
+        { let mut c = credential(); c["@context"].as_array_mut().unwrap().push(json!("https://evil.example/v1")); c },
+        ValidationOptions::default().with_context(
+            "https://evil.example/v1",
+            json!({"@context": {"issuer": "https://evil.example/issuer"}})
+        ),
+        Some(ValidationErrorKind::ProtectedTermRedefinition)
+    )]
+    #[case::vocab(
+        { let mut c = credential(); c["@context"].as_array_mut().unwrap().push(json!({"@vocab": "https://example.org/vocab#"})); c["credentialSubject"]["lprNumber"] = json!("999-999-999"); c },
+        ValidationOptions::default(),
+        None
+    )]
+    #[case::nested_proof_claim(
+        { let mut c = credential(); c["credentialSubject"]["proof"] = json!({"anything": "goes"}); c },
+        ValidationOptions::default(),
+        Some(ValidationErrorKind::UndefinedTerm)
+    )]
+    #[case::embedded_credential_proof(
+        json!({
+            "@context": ["https://www.w3.org/2018/credentials/v1"],
+            "type": ["VerifiablePresentation"],
+            "verifiableCredential": [credential()],
+            "proof": {"anything": "goes"}
+        }),
+        ValidationOptions::default(),
+        None
+    )]
+    #[case::embedded_credential_nested_proof_claim(
+        json!({
+            "@context": ["https://www.w3.org/2018/credentials/v1"],
+            "type": ["VerifiablePresentation"],
+            "verifiableCredential": [({ let mut c = credential(); c["credentialSubject"]["proof"] = json!({"anything": "goes"}); c })]
+        }),
+        ValidationOptions::default(),
+        Some(ValidationErrorKind::UndefinedTerm)
+    )]
+    #[case::opt_out(
+        { let mut c = credential(); c["credentialSubject"]["lprNumber"] = json!("999-999-999"); c["id"] = json!("1872"); c },
+        ValidationOptions { strict: false, ..Default::default() },
+        None
+    )]
+    fn test_validate(
+        #[case] doc: serde_json::Value,
+        #[case] options: ValidationOptions,
+        #[case] expect_error_kind: Option<ValidationErrorKind>,
+    ) {
+        let res = validate(&doc, &options);
+        match res.err() {
+            Some(e) => assert_eq!(Some(e.kind), expect_error_kind, "{}", e),
+            None => assert!(expect_error_kind.is_none()),
+        }
+    }
+}
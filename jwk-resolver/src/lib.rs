@@ -0,0 +1,222 @@
+use ssi::did::{
+    Did, DidDocument, Jwk, VerificationMethod, VerificationMethodType, VerificationRelationship,
+};
+use ssi::error::{ErrorKind, ResolverError};
+use ssi::resolver::{ResolutionOptions, ResolutionResult};
+
+const DID_METHOD: &str = "jwk";
+const CONTEXT_JWS_2020: &str = "https://w3id.org/security/suites/jws-2020/v1";
+
+/// Resolver for the `did:jwk` method, whose identifier is the base64url encoded public JWK itself.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct JwkResolver {}
+
+impl JwkResolver {
+    pub fn new() -> Self {
+        return Self {};
+    }
+
+    const fn get_method_helper() -> &'static str {
+        return DID_METHOD;
+    }
+}
+
+/// Create the `did:jwk` identifier of a public JWK.
+pub fn create_did(jwk: &Jwk) -> Result<String, ResolverError> {
+    jwk.validate_public()
+        .map_err(|e| ResolverError::new(e.to_string(), ErrorKind::InvalidData))?;
+    let encoded = serde_json::to_vec(jwk)
+        .map_err(|e| ResolverError::new(e.to_string(), ErrorKind::InvalidData))?;
+    return Ok(format!(
+        "did:{DID_METHOD}:{}",
+        base64::encode_config(encoded, base64::URL_SAFE_NO_PAD)
+    ));
+}
+
+/// Expand a `did:jwk` identifier into its DID document.
+/// Keys restricted to encryption by `"use": "enc"` and X25519 keys are only listed under `keyAgreement`,
+/// keys restricted to signing by `"use": "sig"` are not listed under `keyAgreement`.
+pub fn expand(did: &str) -> Result<DidDocument, ResolverError> {
+    let parsed =
+        Did::parse(did).map_err(|e| ResolverError::new(e.to_string(), ErrorKind::InvalidData))?;
+    if parsed.method() != DID_METHOD {
+        return Err(ResolverError::new(
+            format!("{did} is not a did:{DID_METHOD} identifier"),
+            ErrorKind::InvalidData,
+        ));
+    }
+
+    let decoded = base64::decode_config(parsed.method_specific_id(), base64::URL_SAFE_NO_PAD)
+        .map_err(|e| ResolverError::new(e.to_string(), ErrorKind::InvalidData))?;
+    let jwk: Jwk = serde_json::from_slice(&decoded)
+        .map_err(|e| ResolverError::new(e.to_string(), ErrorKind::InvalidData))?;
+    jwk.validate_public()
+        .map_err(|e| ResolverError::new(e.to_string(), ErrorKind::InvalidData))?;
+
+    let encryption_only =
+        jwk.key_use.as_deref() == Some("enc") || jwk.crv.as_deref() == Some("X25519");
+    let signing_only = jwk.key_use.as_deref() == Some("sig");
+
+    let vm_id = format!("{did}#0");
+    let mut doc = DidDocument::new(did);
+    doc.context.push(CONTEXT_JWS_2020.into());
+    doc.verification_method.push(VerificationMethod {
+        id: vm_id.clone(),
+        method_type: VerificationMethodType::JsonWebKey2020,
+        controller: did.to_string(),
+        public_key_multibase: None,
+        public_key_base58: None,
+        public_key_jwk: Some(jwk),
+        property_set: Default::default(),
+    });
+
+    if !encryption_only {
+        for relationship in [
+            &mut doc.authentication,
+            &mut doc.assertion_method,
+            &mut doc.capability_invocation,
+            &mut doc.capability_delegation,
+        ] {
+            relationship.push(VerificationRelationship::Reference(vm_id.clone()));
+        }
+    }
+    if !signing_only {
+        doc.key_agreement
+            .push(VerificationRelationship::Reference(vm_id));
+    }
+
+    return Ok(doc);
+}
+
+#[async_trait::async_trait]
+impl ssi::DIDResolver for JwkResolver {
+    fn get_method(&self) -> &'static str {
+        return Self::get_method_helper();
+    }
+
+    /// `did:jwk` documents cannot be registered, creation only checks that the document is the expansion of the DID.
+    async fn create(&self, did: &str, document: DidDocument) -> Result<(), ResolverError> {
+        if expand(did)? != document {
+            return Err(ResolverError::new(
+                format!("document does not match the expansion of {did}"),
+                ErrorKind::InvalidData,
+            ));
+        }
+
+        Ok(())
+    }
+
+    async fn resolve(&self, did: &str, options: &ResolutionOptions) -> ResolutionResult {
+        ResolutionResult::from_result(expand(did), options)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{create_did, expand, JwkResolver};
+    use ssi::did::{Jwk, VerificationRelationship};
+    use ssi::DIDResolver;
+
+    macro_rules! aw {
+        ($e:expr) => {
+            tokio_test::block_on($e)
+        };
+    }
+
+    fn p256_jwk() -> Jwk {
+        Jwk {
+            kty: "EC".to_string(),
+            crv: Some("P-256".to_string()),
+            x: Some("acbIQiuMs3i8_uszEjJ2tpTtRM4EU3yz91PH6CdH2V0".to_string()),
+            y: Some("_KcyLj9vWMptnmKtm46GqDz8wf74I5LKgrl2GzH3nSE".to_string()),
+            ..Default::default()
+        }
+    }
+
+    fn x25519_jwk() -> Jwk {
+        Jwk {
+            kty: "OKP".to_string(),
+            crv: Some("X25519".to_string()),
+            x: Some("3p7bfXt9wbTTW2HC7OQ1Nz-DQ8hbeGdNrfx-FG-IK08".to_string()),
+            key_use: Some("enc".to_string()),
+            ..Default::default()
+        }
+    }
+
+    fn ed25519_jwk(key_use: Option<&str>) -> Jwk {
+        Jwk {
+            kty: "OKP".to_string(),
+            crv: Some("Ed25519".to_string()),
+            x: Some("VCpo2LMLhn6iWku8MKvSLg2ZAoC-nlOyPVQaO3FxVeQ".to_string()),
+            key_use: key_use.map(String::from),
+            ..Default::default()
+        }
+    }
+
+    #[rstest::rstest]
+    #[case::p256(p256_jwk(), true, true)]
+    #[case::x25519(x25519_jwk(), false, true)]
+    #[case::ed25519_sig(ed25519_jwk(Some("sig")), true, false)]
+    #[case::ed25519(ed25519_jwk(None), true, true)]
+    fn test_round_trip(
+        #[case] jwk: Jwk,
+        #[case] expect_signing: bool,
+        #[case] expect_key_agreement: bool,
+    ) {
+        let did = create_did(&jwk).unwrap();
+        let doc = aw!(JwkResolver::new().read(&did)).unwrap();
+
+        assert!(doc.validate().is_ok());
+        assert_eq!(doc.id, did);
+        assert_eq!(doc.verification_method[0].public_key_jwk, Some(jwk));
+
+        let reference = vec![VerificationRelationship::Reference(format!("{did}#0"))];
+        assert_eq!(doc.assertion_method == reference, expect_signing);
+        assert_eq!(doc.authentication == reference, expect_signing);
+        assert_eq!(doc.key_agreement == reference, expect_key_agreement);
+    }
+
+    #[test]
+    fn test_read_known_did() {
+        // example from the did:jwk specification
+        let did = "did:jwk:eyJjcnYiOiJQLTI1NiIsImt0eSI6IkVDIiwieCI6ImFjYklRaXVNczNpOF91c3pFakoydHBUdFJNNEVVM3l6OTFQSDZDZEgyVjAiLCJ5IjoiX0tjeUxqOXZXTXB0bm1LdG00NkdxRHo4d2Y3NEk1TEtncmwyR3pIM25TRSJ9";
+        let doc = expand(did).unwrap();
+        assert_eq!(doc.verification_method[0].public_key_jwk, Some(p256_jwk()));
+    }
+
+    #[rstest::rstest]
+    #[case::wrong_method("did:key:eyJrdHkiOiJPS1AifQ")]
+    #[case::not_base64("did:jwk:not.base64")]
+    #[case::not_json("did:jwk:bm90IGpzb24")]
+    #[case::private_key(create_private_did())]
+    fn test_read_failure(#[case] did: String) {
+        let res = aw!(JwkResolver::new().read(&did));
+        assert_eq!(res.unwrap_err().kind, ssi::error::ErrorKind::InvalidData);
+    }
+
+    fn create_private_did() -> String {
+        let mut jwk = ed25519_jwk(None);
+        jwk.d = Some("nWGxne_9WmC6hEr0kuwsxERJxWl7MmkZcDusAxyuf2A".to_string());
+        let encoded = serde_json::to_vec(&jwk).unwrap();
+        format!(
+            "did:jwk:{}",
+            base64::encode_config(encoded, base64::URL_SAFE_NO_PAD)
+        )
+    }
+
+    #[test]
+    fn test_create() {
+        let did = create_did(&p256_jwk()).unwrap();
+        let doc = expand(&did).unwrap();
+        assert!(aw!(JwkResolver::new().create(&did, doc)).is_ok());
+
+        let other = expand(&create_did(&x25519_jwk()).unwrap()).unwrap();
+        let res = aw!(JwkResolver::new().create(&did, other));
+        assert_eq!(res.unwrap_err().kind, ssi::error::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_get_method() {
+        assert_eq!(JwkResolver::new().get_method(), "jwk");
+    }
+}
@@ -0,0 +1,317 @@
+use ssi::did::multicodec::{decode_multikey, encode_multikey, KeyType};
+use ssi::did::{
+    Did, DidDocument, VerificationMethod, VerificationMethodType, VerificationRelationship,
+};
+use ssi::error::{ErrorKind, ResolverError};
+use ssi::resolver::{ResolutionOptions, ResolutionResult};
+
+const DID_METHOD: &str = "key";
+const CONTEXT_ED25519_2020: &str = "https://w3id.org/security/suites/ed25519-2020/v1";
+const CONTEXT_X25519_2020: &str = "https://w3id.org/security/suites/x25519-2020/v1";
+const CONTEXT_MULTIKEY_V1: &str = "https://w3id.org/security/multikey/v1";
+
+/// Resolver for the `did:key` method.
+/// The DID document is derived entirely from the public key encoded in the identifier, so no registry is involved.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct KeyResolver {}
+
+impl KeyResolver {
+    pub fn new() -> Self {
+        return Self {};
+    }
+
+    const fn get_method_helper() -> &'static str {
+        return DID_METHOD;
+    }
+}
+
+/// Create the `did:key` identifier of a public key.
+pub fn create_did(key_type: KeyType, public_key: &[u8]) -> Result<String, ResolverError> {
+    validate_public_key(key_type, public_key)?;
+    return Ok(format!(
+        "did:{DID_METHOD}:{}",
+        encode_multikey(key_type, public_key)
+    ));
+}
+
+/// Expand a `did:key` identifier into its DID document.
+pub fn expand(did: &str) -> Result<DidDocument, ResolverError> {
+    let parsed =
+        Did::parse(did).map_err(|e| ResolverError::new(e.to_string(), ErrorKind::InvalidData))?;
+    if parsed.method() != DID_METHOD {
+        return Err(ResolverError::new(
+            format!("{did} is not a did:{DID_METHOD} identifier"),
+            ErrorKind::InvalidData,
+        ));
+    }
+
+    return expand_multikey(did, parsed.method_specific_id());
+}
+
+/// Expand a multibase encoded public key into the DID document of `did`, following the `did:key` rules.
+/// Other methods that embed a single inception key, such as `did:peer:0`, share this expansion.
+pub fn expand_multikey(did: &str, encoded: &str) -> Result<DidDocument, ResolverError> {
+    if !encoded.starts_with('z') {
+        return Err(ResolverError::new(
+            format!("{did} must be base58btc encoded"),
+            ErrorKind::InvalidData,
+        ));
+    }
+    let (key_type, public_key) = decode_multikey(encoded)
+        .map_err(|e| ResolverError::new(e.to_string(), ErrorKind::InvalidData))?;
+    validate_public_key(key_type, &public_key)?;
+
+    let mut doc = DidDocument::new(did);
+    let vm_id = format!("{did}#{encoded}");
+
+    if key_type == KeyType::X25519 {
+        doc.context.push(CONTEXT_X25519_2020.into());
+        doc.verification_method.push(verification_method(
+            did,
+            &vm_id,
+            VerificationMethodType::Other("X25519KeyAgreementKey2020".to_string()),
+            encoded,
+        ));
+        doc.key_agreement
+            .push(VerificationRelationship::Reference(vm_id));
+        return Ok(doc);
+    }
+
+    let method_type = match key_type {
+        KeyType::Ed25519 => {
+            doc.context.push(CONTEXT_ED25519_2020.into());
+            VerificationMethodType::Ed25519VerificationKey2020
+        }
+        _ => {
+            doc.context.push(CONTEXT_MULTIKEY_V1.into());
+            VerificationMethodType::Multikey
+        }
+    };
+    doc.verification_method
+        .push(verification_method(did, &vm_id, method_type, encoded));
+    for relationship in [
+        &mut doc.authentication,
+        &mut doc.assertion_method,
+        &mut doc.capability_invocation,
+        &mut doc.capability_delegation,
+    ] {
+        relationship.push(VerificationRelationship::Reference(vm_id.clone()));
+    }
+
+    if key_type == KeyType::Ed25519 {
+        let x25519 = encode_multikey(KeyType::X25519, &ed25519_to_x25519(&public_key)?);
+        let ka_id = format!("{did}#{x25519}");
+        doc.context.push(CONTEXT_X25519_2020.into());
+        doc.verification_method.push(verification_method(
+            did,
+            &ka_id,
+            VerificationMethodType::Other("X25519KeyAgreementKey2020".to_string()),
+            &x25519,
+        ));
+        doc.key_agreement
+            .push(VerificationRelationship::Reference(ka_id));
+    }
+
+    return Ok(doc);
+}
+
+fn verification_method(
+    did: &str,
+    id: &str,
+    method_type: VerificationMethodType,
+    public_key_multibase: &str,
+) -> VerificationMethod {
+    VerificationMethod {
+        id: id.to_string(),
+        method_type,
+        controller: did.to_string(),
+        public_key_multibase: Some(public_key_multibase.to_string()),
+        public_key_base58: None,
+        public_key_jwk: None,
+        property_set: Default::default(),
+    }
+}
+
+/// Structural checks of the public key, Ed25519 keys must additionally be valid curve points.
+fn validate_public_key(key_type: KeyType, public_key: &[u8]) -> Result<(), ResolverError> {
+    if public_key.len() != key_type.key_length() {
+        return Err(ResolverError::new(
+            format!("{key_type:?} key must be {} bytes", key_type.key_length()),
+            ErrorKind::InvalidData,
+        ));
+    }
+
+    match key_type {
+        KeyType::Ed25519 => ed25519_to_x25519(public_key).map(|_| ()),
+        KeyType::Secp256k1 | KeyType::P256 | KeyType::P384 => match public_key[0] {
+            0x02 | 0x03 => Ok(()),
+            _ => Err(ResolverError::new(
+                format!("{key_type:?} key must be a compressed point"),
+                ErrorKind::InvalidData,
+            )),
+        },
+        _ => Ok(()),
+    }
+}
+
+/// Convert an Ed25519 public key to the X25519 public key used for key agreement.
+fn ed25519_to_x25519(public_key: &[u8]) -> Result<[u8; 32], ResolverError> {
+    let point = curve25519_dalek::edwards::CompressedEdwardsY::from_slice(public_key)
+        .decompress()
+        .ok_or_else(|| {
+            ResolverError::new(
+                "Ed25519 key is not a valid curve point",
+                ErrorKind::InvalidData,
+            )
+        })?;
+    return Ok(point.to_montgomery().to_bytes());
+}
+
+#[async_trait::async_trait]
+impl ssi::DIDResolver for KeyResolver {
+    fn get_method(&self) -> &'static str {
+        return Self::get_method_helper();
+    }
+
+    /// `did:key` documents cannot be registered, creation only checks that the document is the expansion of the DID.
+    async fn create(&self, did: &str, document: DidDocument) -> Result<(), ResolverError> {
+        if expand(did)? != document {
+            return Err(ResolverError::new(
+                format!("document does not match the expansion of {did}"),
+                ErrorKind::InvalidData,
+            ));
+        }
+
+        Ok(())
+    }
+
+    async fn resolve(&self, did: &str, options: &ResolutionOptions) -> ResolutionResult {
+        ResolutionResult::from_result(expand(did), options)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{create_did, expand, KeyResolver};
+    use signature::signer::Ed25519DidSigner;
+    use ssi::did::multicodec::{decode_multikey, KeyType};
+    use ssi::did::VerificationMethodType;
+    use ssi::DIDResolver;
+
+    macro_rules! aw {
+        ($e:expr) => {
+            tokio_test::block_on($e)
+        };
+    }
+
+    #[rstest::rstest]
+    #[case::ed25519(
+        "did:key:z6MkiTBz1ymuepAQ4HEHYSF1H8quG5GLVVQR3djdX3mDooWp",
+        VerificationMethodType::Ed25519VerificationKey2020,
+        Some("z6LShs9GGnqk85isEBzzshkuVWrVKsRp24GnDuHk8QWkARMW")
+    )]
+    #[case::secp256k1(
+        "did:key:zQ3shokFTS3brHcDQrn82RUDfCZESWL1ZdCEJwekUDPQiYBme",
+        VerificationMethodType::Multikey,
+        None
+    )]
+    #[case::p256(
+        "did:key:zDnaerDaTF5BXEavCrfRZEk316dpbLsfPDZ3WJ5hRTPFU2169",
+        VerificationMethodType::Multikey,
+        None
+    )]
+    #[case::bls12381(
+        "did:key:zUC7EK3ZakmukHhuncwkbySmomv3FmrkmS36E4Ks5rsb6VQSRpoCrx6Hb8e2Nk6UvJFSdyw9NK1scFXJp21gNNYFjVWNgaqyGnkyhtagagCpQb5B7tagJu3HDbjQ8h5ypoHjwBb",
+        VerificationMethodType::Multikey,
+        None
+    )]
+    fn test_read(
+        #[case] did: &str,
+        #[case] method_type: VerificationMethodType,
+        #[case] key_agreement: Option<&str>,
+    ) {
+        let doc = aw!(KeyResolver::new().read(did)).unwrap();
+        let encoded = did.strip_prefix("did:key:").unwrap();
+        let vm_id = format!("{did}#{encoded}");
+
+        assert!(doc.validate().is_ok());
+        assert_eq!(doc.id, did);
+        let vm = doc.find_verification_method(&vm_id).unwrap();
+        assert_eq!(vm.method_type, method_type);
+        assert_eq!(vm.public_key_multibase.as_deref(), Some(encoded));
+        for relation in [
+            signature::suite::VerificationRelation::AssertionMethod,
+            signature::suite::VerificationRelation::Authentication,
+            signature::suite::VerificationRelation::CapabilityInvocation,
+            signature::suite::VerificationRelation::CapabilityDelegation,
+        ] {
+            assert_eq!(
+                doc.relationship(relation),
+                &vec![ssi::did::VerificationRelationship::Reference(vm_id.clone())]
+            );
+        }
+
+        match key_agreement {
+            Some(x25519) => {
+                let ka_id = format!("{did}#{x25519}");
+                assert_eq!(
+                    doc.key_agreement,
+                    vec![ssi::did::VerificationRelationship::Reference(ka_id.clone())]
+                );
+                let ka = doc.find_verification_method(&ka_id).unwrap();
+                assert_eq!(ka.public_key_multibase.as_deref(), Some(x25519));
+            }
+            None => assert!(doc.key_agreement.is_empty()),
+        }
+    }
+
+    #[test]
+    fn test_read_x25519() {
+        let did = "did:key:z6LSeu9HkTHSfLLeUs2nnzUSNedgDUevfNQgQjQC23ZCit6F";
+        let doc = expand(did).unwrap();
+        assert!(doc.validate().is_ok());
+        assert_eq!(doc.key_agreement.len(), 1);
+        assert!(doc.authentication.is_empty());
+        assert!(doc.assertion_method.is_empty());
+    }
+
+    #[rstest::rstest]
+    #[case::wrong_method("did:knox:z6MkiTBz1ymuepAQ4HEHYSF1H8quG5GLVVQR3djdX3mDooWp")]
+    #[case::not_base58btc("did:key:f6MkiTBz1ymuepAQ4HEHYSF1H8quG5GLVVQR3djdX3mDooWp")]
+    #[case::unknown_codec("did:key:z2J9gaYxrKVpdoG9A4gRnmpnRCcxU6agDtFVVBVdn1JedouoZN7SzcyREXXzWgt3gGiwpoHq7K68X4m32D8HgzG8wv3sY5j7")]
+    #[case::truncated("did:key:z6MkiTBz1ymuepAQ4HEHYSF1H8quG5GLVVQR3djdX3mDoo")]
+    #[case::invalid_did("did:key:")]
+    fn test_read_failure(#[case] did: &str) {
+        let res = aw!(KeyResolver::new().read(did));
+        assert_eq!(res.unwrap_err().kind, ssi::error::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_create() {
+        let did = "did:key:z6MkiTBz1ymuepAQ4HEHYSF1H8quG5GLVVQR3djdX3mDooWp";
+        let doc = expand(did).unwrap();
+        assert!(aw!(KeyResolver::new().create(did, doc.clone())).is_ok());
+
+        let other = expand("did:key:zQ3shokFTS3brHcDQrn82RUDfCZESWL1ZdCEJwekUDPQiYBme").unwrap();
+        let res = aw!(KeyResolver::new().create(did, other));
+        assert_eq!(res.unwrap_err().kind, ssi::error::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_signer_to_did_key() {
+        let signer = Ed25519DidSigner::new();
+        let did = signer.to_did_key();
+        let doc = expand(&did).unwrap();
+
+        let encoded = did.strip_prefix("did:key:").unwrap();
+        let (key_type, public_key) = decode_multikey(encoded).unwrap();
+        assert_eq!(key_type, KeyType::Ed25519);
+        assert_eq!(create_did(KeyType::Ed25519, &public_key).unwrap(), did);
+        assert_eq!(doc.verification_method[0].controller, did);
+    }
+
+    #[test]
+    fn test_get_method() {
+        assert_eq!(KeyResolver::new().get_method(), "key");
+    }
+}
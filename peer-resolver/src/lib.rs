@@ -0,0 +1,287 @@
+mod numalgo2;
+mod numalgo4;
+const DID_METHOD: &str = "peer";
+pub use numalgo2::{DidCommService, Purpose};
+use ssi::did::multicodec::{encode_multikey, KeyType};
+use ssi::did::{Did, DidDocument};
+use ssi::error::{ErrorKind, ResolverError};
+use ssi::resolver::{ResolutionOptions, ResolutionResult};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// Resolver for the `did:peer` method with numalgo 0, 2 and 4.
+/// Short form `did:peer:4` identifiers can only be resolved once their long form has been created or read,
+/// clones of a resolver share the long forms they have seen.
+#[derive(Clone, Debug, Default)]
+pub struct PeerResolver {
+    long_forms: Arc<Mutex<HashMap<String, String>>>,
+}
+
+impl PeerResolver {
+    pub fn new() -> Self {
+        return Self::default();
+    }
+
+    const fn get_method_helper() -> &'static str {
+        return DID_METHOD;
+    }
+
+    fn remember(&self, long_form: &str) -> Result<(), ResolverError> {
+        let short_form = numalgo4::short_form(long_form)?;
+        self.long_forms
+            .lock()
+            .unwrap()
+            .insert(short_form, long_form.to_string());
+        Ok(())
+    }
+
+    fn expand(&self, did: &str) -> Result<DidDocument, ResolverError> {
+        let parsed = Did::parse(did)
+            .map_err(|e| ResolverError::new(e.to_string(), ErrorKind::InvalidData))?;
+        if parsed.method() != DID_METHOD {
+            return Err(ResolverError::new(
+                format!("{did} is not a did:{DID_METHOD} identifier"),
+                ErrorKind::InvalidData,
+            ));
+        }
+
+        let id = parsed.method_specific_id();
+        match id.chars().next() {
+            Some('0') => key_resolver::expand_multikey(did, &id[1..]),
+            Some('2') => numalgo2::expand(did),
+            Some('4') if numalgo4::is_short_form(did) => {
+                let long_form = self.long_forms.lock().unwrap().get(did).cloned();
+                match long_form {
+                    Some(long_form) => numalgo4::expand(&long_form, did),
+                    None => Err(ResolverError::new(
+                        format!("long form of {did} is not known"),
+                        ErrorKind::DocumentNotFound,
+                    )),
+                }
+            }
+            Some('4') => {
+                let doc = numalgo4::expand(did, did)?;
+                self.remember(did)?;
+                Ok(doc)
+            }
+            _ => Err(ResolverError::new(
+                format!("{did} uses an unsupported numalgo"),
+                ErrorKind::InvalidData,
+            )),
+        }
+    }
+}
+
+/// Create a `did:peer:0` identifier, which wraps a single inception key like `did:key`.
+pub fn create_did_0(key_type: KeyType, public_key: &[u8]) -> String {
+    return format!("did:peer:0{}", encode_multikey(key_type, public_key));
+}
+
+/// Create a `did:peer:2` identifier from multibase encoded keys and DIDComm service endpoints.
+pub fn create_did_2(
+    keys: &[(Purpose, String)],
+    services: &[DidCommService],
+) -> Result<String, ResolverError> {
+    return numalgo2::create_did(keys, services);
+}
+
+/// Create the long and short form `did:peer:4` identifiers of an input document without `id`.
+pub fn create_did_4(input: &serde_json::Value) -> Result<(String, String), ResolverError> {
+    return numalgo4::create_did(input);
+}
+
+#[async_trait::async_trait]
+impl ssi::DIDResolver for PeerResolver {
+    fn get_method(&self) -> &'static str {
+        return Self::get_method_helper();
+    }
+
+    /// `did:peer` documents are exchanged directly between peers, creation checks that the document matches the DID.
+    /// Creating a long form `did:peer:4` makes its short form resolvable.
+    async fn create(&self, did: &str, document: DidDocument) -> Result<(), ResolverError> {
+        if self.expand(did)? != document {
+            return Err(ResolverError::new(
+                format!("document does not match the expansion of {did}"),
+                ErrorKind::InvalidData,
+            ));
+        }
+
+        Ok(())
+    }
+
+    async fn resolve(&self, did: &str, options: &ResolutionOptions) -> ResolutionResult {
+        ResolutionResult::from_result(self.expand(did), options)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{create_did_0, create_did_2, create_did_4, DidCommService, PeerResolver, Purpose};
+    use ssi::did::multicodec::KeyType;
+    use ssi::did::VerificationRelationship;
+    use ssi::DIDResolver;
+
+    macro_rules! aw {
+        ($e:expr) => {
+            tokio_test::block_on($e)
+        };
+    }
+
+    const ED25519: &str = "z6MkqRYqQiSgvZQdnBytw86Qbs2ZWUkGv22od935YF4s8M7V";
+    const X25519: &str = "z6LSbysY2xFMRpGMhb7tFTLMpeuPRaqaWM1yECx2AtzE3KCc";
+
+    #[test]
+    fn test_numalgo_0() {
+        let public_key = ssi::did::multicodec::decode_multikey(ED25519).unwrap().1;
+        let did = create_did_0(KeyType::Ed25519, &public_key);
+        assert_eq!(did, format!("did:peer:0{ED25519}"));
+
+        let doc = aw!(PeerResolver::new().read(&did)).unwrap();
+        assert_eq!(doc.id, did);
+        assert!(doc
+            .find_verification_method(&format!("{did}#{ED25519}"))
+            .is_some());
+        assert_eq!(doc.key_agreement.len(), 1);
+    }
+
+    #[test]
+    fn test_numalgo_2() {
+        let service = DidCommService {
+            endpoint: "https://example.com/endpoint".to_string(),
+            routing_keys: vec!["did:example:somemediator#somekey".to_string()],
+            accept: vec!["didcomm/v2".to_string()],
+        };
+        let did = create_did_2(
+            &[
+                (Purpose::Encryption, X25519.to_string()),
+                (Purpose::Verification, ED25519.to_string()),
+            ],
+            &[service.clone(), service],
+        )
+        .unwrap();
+        assert!(did.starts_with(&format!("did:peer:2.E{X25519}.V{ED25519}.S")));
+
+        let doc = aw!(PeerResolver::new().read(&did)).unwrap();
+        assert_eq!(doc.id, did);
+        assert_eq!(
+            doc.key_agreement,
+            vec![VerificationRelationship::Reference("#key-1".to_string())]
+        );
+        assert_eq!(
+            doc.authentication,
+            vec![VerificationRelationship::Reference("#key-2".to_string())]
+        );
+        assert_eq!(
+            doc.find_verification_method("#key-2")
+                .unwrap()
+                .public_key_multibase
+                .as_deref(),
+            Some(ED25519)
+        );
+
+        assert_eq!(doc.service.len(), 2);
+        assert_eq!(doc.service[0].id, "#service");
+        assert_eq!(doc.service[1].id, "#service-1");
+        assert_eq!(doc.service[0].service_type, vec!["DIDCommMessaging"]);
+        assert_eq!(
+            doc.service[0].service_endpoint,
+            serde_json::json!({
+                "uri": "https://example.com/endpoint",
+                "accept": ["didcomm/v2"],
+                "routingKeys": ["did:example:somemediator#somekey"]
+            })
+        );
+    }
+
+    #[test]
+    fn test_numalgo_2_abbreviated_service() {
+        // service encoding of the did:peer specification examples
+        let did = format!(
+            "did:peer:2.V{ED25519}.SeyJ0IjoiZG0iLCJzIjoiaHR0cHM6Ly9leGFtcGxlLmNvbS9lbmRwb2ludCIsInIiOlsiZGlkOmV4YW1wbGU6c29tZW1lZGlhdG9yI3NvbWVrZXkiXSwiYSI6WyJkaWRjb21tL3YyIiwiZGlkY29tbS9haXAyO2Vudj1yZmM1ODciXX0"
+        );
+        let doc = aw!(PeerResolver::new().read(&did)).unwrap();
+        assert_eq!(
+            doc.service[0].service_endpoint,
+            serde_json::json!("https://example.com/endpoint")
+        );
+        assert_eq!(
+            doc.service[0].property_set["routingKeys"],
+            serde_json::json!(["did:example:somemediator#somekey"])
+        );
+    }
+
+    fn create_input_doc() -> serde_json::Value {
+        serde_json::json!({
+            "@context": ["https://www.w3.org/ns/did/v1", "https://w3id.org/security/multikey/v1"],
+            "verificationMethod": [{
+                "id": "#key-1",
+                "type": "Multikey",
+                "publicKeyMultibase": ED25519
+            }],
+            "authentication": ["#key-1"],
+            "service": [{
+                "id": "#didcomm",
+                "type": "DIDCommMessaging",
+                "serviceEndpoint": {"uri": "didcomm:transport/queue", "accept": ["didcomm/v2"]}
+            }]
+        })
+    }
+
+    #[test]
+    fn test_numalgo_4() {
+        let (long_form, short_form) = create_did_4(&create_input_doc()).unwrap();
+        assert!(long_form.starts_with(&format!("{short_form}:z")));
+
+        let resolver = PeerResolver::new();
+        let res = aw!(resolver.read(&short_form));
+        assert_eq!(
+            res.unwrap_err().kind,
+            ssi::error::ErrorKind::DocumentNotFound
+        );
+
+        let doc = aw!(resolver.read(&long_form)).unwrap();
+        assert_eq!(doc.id, long_form);
+        assert_eq!(doc.also_known_as, vec![short_form.clone()]);
+        assert_eq!(
+            doc.find_verification_method("#key-1").unwrap().controller,
+            long_form
+        );
+
+        let doc = aw!(resolver.read(&short_form)).unwrap();
+        assert_eq!(doc.id, short_form);
+        assert_eq!(doc.also_known_as, vec![long_form]);
+    }
+
+    #[test]
+    fn test_numalgo_4_create() {
+        let (long_form, short_form) = create_did_4(&create_input_doc()).unwrap();
+        let resolver = PeerResolver::new();
+        let doc = aw!(resolver.read(&long_form)).unwrap();
+
+        let other = PeerResolver::new();
+        assert!(aw!(other.create(&long_form, doc)).is_ok());
+        assert!(aw!(other.read(&short_form)).is_ok());
+    }
+
+    #[rstest::rstest]
+    #[case::wrong_method(format!("did:key:0{ED25519}"))]
+    #[case::unknown_numalgo(format!("did:peer:1{ED25519}"))]
+    #[case::numalgo_2_without_elements("did:peer:2".to_string())]
+    #[case::numalgo_2_unknown_purpose(format!("did:peer:2.X{ED25519}"))]
+    #[case::numalgo_2_invalid_key("did:peer:2.Vz6Mk".to_string())]
+    #[case::numalgo_2_invalid_service(format!("did:peer:2.V{ED25519}.Snotjson"))]
+    #[case::numalgo_4_tampered({
+        let (long_form, _) = create_did_4(&create_input_doc()).unwrap();
+        let (other, _) = create_did_4(&serde_json::json!({"authentication": []})).unwrap();
+        format!("{}:{}", long_form.split(':').nth(2).map(|h| format!("did:peer:{h}")).unwrap(), other.split(':').nth(3).unwrap())
+    })]
+    fn test_read_failure(#[case] did: String) {
+        let res = aw!(PeerResolver::new().read(&did));
+        assert_eq!(res.unwrap_err().kind, ssi::error::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_get_method() {
+        assert_eq!(PeerResolver::new().get_method(), "peer");
+    }
+}
@@ -0,0 +1,192 @@
+use serde_json::{json, Map, Value};
+use ssi::did::{
+    multicodec::decode_multikey, DidDocument, VerificationMethod, VerificationMethodType,
+    VerificationRelationship,
+};
+use ssi::error::{ErrorKind, ResolverError};
+
+/// The purpose codes prefixing each element of a `did:peer:2` identifier.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Purpose {
+    Assertion,
+    Encryption,
+    Verification,
+    CapabilityInvocation,
+    CapabilityDelegation,
+}
+
+impl Purpose {
+    pub fn code(&self) -> char {
+        match self {
+            Purpose::Assertion => 'A',
+            Purpose::Encryption => 'E',
+            Purpose::Verification => 'V',
+            Purpose::CapabilityInvocation => 'I',
+            Purpose::CapabilityDelegation => 'D',
+        }
+    }
+
+    fn from_code(code: char) -> Option<Purpose> {
+        match code {
+            'A' => Some(Purpose::Assertion),
+            'E' => Some(Purpose::Encryption),
+            'V' => Some(Purpose::Verification),
+            'I' => Some(Purpose::CapabilityInvocation),
+            'D' => Some(Purpose::CapabilityDelegation),
+            _ => None,
+        }
+    }
+}
+
+/// A DIDComm messaging endpoint to be encoded into a `did:peer:2` identifier.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct DidCommService {
+    pub endpoint: String,
+    pub routing_keys: Vec<String>,
+    pub accept: Vec<String>,
+}
+
+const SERVICE_CODE: char = 'S';
+
+/// Abbreviations used by the compact service encoding and the terms they stand for.
+const ABBREVIATIONS: [(&str, &str); 5] = [
+    ("t", "type"),
+    ("s", "serviceEndpoint"),
+    ("r", "routingKeys"),
+    ("a", "accept"),
+    ("dm", "DIDCommMessaging"),
+];
+
+/// Create a `did:peer:2` identifier from multibase encoded keys and DIDComm services.
+pub fn create_did(
+    keys: &[(Purpose, String)],
+    services: &[DidCommService],
+) -> Result<String, ResolverError> {
+    let mut did = String::from("did:peer:2");
+
+    for (purpose, key) in keys {
+        decode_multikey(key)
+            .map_err(|e| ResolverError::new(e.to_string(), ErrorKind::InvalidData))?;
+        did.push('.');
+        did.push(purpose.code());
+        did.push_str(key);
+    }
+
+    for service in services {
+        let mut endpoint = Map::new();
+        endpoint.insert("uri".to_string(), Value::from(service.endpoint.clone()));
+        if !service.accept.is_empty() {
+            endpoint.insert("a".to_string(), json!(service.accept));
+        }
+        if !service.routing_keys.is_empty() {
+            endpoint.insert("r".to_string(), json!(service.routing_keys));
+        }
+        let encoded = serde_json::to_vec(&json!({"t": "dm", "s": endpoint}))
+            .map_err(|e| ResolverError::new(e.to_string(), ErrorKind::InvalidData))?;
+
+        did.push('.');
+        did.push(SERVICE_CODE);
+        did.push_str(&base64::encode_config(encoded, base64::URL_SAFE_NO_PAD));
+    }
+
+    return Ok(did);
+}
+
+/// Expand a `did:peer:2` identifier into its DID document.
+pub fn expand(did: &str) -> Result<DidDocument, ResolverError> {
+    let elements = did
+        .strip_prefix("did:peer:2.")
+        .ok_or_else(|| invalid(did, "must contain at least one element"))?;
+
+    let mut doc = DidDocument::new(did);
+    doc.context
+        .push(Value::from("https://w3id.org/security/multikey/v1"));
+
+    let mut services = 0;
+    for element in elements.split('.') {
+        let mut chars = element.chars();
+        let code = chars.next().ok_or_else(|| invalid(did, "empty element"))?;
+        let value = chars.as_str();
+
+        if code == SERVICE_CODE {
+            let decoded = base64::decode_config(value, base64::URL_SAFE_NO_PAD)
+                .map_err(|e| invalid(did, &e.to_string()))?;
+            let mut service: Value =
+                serde_json::from_slice(&decoded).map_err(|e| invalid(did, &e.to_string()))?;
+            expand_abbreviations(&mut service);
+
+            if service.get("id").is_none() {
+                let id = match services {
+                    0 => "#service".to_string(),
+                    n => format!("#service-{n}"),
+                };
+                service["id"] = Value::from(id);
+            }
+            doc.service
+                .push(serde_json::from_value(service).map_err(|e| invalid(did, &e.to_string()))?);
+            services += 1;
+            continue;
+        }
+
+        let purpose = Purpose::from_code(code)
+            .ok_or_else(|| invalid(did, &format!("unknown purpose code {code}")))?;
+        decode_multikey(value).map_err(|e| invalid(did, &e.to_string()))?;
+
+        let id = format!("#key-{}", doc.verification_method.len() + 1);
+        doc.verification_method.push(VerificationMethod {
+            id: id.clone(),
+            method_type: VerificationMethodType::Multikey,
+            controller: did.to_string(),
+            public_key_multibase: Some(value.to_string()),
+            public_key_base58: None,
+            public_key_jwk: None,
+            property_set: Default::default(),
+        });
+
+        let relationship = match purpose {
+            Purpose::Assertion => &mut doc.assertion_method,
+            Purpose::Encryption => &mut doc.key_agreement,
+            Purpose::Verification => &mut doc.authentication,
+            Purpose::CapabilityInvocation => &mut doc.capability_invocation,
+            Purpose::CapabilityDelegation => &mut doc.capability_delegation,
+        };
+        relationship.push(VerificationRelationship::Reference(id));
+    }
+
+    doc.validate()
+        .map_err(|e| ResolverError::new(e.to_string(), ErrorKind::InvalidData))?;
+    return Ok(doc);
+}
+
+fn expand_abbreviations(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            let entries: Vec<(String, Value)> = std::mem::take(map).into_iter().collect();
+            for (key, mut v) in entries {
+                expand_abbreviations(&mut v);
+                if key == "t" {
+                    if let Value::String(t) = &v {
+                        v = Value::from(expand_abbreviation(t));
+                    }
+                }
+                map.insert(expand_abbreviation(&key).to_string(), v);
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(expand_abbreviations),
+        _ => {}
+    }
+}
+
+fn expand_abbreviation(s: &str) -> &str {
+    return ABBREVIATIONS
+        .iter()
+        .find(|(abbreviation, _)| *abbreviation == s)
+        .map_or(s, |(_, term)| term);
+}
+
+fn invalid(did: &str, reason: &str) -> ResolverError {
+    ResolverError::new(
+        format!("{did} is not a valid did:peer:2 identifier: {reason}"),
+        ErrorKind::InvalidData,
+    )
+}
@@ -0,0 +1,119 @@
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use ssi::did::DidDocument;
+use ssi::error::{ErrorKind, ResolverError};
+
+const JSON_MULTICODEC: [u8; 2] = [0x80, 0x04];
+const SHA2_256_MULTIHASH: [u8; 2] = [0x12, 0x20];
+
+/// Create the long and short form `did:peer:4` identifiers of an input document, which is a DID document without `id`.
+pub fn create_did(input: &Value) -> Result<(String, String), ResolverError> {
+    if !input.is_object() || input.get("id").is_some() {
+        return Err(ResolverError::new(
+            "input document must be an object without an id",
+            ErrorKind::InvalidData,
+        ));
+    }
+
+    let mut bytes = JSON_MULTICODEC.to_vec();
+    bytes.extend(
+        serde_json::to_vec(input)
+            .map_err(|e| ResolverError::new(e.to_string(), ErrorKind::InvalidData))?,
+    );
+    let encoded = multibase::encode(multibase::Base::Base58Btc, bytes);
+    let hash = hash(&encoded);
+
+    return Ok((
+        format!("did:peer:4{hash}:{encoded}"),
+        format!("did:peer:4{hash}"),
+    ));
+}
+
+/// The short form of a long form `did:peer:4` identifier, checking that the hash matches the encoded document.
+pub fn short_form(did: &str) -> Result<String, ResolverError> {
+    let (hash_part, encoded) = split(did)?;
+    match encoded {
+        Some(encoded) if hash(encoded) == hash_part => Ok(format!("did:peer:4{hash_part}")),
+        Some(_) => Err(invalid(did, "hash does not match the encoded document")),
+        None => Err(invalid(did, "not a long form identifier")),
+    }
+}
+
+/// Whether the identifier is a short form `did:peer:4` identifier.
+pub fn is_short_form(did: &str) -> bool {
+    matches!(split(did), Ok((_, None)))
+}
+
+/// Resolve a long form `did:peer:4` identifier. The document is contextualized for `id`, which may be either form.
+pub fn expand(long_form: &str, id: &str) -> Result<DidDocument, ResolverError> {
+    let short = short_form(long_form)?;
+    let (_, encoded) = split(long_form)?;
+
+    let (_, bytes) = multibase::decode(encoded.unwrap_or_default())
+        .map_err(|e| invalid(long_form, &e.to_string()))?;
+    if !bytes.starts_with(&JSON_MULTICODEC) {
+        return Err(invalid(long_form, "document must be json encoded"));
+    }
+    let mut doc: Value =
+        serde_json::from_slice(&bytes[2..]).map_err(|e| invalid(long_form, &e.to_string()))?;
+    if !doc.is_object() || doc.get("id").is_some() {
+        return Err(invalid(
+            long_form,
+            "document must be an object without an id",
+        ));
+    }
+
+    doc["id"] = Value::from(id);
+    let other = match id == long_form {
+        true => short,
+        false => long_form.to_string(),
+    };
+    match doc.get_mut("alsoKnownAs").and_then(Value::as_array_mut) {
+        Some(aka) => aka.push(Value::from(other)),
+        None => doc["alsoKnownAs"] = Value::from(vec![other]),
+    }
+
+    // verification methods may omit their controller, which is then the DID itself
+    for property in [
+        "verificationMethod",
+        "authentication",
+        "assertionMethod",
+        "keyAgreement",
+        "capabilityInvocation",
+        "capabilityDelegation",
+    ] {
+        if let Some(methods) = doc.get_mut(property).and_then(Value::as_array_mut) {
+            for vm in methods.iter_mut().filter(|vm| vm.is_object()) {
+                if vm.get("controller").is_none() {
+                    vm["controller"] = Value::from(id);
+                }
+            }
+        }
+    }
+
+    return DidDocument::deserialize(doc)
+        .map_err(|e| ResolverError::new(e.to_string(), ErrorKind::InvalidData));
+}
+
+fn hash(encoded: &str) -> String {
+    let mut bytes = SHA2_256_MULTIHASH.to_vec();
+    bytes.extend(Sha256::digest(encoded.as_bytes()));
+    return multibase::encode(multibase::Base::Base58Btc, bytes);
+}
+
+fn split(did: &str) -> Result<(&str, Option<&str>), ResolverError> {
+    let rest = did
+        .strip_prefix("did:peer:4")
+        .ok_or_else(|| invalid(did, "not a did:peer:4 identifier"))?;
+    return match rest.split_once(':') {
+        Some((hash, encoded)) => Ok((hash, Some(encoded))),
+        None => Ok((rest, None)),
+    };
+}
+
+fn invalid(did: &str, reason: &str) -> ResolverError {
+    ResolverError::new(
+        format!("{did} is not a valid did:peer:4 identifier: {reason}"),
+        ErrorKind::InvalidData,
+    )
+}
@@ -0,0 +1,254 @@
+use signature::signer::DIDSigner;
+use signature::suite::{Ed25519Signature, VerificationRelation};
+use ssi::did::multicodec::KeyType;
+use ssi::did::DidDocument;
+use ssi::error::{ErrorKind, ResolverError};
+use ssi::proof::DataIntegrityProof;
+
+/// The bundled JSON-LD contexts do not cover DID documents, their structure is checked by `DidDocument::validate` instead.
+fn validation_options() -> ssi::validation::ValidationOptions {
+    return ssi::validation::ValidationOptions {
+        strict: false,
+        ..Default::default()
+    };
+}
+
+fn unauthorized(message: impl Into<String>) -> ResolverError {
+    return ResolverError::new(message, ErrorKind::Unauthorized);
+}
+
+/// The statement the proof of control of a deactivation is made over, binding it to the deactivated DID and to its
+/// registered version, so that the proof is void once the DID has changed.
+fn deactivation(did: &str, version_id: &str) -> serde_json::Value {
+    return serde_json::json!({ "id": did, "versionId": version_id, "deactivated": true });
+}
+
+/// Serialize the document sent to the registry with a `capabilityInvocation` Data Integrity proof made by `signer`
+/// attached as its `proof` property.
+pub fn sign_document(
+    document: &DidDocument,
+    signer: &(dyn DIDSigner<Ed25519Signature> + Send + Sync),
+) -> Result<serde_json::Value, ResolverError> {
+    let mut signed = document.serialize();
+    let proof = ssi::proof::create_data_integrity_proof_with_options(
+        signer,
+        signed.clone(),
+        VerificationRelation::CapabilityInvocation,
+        &validation_options(),
+    )
+    .map_err(|e| ResolverError::new(e.to_string(), ErrorKind::InvalidData))?;
+
+    signed["proof"] = serde_json::to_value(proof)
+        .map_err(|e| ResolverError::new(e.to_string(), ErrorKind::InvalidData))?;
+    return Ok(signed);
+}
+
+/// Create the `capabilityInvocation` proof of control of the deactivation of version `version_id` of `did`, the
+/// latest registered version, made by `signer` and sent as the `proof` of the deactivation request.
+pub fn sign_deactivation(
+    did: &str,
+    version_id: &str,
+    signer: &(dyn DIDSigner<Ed25519Signature> + Send + Sync),
+) -> Result<serde_json::Value, ResolverError> {
+    let proof = ssi::proof::create_data_integrity_proof_with_options(
+        signer,
+        deactivation(did, version_id),
+        VerificationRelation::CapabilityInvocation,
+        &validation_options(),
+    )
+    .map_err(|e| ResolverError::new(e.to_string(), ErrorKind::InvalidData))?;
+    return serde_json::to_value(proof)
+        .map_err(|e| ResolverError::new(e.to_string(), ErrorKind::InvalidData));
+}
+
+/// Verify the proof of control of the deactivation of `did`, which must be made for its latest registered version
+/// `version_id` with a `capabilityInvocation` method of the `registered` document of that version.
+pub fn verify_deactivation(
+    did: &str,
+    version_id: &str,
+    proof: serde_json::Value,
+    registered: &DidDocument,
+) -> Result<(), ResolverError> {
+    let mut signed = deactivation(did, version_id);
+    signed["proof"] = proof;
+    verify_signed_document(did, signed, Some(registered))?;
+    Ok(())
+}
+
+/// Verify the proof of control of a document submitted to the registry for `did` and return the unsigned document.
+/// The proof must be a `capabilityInvocation` proof made with a key of the `registered` document, or of the submitted
+/// document itself when the DID is not registered yet. Rejecting proofs older than the registered version, so that
+/// earlier updates cannot be replayed, is left to the registry.
+pub fn verify_signed_document(
+    did: &str,
+    mut signed: serde_json::Value,
+    registered: Option<&DidDocument>,
+) -> Result<DidDocument, ResolverError> {
+    let proof = signed
+        .as_object_mut()
+        .and_then(|doc| doc.remove("proof"))
+        .ok_or_else(|| unauthorized(format!("document of {did} has no proof")))?;
+    let proof: DataIntegrityProof = serde_json::from_value(proof)
+        .map_err(|e| ResolverError::new(e.to_string(), ErrorKind::InvalidData))?;
+
+    let document = DidDocument::deserialize(signed.clone())
+        .map_err(|e| ResolverError::new(e.to_string(), ErrorKind::InvalidData))?;
+    if document.id != did {
+        return Err(ResolverError::new(
+            format!("document id {} does not match {did}", document.id),
+            ErrorKind::InvalidData,
+        ));
+    }
+
+    let relation = VerificationRelation::CapabilityInvocation;
+    if proof.proof_purpose != relation.to_string() {
+        return Err(unauthorized(format!(
+            "proof purpose {} is not {relation}",
+            proof.proof_purpose
+        )));
+    }
+
+    let authority = registered.unwrap_or(&document);
+    let vm_id = &proof.verification_method;
+    let vm = authority
+        .find_verification_method(vm_id)
+        .filter(|_| authority.has_relationship(vm_id, relation))
+        .ok_or_else(|| {
+            unauthorized(format!(
+                "{vm_id} is not a {relation} method of {}",
+                authority.id
+            ))
+        })?;
+
+    let public_key = match vm.public_key() {
+        Ok((KeyType::Ed25519, public_key)) => public_key,
+        Ok((key_type, _)) => {
+            return Err(unauthorized(format!(
+                "{vm_id} is a {key_type:?} key, only Ed25519 proofs are supported"
+            )))
+        }
+        Err(e) => return Err(ResolverError::new(e.to_string(), ErrorKind::InvalidData)),
+    };
+    let verifier = signature::verifier::Ed25519DidVerifier::try_from(public_key.as_slice())
+        .map_err(|e| ResolverError::new(e.to_string(), ErrorKind::InvalidData))?;
+
+    ssi::proof::verify_proof_value(signed, &proof, &verifier)
+        .map_err(|e| unauthorized(format!("proof of {did} is invalid: {e}")))?;
+    return Ok(document);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{sign_deactivation, sign_document, verify_deactivation, verify_signed_document};
+    use signature::signer::Ed25519DidSigner;
+    use signature::suite::VerificationRelation;
+    use ssi::error::ErrorKind;
+    use ssi::test_utils::signer_document;
+
+    #[rstest::rstest]
+    #[case::create(false, VerificationRelation::CapabilityInvocation, None, None)]
+    #[case::update(true, VerificationRelation::CapabilityInvocation, None, None)]
+    #[case::not_capability_invocation(
+        false,
+        VerificationRelation::Authentication,
+        None,
+        Some(ErrorKind::Unauthorized)
+    )]
+    #[case::tampered(
+        false,
+        VerificationRelation::CapabilityInvocation,
+        Some(("alsoKnownAs", serde_json::json!(["https://example.com/squatter"]))),
+        Some(ErrorKind::Unauthorized)
+    )]
+    #[case::wrong_purpose(
+        false,
+        VerificationRelation::CapabilityInvocation,
+        Some(("proof", serde_json::json!({"verificationPurpose": "assertionMethod"}))),
+        Some(ErrorKind::Unauthorized)
+    )]
+    #[case::unsigned(
+        false,
+        VerificationRelation::CapabilityInvocation,
+        Some(("proof", serde_json::Value::Null)),
+        Some(ErrorKind::Unauthorized)
+    )]
+    #[case::wrong_id(
+        false,
+        VerificationRelation::CapabilityInvocation,
+        Some(("id", serde_json::json!("did:knox:z6MkfFmsob7fC3MmqU1JVfdBnMbnAw7xm1mrEtPvAoojLcRh"))),
+        Some(ErrorKind::InvalidData)
+    )]
+    fn test_verify_signed_document(
+        #[case] registered: bool,
+        #[case] relation: VerificationRelation,
+        #[case] tamper: Option<(&str, serde_json::Value)>,
+        #[case] expect_error_kind: Option<ErrorKind>,
+    ) {
+        let signer = Ed25519DidSigner::new();
+        let doc = signer_document(&signer, &[relation]);
+        let mut signed = sign_document(&doc, &signer).unwrap();
+        match tamper {
+            Some(("proof", serde_json::Value::Null)) => {
+                signed.as_object_mut().unwrap().remove("proof");
+            }
+            Some(("proof", serde_json::Value::Object(fields))) => {
+                for (key, value) in fields {
+                    signed["proof"][key] = value;
+                }
+            }
+            Some((key, value)) => signed[key] = value,
+            None => {}
+        }
+
+        let registered = registered.then(|| doc.clone());
+        let res = verify_signed_document(&doc.id, signed, registered.as_ref());
+        match expect_error_kind {
+            Some(kind) => assert_eq!(res.unwrap_err().kind, kind),
+            None => assert_eq!(res.unwrap(), doc),
+        }
+    }
+
+    #[test]
+    fn test_verify_signed_document_rotated_key() {
+        let signer = Ed25519DidSigner::new();
+        let registered = signer_document(&signer, &[VerificationRelation::CapabilityInvocation]);
+
+        // a new key may only take over the DID if the registered document authorizes it
+        let other = Ed25519DidSigner::new();
+        let mut doc = signer_document(&other, &[VerificationRelation::CapabilityInvocation]);
+        doc.id = registered.id.clone();
+        doc.verification_method[0].controller = registered.id.clone();
+        let signed = sign_document(&doc, &other).unwrap();
+
+        let res = verify_signed_document(&registered.id, signed, Some(&registered));
+        assert_eq!(res.unwrap_err().kind, ErrorKind::Unauthorized);
+    }
+
+    #[rstest::rstest]
+    #[case::registered_key(false, false, "2", None)]
+    #[case::other_key(true, false, "2", Some(ErrorKind::Unauthorized))]
+    #[case::other_did(false, true, "2", Some(ErrorKind::Unauthorized))]
+    #[case::other_version(false, false, "1", Some(ErrorKind::Unauthorized))]
+    fn test_verify_deactivation(
+        #[case] signed_by_other: bool,
+        #[case] other_did: bool,
+        #[case] version_id: &str,
+        #[case] expect_error_kind: Option<ErrorKind>,
+    ) {
+        let signer = Ed25519DidSigner::new();
+        let other = Ed25519DidSigner::new();
+        let registered = signer_document(&signer, &[VerificationRelation::CapabilityInvocation]);
+        let did = match other_did {
+            true => signer_document(&other, &[]).id,
+            false => registered.id.clone(),
+        };
+        let proof = match signed_by_other {
+            true => sign_deactivation(&did, version_id, &other).unwrap(),
+            false => sign_deactivation(&did, version_id, &signer).unwrap(),
+        };
+
+        // the proof is checked against the DID and the latest version of the request
+        let res = verify_deactivation(&registered.id, "2", proof, &registered);
+        assert_eq!(res.err().map(|e| e.kind), expect_error_kind);
+    }
+}
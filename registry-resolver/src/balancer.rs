@@ -0,0 +1,400 @@
+use crate::config::{is_transient, LoadBalancing, RegistryResolverConfig, RetryPolicy};
+use crate::registry_client::{registry, GrpcClient, RegistryClient};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+struct Replica {
+    client: Box<dyn RegistryClient + Send + Sync>,
+    /// When the replica last failed with a transient error, cleared once it answers again.
+    failed_at: Mutex<Option<Instant>>,
+    /// Moving average of the response time in microseconds, `0` until the replica has answered.
+    latency: AtomicU64,
+}
+
+impl Replica {
+    fn new(client: Box<dyn RegistryClient + Send + Sync>) -> Self {
+        return Self {
+            client,
+            failed_at: Mutex::new(None),
+            latency: AtomicU64::new(0),
+        };
+    }
+
+    fn is_healthy(&self, now: Instant, health_check_interval: Duration) -> bool {
+        return match *self.failed_at.lock().unwrap() {
+            Some(failed_at) => now.duration_since(failed_at) >= health_check_interval,
+            None => true,
+        };
+    }
+
+    fn failed(&self) {
+        *self.failed_at.lock().unwrap() = Some(Instant::now());
+    }
+
+    fn answered(&self, elapsed: Duration) {
+        *self.failed_at.lock().unwrap() = None;
+        let sample = (elapsed.as_micros() as u64).max(1);
+        let latency = match self.latency.load(Ordering::Relaxed) {
+            0 => sample,
+            average => (average * 7 + sample) / 8,
+        };
+        self.latency.store(latency, Ordering::Relaxed);
+    }
+}
+
+/// Registry client balancing requests over the replicas of the registry.
+/// A replica failing with a transient error is skipped for `health_check_interval`, the request failing over to
+/// the other replicas, which are retried with the `RetryPolicy` of the configuration once all of them failed.
+/// Writes are only sent to the first replica in the balancing order, see `RetryPolicy`.
+/// Reads that do not find a document are repeated on the other replicas, since a replica may lag behind the one
+/// that accepted a write.
+pub struct BalancedClient {
+    replicas: Vec<Replica>,
+    load_balancing: LoadBalancing,
+    retry: RetryPolicy,
+    health_check_interval: Duration,
+    next: AtomicUsize,
+}
+
+impl BalancedClient {
+    fn new(
+        clients: Vec<Box<dyn RegistryClient + Send + Sync>>,
+        config: &RegistryResolverConfig,
+    ) -> Self {
+        return Self {
+            replicas: clients.into_iter().map(Replica::new).collect(),
+            load_balancing: config.load_balancing,
+            retry: config.retry.clone(),
+            health_check_interval: config.health_check_interval,
+            next: AtomicUsize::new(0),
+        };
+    }
+
+    /// Connect to `url` and the replicas of the configuration. Unreachable replicas are connected lazily and start
+    /// out as failed, connecting only fails when none of the replicas can be reached.
+    pub async fn connect(
+        config: &RegistryResolverConfig,
+    ) -> Result<Self, ssi::error::ResolverError> {
+        let mut clients: Vec<Box<dyn RegistryClient + Send + Sync>> = vec![];
+        let mut unreachable = vec![];
+        let mut error = None;
+        for url in std::iter::once(&config.url).chain(&config.replicas) {
+            let replica_config = RegistryResolverConfig {
+                url: url.clone(),
+                retry: RetryPolicy::none(),
+                replicas: vec![],
+                ..config.clone()
+            };
+            match GrpcClient::connect(&replica_config).await {
+                Ok(client) => clients.push(Box::new(client)),
+                Err(e) if e.kind == ssi::error::ErrorKind::NetworkFailure => {
+                    let lazy_config = RegistryResolverConfig {
+                        lazy: true,
+                        ..replica_config
+                    };
+                    unreachable.push(clients.len());
+                    clients.push(Box::new(GrpcClient::connect(&lazy_config).await?));
+                    error = Some(e);
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        if unreachable.len() == clients.len() {
+            return Err(error.expect("at least one replica is configured"));
+        }
+        let client = Self::new(clients, config);
+        for index in unreachable {
+            client.replicas[index].failed();
+        }
+        return Ok(client);
+    }
+
+    /// Indices of the replicas in the order they are tried, replicas that recently failed coming last.
+    fn order(&self) -> Vec<usize> {
+        let count = self.replicas.len();
+        let mut order: Vec<usize> = match self.load_balancing {
+            LoadBalancing::RoundRobin => {
+                let start = self.next.fetch_add(1, Ordering::Relaxed) % count;
+                (0..count).map(|i| (start + i) % count).collect()
+            }
+            LoadBalancing::LeastLatency => {
+                let mut order: Vec<usize> = (0..count).collect();
+                order.sort_by_key(|i| self.replicas[*i].latency.load(Ordering::Relaxed));
+                order
+            }
+        };
+
+        let now = Instant::now();
+        order.sort_by_key(|i| !self.replicas[*i].is_healthy(now, self.health_check_interval));
+        return order;
+    }
+
+    /// Send the read `request` to the replicas until one answers. A `NotFound` answer is only returned once every
+    /// replica gave it.
+    async fn read_any<'a, T, F, Fut>(&'a self, mut request: F) -> Result<T, tonic::Status>
+    where
+        F: FnMut(&'a (dyn RegistryClient + Send + Sync)) -> Fut,
+        Fut: std::future::Future<Output = Result<T, tonic::Status>>,
+    {
+        let mut retry = 0;
+        loop {
+            let mut transient = None;
+            let mut not_found = None;
+            for index in self.order() {
+                let replica = &self.replicas[index];
+                let start = Instant::now();
+                match request(replica.client.as_ref()).await {
+                    Err(status) if is_transient(status.code()) => {
+                        replica.failed();
+                        transient = Some(status);
+                    }
+                    Err(status) if status.code() == tonic::Code::NotFound => {
+                        replica.answered(start.elapsed());
+                        not_found = Some(status);
+                    }
+                    res => {
+                        replica.answered(start.elapsed());
+                        return res;
+                    }
+                }
+            }
+
+            if transient.is_some() && retry < self.retry.max_retries {
+                tokio::time::sleep(self.retry.backoff(retry)).await;
+                retry += 1;
+                continue;
+            }
+            return Err(not_found
+                .or(transient)
+                .expect("at least one replica is configured"));
+        }
+    }
+
+    /// Send the write `request` to the first replica in the balancing order only. It is neither retried nor failed
+    /// over, since the replica may have applied the write before failing.
+    async fn write<'a, T, F, Fut>(&'a self, request: F) -> Result<T, tonic::Status>
+    where
+        F: FnOnce(&'a (dyn RegistryClient + Send + Sync)) -> Fut,
+        Fut: std::future::Future<Output = Result<T, tonic::Status>>,
+    {
+        let replica = &self.replicas[self.order()[0]];
+        let start = Instant::now();
+        let res = request(replica.client.as_ref()).await;
+        match &res {
+            Err(status) if is_transient(status.code()) => replica.failed(),
+            _ => replica.answered(start.elapsed()),
+        }
+        return res;
+    }
+}
+
+#[async_trait::async_trait]
+impl RegistryClient for BalancedClient {
+    async fn create(
+        &self,
+        did: String,
+        document: Option<pbjson_types::Struct>,
+    ) -> Result<tonic::Response<registry::CreateResponse>, tonic::Status> {
+        self.write(|client| client.create(did, document)).await
+    }
+
+    async fn read(
+        &self,
+        did: String,
+    ) -> Result<tonic::Response<registry::ReadResponse>, tonic::Status> {
+        self.read_any(|client| client.read(did.clone())).await
+    }
+
+    async fn update(
+        &self,
+        did: String,
+        document: Option<pbjson_types::Struct>,
+    ) -> Result<tonic::Response<registry::UpdateResponse>, tonic::Status> {
+        self.write(|client| client.update(did, document)).await
+    }
+
+    async fn deactivate(
+        &self,
+        did: String,
+        proof: Option<pbjson_types::Struct>,
+    ) -> Result<tonic::Response<registry::DeactivateResponse>, tonic::Status> {
+        self.write(|client| client.deactivate(did, proof)).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BalancedClient;
+    use crate::config::{LoadBalancing, RegistryResolverConfig, RetryPolicy};
+    use crate::registry_client::registry::ReadResponse;
+    use crate::registry_client::{MockRegistryClient, RegistryClient};
+    use std::sync::atomic::Ordering;
+    use std::time::Duration;
+
+    macro_rules! aw {
+        ($e:expr) => {
+            tokio_test::block_on($e)
+        };
+    }
+
+    fn create_client(
+        replicas: Vec<MockRegistryClient>,
+        load_balancing: LoadBalancing,
+        max_retries: u32,
+    ) -> BalancedClient {
+        let config = RegistryResolverConfig {
+            retry: RetryPolicy {
+                max_retries,
+                initial_backoff: Duration::from_millis(1),
+                max_backoff: Duration::from_millis(1),
+            },
+            load_balancing,
+            ..RegistryResolverConfig::new("http://registry.test")
+        };
+        let clients = replicas
+            .into_iter()
+            .map(|replica| Box::new(replica) as Box<dyn RegistryClient + Send + Sync>)
+            .collect();
+        return BalancedClient::new(clients, &config);
+    }
+
+    /// Replica answering `times` reads with `code`, or with a document when `code` is `Ok`.
+    fn create_replica(times: usize, code: tonic::Code) -> MockRegistryClient {
+        let mut replica = MockRegistryClient::new();
+        replica.expect_read().times(times).returning(move |did| {
+            if code != tonic::Code::Ok {
+                return Err(tonic::Status::new(code, did));
+            }
+            return Ok(tonic::Response::new(ReadResponse {
+                did,
+                document: None,
+                metadata: None,
+            }));
+        });
+        return replica;
+    }
+
+    #[test]
+    fn test_round_robin() {
+        let replicas = vec![
+            create_replica(2, tonic::Code::Ok),
+            create_replica(2, tonic::Code::Ok),
+            create_replica(2, tonic::Code::Ok),
+        ];
+        let client = create_client(replicas, LoadBalancing::RoundRobin, 0);
+        for _ in 0..6 {
+            assert!(aw!(client.read("did:knox:z123".to_string())).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_least_latency() {
+        let replicas = vec![
+            create_replica(0, tonic::Code::Ok),
+            create_replica(2, tonic::Code::Ok),
+        ];
+        let client = create_client(replicas, LoadBalancing::LeastLatency, 0);
+        client.replicas[0]
+            .latency
+            .store(u64::MAX, Ordering::Relaxed);
+        for _ in 0..2 {
+            assert!(aw!(client.read("did:knox:z123".to_string())).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_failover() {
+        // the unavailable replica is left out of the balancing once it failed
+        let replicas = vec![
+            create_replica(1, tonic::Code::Unavailable),
+            create_replica(3, tonic::Code::Ok),
+        ];
+        let client = create_client(replicas, LoadBalancing::RoundRobin, 0);
+        for _ in 0..3 {
+            assert!(aw!(client.read("did:knox:z123".to_string())).is_ok());
+        }
+        assert!(
+            !client.replicas[0].is_healthy(std::time::Instant::now(), client.health_check_interval)
+        );
+    }
+
+    #[rstest::rstest]
+    #[case::lagging_replica(tonic::Code::NotFound, tonic::Code::Ok, None)]
+    #[case::not_found(
+        tonic::Code::NotFound,
+        tonic::Code::NotFound,
+        Some(tonic::Code::NotFound)
+    )]
+    #[case::not_found_and_unavailable(
+        tonic::Code::NotFound,
+        tonic::Code::Unavailable,
+        Some(tonic::Code::NotFound)
+    )]
+    #[case::invalid(
+        tonic::Code::InvalidArgument,
+        tonic::Code::Ok,
+        Some(tonic::Code::InvalidArgument)
+    )]
+    fn test_consistent_read(
+        #[case] first: tonic::Code,
+        #[case] second: tonic::Code,
+        #[case] expect_code: Option<tonic::Code>,
+    ) {
+        let second_attempts = match first {
+            tonic::Code::NotFound => 1,
+            _ => 0,
+        };
+        let replicas = vec![
+            create_replica(1, first),
+            create_replica(second_attempts, second),
+        ];
+        let client = create_client(replicas, LoadBalancing::RoundRobin, 0);
+        let res = aw!(client.read("did:knox:z123".to_string()));
+        assert_eq!(res.err().map(|status| status.code()), expect_code);
+    }
+
+    #[test]
+    fn test_all_unavailable() {
+        let replicas = vec![
+            create_replica(3, tonic::Code::Unavailable),
+            create_replica(3, tonic::Code::Unavailable),
+        ];
+        let client = create_client(replicas, LoadBalancing::RoundRobin, 2);
+        let res = aw!(client.read("did:knox:z123".to_string()));
+        assert_eq!(res.unwrap_err().code(), tonic::Code::Unavailable);
+    }
+
+    #[rstest::rstest]
+    #[case::unavailable(tonic::Code::Unavailable)]
+    #[case::timed_out(tonic::Code::DeadlineExceeded)]
+    fn test_write_no_failover(#[case] code: tonic::Code) {
+        // the first replica may have applied the write before failing
+        let mut first = MockRegistryClient::new();
+        first
+            .expect_update()
+            .times(1)
+            .returning(move |did, _| Err(tonic::Status::new(code, did)));
+        let mut second = MockRegistryClient::new();
+        second.expect_update().times(0);
+
+        let client = create_client(vec![first, second], LoadBalancing::RoundRobin, 2);
+        let res = aw!(client.update("did:knox:z123".to_string(), None));
+        assert_eq!(res.unwrap_err().code(), code);
+    }
+
+    #[test]
+    fn test_connect_unreachable() {
+        let config = RegistryResolverConfig {
+            connect_timeout: Some(Duration::from_millis(100)),
+            replicas: vec!["http://127.0.0.1:2".to_string()],
+            ..RegistryResolverConfig::new("http://127.0.0.1:1")
+        };
+        let res = aw!(BalancedClient::connect(&config));
+        assert_eq!(
+            res.err().map(|e| e.kind),
+            Some(ssi::error::ErrorKind::NetworkFailure)
+        );
+    }
+}
@@ -0,0 +1,284 @@
+use std::time::Duration;
+
+/// Connection settings of a `RegistryResolver`.
+#[derive(Clone, PartialEq)]
+pub struct RegistryResolverConfig {
+    /// URL of the registry's gRPC endpoint, e.g. `http://localhost:50051`.
+    pub url: String,
+    /// Time allowed to establish the connection. No limit when not set.
+    pub connect_timeout: Option<Duration>,
+    /// Time allowed for each attempt of a request. No limit when not set.
+    pub request_timeout: Option<Duration>,
+    /// Connect on the first request instead of when the resolver is created, so that an unavailable registry
+    /// does not prevent the resolver from being created.
+    pub lazy: bool,
+    pub retry: RetryPolicy,
+    /// Compress requests and accept compressed responses with gzip.
+    pub gzip: bool,
+    /// Connect over TLS, the `url` must use the `https` scheme.
+    pub tls: Option<TlsConfig>,
+    /// Token sent as `authorization: Bearer <token>` metadata with every request.
+    pub bearer_token: Option<String>,
+    /// URLs of further replicas of the registry. Requests are balanced over `url` and the replicas,
+    /// and reads fail over to another replica when one is unavailable.
+    pub replicas: Vec<String>,
+    pub load_balancing: LoadBalancing,
+    /// Time a replica that failed with a transient error is left out of the balancing before it is tried again.
+    pub health_check_interval: Duration,
+}
+
+/// How requests are distributed over the replicas of the registry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LoadBalancing {
+    /// Take turns between the healthy replicas.
+    RoundRobin,
+    /// Prefer the healthy replica with the lowest average response time.
+    LeastLatency,
+}
+
+/// TLS settings of the registry connection. The server certificate is verified against the platform's trusted roots
+/// and `ca_certificate`, a client certificate is presented for mutual TLS when `client_certificate` and `client_key` are set.
+#[derive(Clone, Default, PartialEq)]
+pub struct TlsConfig {
+    /// PEM encoded certificate of an additional CA to trust.
+    pub ca_certificate: Option<String>,
+    /// PEM encoded client certificate chain.
+    pub client_certificate: Option<String>,
+    /// PEM encoded private key of the client certificate.
+    pub client_key: Option<String>,
+    /// Name to verify the server certificate against and send as SNI, instead of the host of the `url`.
+    pub domain_name: Option<String>,
+}
+
+/// Private keys and tokens are not printed.
+fn redact(secret: &Option<String>) -> Option<&'static str> {
+    return secret.as_ref().map(|_| "<redacted>");
+}
+
+impl std::fmt::Debug for RegistryResolverConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("RegistryResolverConfig")
+            .field("url", &self.url)
+            .field("connect_timeout", &self.connect_timeout)
+            .field("request_timeout", &self.request_timeout)
+            .field("lazy", &self.lazy)
+            .field("retry", &self.retry)
+            .field("gzip", &self.gzip)
+            .field("tls", &self.tls)
+            .field("bearer_token", &redact(&self.bearer_token))
+            .field("replicas", &self.replicas)
+            .field("load_balancing", &self.load_balancing)
+            .field("health_check_interval", &self.health_check_interval)
+            .finish()
+    }
+}
+
+impl std::fmt::Debug for TlsConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("TlsConfig")
+            .field("ca_certificate", &self.ca_certificate)
+            .field("client_certificate", &self.client_certificate)
+            .field("client_key", &redact(&self.client_key))
+            .field("domain_name", &self.domain_name)
+            .finish()
+    }
+}
+
+impl TlsConfig {
+    pub(crate) fn to_client_tls_config(
+        &self,
+    ) -> Result<tonic::transport::ClientTlsConfig, ssi::error::ResolverError> {
+        let mut config = tonic::transport::ClientTlsConfig::new();
+        if let Some(ca_certificate) = &self.ca_certificate {
+            config = config.ca_certificate(tonic::transport::Certificate::from_pem(ca_certificate));
+        }
+        if let Some(domain_name) = &self.domain_name {
+            config = config.domain_name(domain_name);
+        }
+
+        return match (&self.client_certificate, &self.client_key) {
+            (Some(certificate), Some(key)) => {
+                Ok(config.identity(tonic::transport::Identity::from_pem(certificate, key)))
+            }
+            (None, None) => Ok(config),
+            _ => Err(ssi::error::ResolverError::new(
+                "mutual TLS requires both a client certificate and its key",
+                ssi::error::ErrorKind::InvalidData,
+            )),
+        };
+    }
+}
+
+/// Interceptor adding the bearer token of the configuration to the metadata of each request.
+#[derive(Clone)]
+pub(crate) struct BearerToken(Option<tonic::metadata::MetadataValue<tonic::metadata::Ascii>>);
+
+impl BearerToken {
+    pub(crate) fn new(token: &Option<String>) -> Result<Self, ssi::error::ResolverError> {
+        let value = match token {
+            Some(token) => Some(format!("Bearer {token}").parse().map_err(|_| {
+                ssi::error::ResolverError::new(
+                    "bearer token must be visible ASCII",
+                    ssi::error::ErrorKind::InvalidData,
+                )
+            })?),
+            None => None,
+        };
+        return Ok(Self(value));
+    }
+}
+
+impl tonic::service::Interceptor for BearerToken {
+    fn call(
+        &mut self,
+        mut request: tonic::Request<()>,
+    ) -> Result<tonic::Request<()>, tonic::Status> {
+        if let Some(value) = &self.0 {
+            request
+                .metadata_mut()
+                .insert("authorization", value.clone());
+        }
+        Ok(request)
+    }
+}
+
+impl RegistryResolverConfig {
+    pub fn new(url: impl Into<String>) -> Self {
+        return Self {
+            url: url.into(),
+            connect_timeout: Some(Duration::from_secs(5)),
+            request_timeout: Some(Duration::from_secs(10)),
+            lazy: false,
+            retry: RetryPolicy::default(),
+            gzip: false,
+            tls: None,
+            bearer_token: None,
+            replicas: vec![],
+            load_balancing: LoadBalancing::RoundRobin,
+            health_check_interval: Duration::from_secs(30),
+        };
+    }
+}
+
+/// Retries of requests that failed with a transient `tonic::Status`, with exponential backoff between attempts.
+/// Reads are retried when the registry is unavailable, overloaded or did not answer in time. Writes are not retried,
+/// since a write that failed may still have been applied and repeating it would be rejected or register another version.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryPolicy {
+    /// Retries after the first attempt, `0` disables retries.
+    pub max_retries: u32,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(2),
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn none() -> Self {
+        return Self {
+            max_retries: 0,
+            ..Default::default()
+        };
+    }
+
+    /// The backoff before retry `retry`, counting from `0`. It doubles with each retry up to `max_backoff`.
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let factor = 2u32.saturating_pow(retry);
+        return self
+            .initial_backoff
+            .checked_mul(factor)
+            .unwrap_or(self.max_backoff)
+            .min(self.max_backoff);
+    }
+
+    /// Call `request` until it succeeds, fails with a status that is not transient, or the retries are exhausted.
+    /// Only for reads, writes call the registry once.
+    pub(crate) async fn run<T, F, Fut>(&self, mut request: F) -> Result<T, tonic::Status>
+    where
+        F: FnMut() -> Fut,
+        Fut: std::future::Future<Output = Result<T, tonic::Status>>,
+    {
+        let mut retry = 0;
+        loop {
+            match request().await {
+                Err(status) if retry < self.max_retries && is_transient(status.code()) => {
+                    tokio::time::sleep(self.backoff(retry)).await;
+                    retry += 1;
+                }
+                res => return res,
+            }
+        }
+    }
+}
+
+/// Failures after which the registry may answer again, because it was unavailable, overloaded or did not answer in time.
+pub(crate) fn is_transient(code: tonic::Code) -> bool {
+    return matches!(
+        code,
+        tonic::Code::Unavailable | tonic::Code::DeadlineExceeded | tonic::Code::ResourceExhausted
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RetryPolicy;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::time::Duration;
+
+    macro_rules! aw {
+        ($e:expr) => {
+            tokio_test::block_on($e)
+        };
+    }
+
+    #[rstest::rstest]
+    #[case(0, Duration::from_millis(100))]
+    #[case(1, Duration::from_millis(200))]
+    #[case(3, Duration::from_millis(800))]
+    #[case(5, Duration::from_secs(2))]
+    #[case(40, Duration::from_secs(2))]
+    fn test_backoff(#[case] retry: u32, #[case] expect_backoff: Duration) {
+        assert_eq!(RetryPolicy::default().backoff(retry), expect_backoff);
+    }
+
+    #[rstest::rstest]
+    #[case::success(vec![], Ok(()), 1)]
+    #[case::recovered(
+        vec![tonic::Code::Unavailable, tonic::Code::DeadlineExceeded],
+        Ok(()),
+        3
+    )]
+    #[case::exhausted(vec![tonic::Code::Unavailable; 5], Err(tonic::Code::Unavailable), 3)]
+    #[case::permanent(vec![tonic::Code::NotFound], Err(tonic::Code::NotFound), 1)]
+    fn test_run(
+        #[case] failures: Vec<tonic::Code>,
+        #[case] expect_result: Result<(), tonic::Code>,
+        #[case] expect_attempts: u32,
+    ) {
+        let policy = RetryPolicy {
+            max_retries: 2,
+            initial_backoff: Duration::from_millis(1),
+            max_backoff: Duration::from_millis(1),
+        };
+        let attempts = AtomicU32::new(0);
+        let res = aw!(policy.run(|| {
+            let attempt = attempts.fetch_add(1, Ordering::SeqCst) as usize;
+            let res = match failures.get(attempt) {
+                Some(code) => Err(tonic::Status::new(*code, "message")),
+                None => Ok(()),
+            };
+            async move { res }
+        }));
+
+        assert_eq!(res.map_err(|status| status.code()), expect_result);
+        assert_eq!(attempts.load(Ordering::SeqCst), expect_attempts);
+    }
+}
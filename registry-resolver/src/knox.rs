@@ -0,0 +1,222 @@
+use crate::{verify_signed_document, DID_METHOD};
+use ssi::did::multicodec::{decode_multikey, KeyType};
+use ssi::did::{Did, DidDocument};
+use ssi::error::{ErrorKind, ResolverError};
+
+/// Create the `did:knox` identifier of an Ed25519 public key, the base58btc encoding used by `Ed25519DidSigner`.
+pub fn create_did(public_key: &[u8]) -> String {
+    return format!(
+        "did:{DID_METHOD}:{}",
+        multibase::encode(multibase::Base::Base58Btc, public_key)
+    );
+}
+
+/// Decode the Ed25519 public key that a `did:knox` identifier is derived from.
+/// The method-specific id is the base58btc multibase encoding of the key, with or without its multicodec prefix.
+pub fn decode_did(did: &str) -> Result<Vec<u8>, ResolverError> {
+    let parsed =
+        Did::parse(did).map_err(|e| ResolverError::new(e.to_string(), ErrorKind::InvalidDid))?;
+    if parsed.method() != DID_METHOD {
+        return Err(ResolverError::new(
+            format!("{did} is not a did:{DID_METHOD} identifier"),
+            ErrorKind::InvalidDid,
+        ));
+    }
+
+    let encoded = parsed.method_specific_id();
+    if !encoded.starts_with('z') {
+        return Err(ResolverError::new(
+            format!("{did} must be base58btc encoded"),
+            ErrorKind::InvalidDid,
+        ));
+    }
+    let (_, bytes) = multibase::decode(encoded)
+        .map_err(|e| ResolverError::new(e.to_string(), ErrorKind::InvalidDid))?;
+    if bytes.len() == KeyType::Ed25519.key_length() {
+        return Ok(bytes);
+    }
+
+    return match decode_multikey(encoded) {
+        Ok((KeyType::Ed25519, public_key)) => Ok(public_key),
+        _ => Err(ResolverError::new(
+            format!("{did} does not encode an Ed25519 public key"),
+            ErrorKind::InvalidDid,
+        )),
+    };
+}
+
+/// Check that the document belongs to `did` and contains the key the identifier encodes,
+/// so that a registry cannot substitute the keys of a `did:knox` identifier.
+pub fn verify_document(did: &str, document: &DidDocument) -> Result<(), ResolverError> {
+    let public_key = decode_did(did)?;
+    if document.id != did {
+        return Err(ResolverError::new(
+            format!("document id {} does not match {did}", document.id),
+            ErrorKind::InvalidData,
+        ));
+    }
+
+    let found = document
+        .verification_methods()
+        .any(|vm| matches!(vm.public_key(), Ok((KeyType::Ed25519, key)) if key == public_key));
+    if !found {
+        return Err(ResolverError::new(
+            format!("document of {did} does not contain the key of the identifier"),
+            ErrorKind::InvalidData,
+        ));
+    }
+
+    Ok(())
+}
+
+/// Verify the `capabilityInvocation` proof of a version of the document of `did` and return the document without it.
+/// A version is authorized by a key of the `previous` version. The first version has no earlier document to authorize
+/// it, so it must be signed with the key the identifier encodes rather than any key it lists.
+pub fn verify_version(
+    did: &str,
+    signed: serde_json::Value,
+    previous: Option<&DidDocument>,
+) -> Result<DidDocument, ResolverError> {
+    let vm_id = signed["proof"]["verificationMethod"]
+        .as_str()
+        .unwrap_or_default()
+        .to_string();
+    let document = verify_signed_document(did, signed, previous)?;
+    verify_document(did, &document)?;
+    if previous.is_some() {
+        return Ok(document);
+    }
+
+    let public_key = decode_did(did)?;
+    let signed_by_identifier = document
+        .find_verification_method(&vm_id)
+        .map(|vm| matches!(vm.public_key(), Ok((KeyType::Ed25519, key)) if key == public_key));
+    if signed_by_identifier != Some(true) {
+        return Err(ResolverError::new(
+            format!("first version of {did} is not signed with the key of the identifier"),
+            ErrorKind::Unauthorized,
+        ));
+    }
+    return Ok(document);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{create_did, decode_did, verify_document, verify_version};
+    use crate::sign_document;
+    use signature::signer::{DIDSigner, Ed25519DidSigner};
+    use signature::suite::VerificationRelation;
+    use ssi::did::{DidDocument, VerificationRelationship};
+    use ssi::error::ErrorKind;
+    use ssi::test_utils::signer_document;
+
+    const DID: &str = "did:knox:z6MkfFmsob7fC3MmqU1JVfdBnMbnAw7xm1mrEtPvAoojLcRh";
+
+    fn create_did_doc(public_key_multibase: &str) -> DidDocument {
+        return DidDocument::deserialize(serde_json::json!({
+            "id": DID,
+            "verificationMethod": [{
+                "id": format!("{DID}#key-1"),
+                "type": "Ed25519VerificationKey2020",
+                "controller": DID,
+                "publicKeyMultibase": public_key_multibase
+            }],
+            "capabilityInvocation": ["#key-1"]
+        }))
+        .unwrap();
+    }
+
+    #[rstest::rstest]
+    #[case::multicodec(DID, None)]
+    #[case::wrong_method(
+        "did:key:z6MkfFmsob7fC3MmqU1JVfdBnMbnAw7xm1mrEtPvAoojLcRh",
+        Some(ErrorKind::InvalidDid)
+    )]
+    #[case::not_base58btc("did:knox:12345", Some(ErrorKind::InvalidDid))]
+    #[case::not_a_key("did:knox:z12345", Some(ErrorKind::InvalidDid))]
+    #[case::x25519_key(
+        "did:knox:z6LSbysY2xFMRpGMhb7tFTLMpeuPRaqaWM1yECx2AtzE3KCc",
+        Some(ErrorKind::InvalidDid)
+    )]
+    fn test_decode_did(#[case] did: &str, #[case] expect_error_kind: Option<ErrorKind>) {
+        let res = decode_did(did);
+        match expect_error_kind {
+            Some(kind) => assert_eq!(res.unwrap_err().kind, kind),
+            None => assert_eq!(res.unwrap().len(), 32),
+        }
+    }
+
+    #[test]
+    fn test_create_did() {
+        // the identifier of a signer's verification method is the raw encoding of its key
+        let did = signature::signer::Ed25519DidSigner::new()
+            .get_verification_method(signature::suite::VerificationRelation::CapabilityInvocation)
+            .split('#')
+            .next()
+            .unwrap()
+            .to_string();
+        assert_eq!(create_did(&decode_did(&did).unwrap()), did);
+    }
+
+    #[rstest::rstest]
+    #[case::matching_key(
+        create_did_doc("z6MkfFmsob7fC3MmqU1JVfdBnMbnAw7xm1mrEtPvAoojLcRh"),
+        None
+    )]
+    #[case::substituted_key(
+        create_did_doc("z6MkiTBz1ymuepAQ4HEHYSF1H8quG5GLVVQR3djdX3mDooWp"),
+        Some(ErrorKind::InvalidData)
+    )]
+    #[case::other_id(
+        { let mut doc = create_did_doc("z6MkfFmsob7fC3MmqU1JVfdBnMbnAw7xm1mrEtPvAoojLcRh"); doc.id = "did:knox:z6MkiTBz1ymuepAQ4HEHYSF1H8quG5GLVVQR3djdX3mDooWp".to_string(); doc },
+        Some(ErrorKind::InvalidData)
+    )]
+    fn test_verify_document(
+        #[case] document: DidDocument,
+        #[case] expect_error_kind: Option<ErrorKind>,
+    ) {
+        let res = verify_document(DID, &document);
+        assert_eq!(res.err().map(|e| e.kind), expect_error_kind);
+    }
+
+    /// Document of `signer`'s identifier that also lists the key of `other` as a `capabilityInvocation` method.
+    fn shared_document(signer: &Ed25519DidSigner, other: &Ed25519DidSigner) -> DidDocument {
+        let mut doc = signer_document(signer, &[VerificationRelation::CapabilityInvocation]);
+        let mut vm = signer_document(other, &[]).verification_method.remove(0);
+        vm.controller = doc.id.clone();
+        doc.capability_invocation
+            .push(VerificationRelationship::Reference(vm.id.clone()));
+        doc.verification_method.push(vm);
+        return doc;
+    }
+
+    #[rstest::rstest]
+    #[case::first_version(false, false, false, None)]
+    #[case::first_version_other_key(false, true, true, Some(ErrorKind::Unauthorized))]
+    #[case::later_version(true, false, false, None)]
+    #[case::later_version_other_key(true, true, true, Some(ErrorKind::Unauthorized))]
+    fn test_verify_version(
+        #[case] has_previous: bool,
+        #[case] shared: bool,
+        #[case] signed_by_other: bool,
+        #[case] expect_error_kind: Option<ErrorKind>,
+    ) {
+        let signer = Ed25519DidSigner::new();
+        let other = Ed25519DidSigner::new();
+        let previous = signer_document(&signer, &[VerificationRelation::CapabilityInvocation]);
+        let document = match shared {
+            true => shared_document(&signer, &other),
+            false => previous.clone(),
+        };
+        let signed = match signed_by_other {
+            true => sign_document(&document, &other).unwrap(),
+            false => sign_document(&document, &signer).unwrap(),
+        };
+
+        let res = verify_version(&document.id, signed, has_previous.then_some(&previous));
+        match expect_error_kind {
+            Some(kind) => assert_eq!(res.unwrap_err().kind, kind),
+            None => assert_eq!(res.unwrap(), document),
+        }
+    }
+}
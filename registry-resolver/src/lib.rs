@@ -1,71 +1,340 @@
+// Every registry RPC fails with `tonic::Status`, which is larger than clippy's limit for error variants.
+#![allow(clippy::result_large_err)]
+mod authorization;
+mod balancer;
+mod config;
+pub mod knox;
 mod registry_client;
+#[cfg(feature = "server")]
+pub mod server;
 const DID_METHOD: &'static str = "knox";
+pub use authorization::{
+    sign_deactivation, sign_document, verify_deactivation, verify_signed_document,
+};
+use balancer::BalancedClient;
+pub use config::{LoadBalancing, RegistryResolverConfig, RetryPolicy, TlsConfig};
 use registry_client::GrpcClient;
+use signature::signer::DIDSigner;
+use signature::suite::Ed25519Signature;
+use ssi::resolver::{DocumentMetadata, ResolutionOptions, ResolutionResult};
 
+/// Resolver for `did:knox` identifiers kept in the Knox registry.
+/// Documents created or updated through a resolver with a signer carry a `capabilityInvocation` proof of control.
+/// `did:knox` identifiers encode a public key, documents returned by the registry must contain it and, unless unsigned
+/// documents are allowed, the proof that authorized their version, see `knox::verify_version`.
 pub struct RegistryResolver {
     client: Box<dyn registry_client::RegistryClient + Send + Sync>,
+    signer: Option<Box<dyn DIDSigner<Ed25519Signature> + Send + Sync>>,
+    require_proof: bool,
 }
 
 impl RegistryResolver {
-    pub async fn new(url: impl Into<String>) -> Self {
-        let client = GrpcClient::new(url.into()).await;
-        return Self {
-            client: Box::new(client),
-        };
+    /// Connect to the registry at `url` with the default `RegistryResolverConfig`.
+    pub async fn new(url: impl Into<String>) -> Result<Self, ssi::error::ResolverError> {
+        return Self::from_config(RegistryResolverConfig::new(url)).await;
+    }
+
+    /// Connect to the registry, an invalid configuration fails with `InvalidData` and an unreachable registry with `NetworkFailure`.
+    /// Lazy connections only fail once a request is made. With replicas, creating the resolver only fails
+    /// when none of them can be reached.
+    pub async fn from_config(
+        config: RegistryResolverConfig,
+    ) -> Result<Self, ssi::error::ResolverError> {
+        let client: Box<dyn registry_client::RegistryClient + Send + Sync> =
+            match config.replicas.is_empty() {
+                true => Box::new(GrpcClient::connect(&config).await?),
+                false => Box::new(BalancedClient::connect(&config).await?),
+            };
+        return Ok(Self {
+            client,
+            signer: None,
+            require_proof: true,
+        });
+    }
+
+    /// Sign created and updated documents with `signer`, whose verification method must be a `capabilityInvocation`
+    /// method of the document being created, or of the registered document being updated.
+    pub fn with_signer(
+        mut self,
+        signer: impl DIDSigner<Ed25519Signature> + Send + Sync + 'static,
+    ) -> Self {
+        self.signer = Some(Box::new(signer));
+        return self;
+    }
+
+    /// Accept documents read from the registry without a proof, as written by resolvers without a signer to a registry
+    /// that allows unsigned writes. Documents that do carry a proof are still verified.
+    pub fn allow_unsigned(mut self) -> Self {
+        self.require_proof = false;
+        return self;
     }
 
     const fn get_method_helper() -> &'static str {
         return DID_METHOD;
     }
+
+    /// Validate the document of `did` and convert it to the `Struct` sent to the registry, signed if the resolver has a signer.
+    fn document_to_struct(
+        &self,
+        did: &str,
+        document: ssi::did::DidDocument,
+    ) -> Result<pbjson_types::Struct, ssi::error::ResolverError> {
+        knox::verify_document(did, &document)?;
+        document.validate().map_err(|e| {
+            ssi::error::ResolverError::new(e.to_string(), ssi::error::ErrorKind::InvalidData)
+        })?;
+
+        let document = match &self.signer {
+            Some(signer) => sign_document(&document, signer.as_ref())?,
+            None => document.serialize(),
+        };
+        return serde_json::from_value(document).map_err(|e| {
+            ssi::error::ResolverError::new(e.to_string(), ssi::error::ErrorKind::InvalidData)
+        });
+    }
+
+    /// Read the document from the registry, a requested version is passed as the `versionId` or `versionTime`
+    /// parameter of the DID URL sent to the registry.
+    async fn fetch(
+        &self,
+        did: &str,
+        options: &ResolutionOptions,
+    ) -> Result<(Option<serde_json::Value>, DocumentMetadata), ssi::error::ResolverError> {
+        knox::decode_did(did)?;
+        let res = self
+            .client
+            .read(versioned_did(did, options))
+            .await
+            .map_err(status_to_error)?;
+        let res = res.into_inner();
+
+        let metadata = match res.metadata {
+            Some(metadata) => serde_json::to_value(metadata)
+                .and_then(serde_json::from_value)
+                .map_err(|e| {
+                    ssi::error::ResolverError::new(
+                        e.to_string(),
+                        ssi::error::ErrorKind::InvalidData,
+                    )
+                })?,
+            None => DocumentMetadata::default(),
+        };
+
+        let document = res
+            .document
+            .map(serde_json::to_value)
+            .transpose()
+            .map_err(|e| {
+                ssi::error::ResolverError::new(e.to_string(), ssi::error::ErrorKind::InvalidData)
+            })?;
+
+        return Ok((document, metadata));
+    }
+
+    /// Check a document read from the registry and return it without its proof. The proof of each version is checked
+    /// against the version before it, which has been checked in turn, so every version up to the `versionId` in
+    /// `metadata` is read from the registry and checked in order, starting from the first version, which must be signed
+    /// with the key of the identifier, see `knox::verify_version`. Without a `versionId` the document is taken to be the
+    /// first version.
+    async fn verify(
+        &self,
+        did: &str,
+        document: serde_json::Value,
+        metadata: &DocumentMetadata,
+    ) -> Result<ssi::did::DidDocument, ssi::error::ResolverError> {
+        if document.get("proof").is_none() && !self.require_proof {
+            return self.verify_version(did, document, None);
+        }
+
+        let version = metadata
+            .version_id
+            .as_ref()
+            .and_then(|version_id| version_id.parse::<usize>().ok())
+            .unwrap_or(1);
+        let mut previous = None;
+        for version_id in 1..version {
+            let options = ResolutionOptions {
+                version_id: Some(version_id.to_string()),
+                ..Default::default()
+            };
+            let earlier = self.fetch(did, &options).await?.0.ok_or_else(|| {
+                ssi::error::ResolverError::new(
+                    format!("version {version_id} of {did} is missing"),
+                    ssi::error::ErrorKind::InvalidData,
+                )
+            })?;
+            previous = Some(self.verify_version(did, earlier, previous.as_ref())?);
+        }
+        return self.verify_version(did, document, previous.as_ref());
+    }
+
+    /// Check a version of the document of `did` against the `previous` version, accepting a version without a proof
+    /// when unsigned documents are allowed.
+    fn verify_version(
+        &self,
+        did: &str,
+        document: serde_json::Value,
+        previous: Option<&ssi::did::DidDocument>,
+    ) -> Result<ssi::did::DidDocument, ssi::error::ResolverError> {
+        if document.get("proof").is_none() && !self.require_proof {
+            let document = to_document(document)?;
+            knox::verify_document(did, &document)?;
+            return Ok(document);
+        }
+        return knox::verify_version(did, document, previous);
+    }
 }
+
+fn to_document(
+    document: serde_json::Value,
+) -> Result<ssi::did::DidDocument, ssi::error::ResolverError> {
+    return ssi::did::DidDocument::deserialize(document).map_err(|e| {
+        ssi::error::ResolverError::new(e.to_string(), ssi::error::ErrorKind::InvalidData)
+    });
+}
+
+/// Map a gRPC status of the registry to the `ResolverError` of every registry operation.
+fn status_to_error(status: tonic::Status) -> ssi::error::ResolverError {
+    let kind = match status.code() {
+        tonic::Code::NotFound => ssi::error::ErrorKind::DocumentNotFound,
+        tonic::Code::InvalidArgument | tonic::Code::AlreadyExists => {
+            ssi::error::ErrorKind::InvalidData
+        }
+        tonic::Code::FailedPrecondition => ssi::error::ErrorKind::Deactivated,
+        tonic::Code::Unimplemented => ssi::error::ErrorKind::OperationNotSupported,
+        tonic::Code::PermissionDenied | tonic::Code::Unauthenticated => {
+            ssi::error::ErrorKind::Unauthorized
+        }
+        _ => ssi::error::ErrorKind::NetworkFailure,
+    };
+    return ssi::error::ResolverError::new(status.message(), kind);
+}
+
+fn versioned_did(did: &str, options: &ResolutionOptions) -> String {
+    let parameters: Vec<String> = [
+        ("versionId", &options.version_id),
+        ("versionTime", &options.version_time),
+    ]
+    .iter()
+    .filter_map(|(name, value)| {
+        value
+            .as_ref()
+            .map(|value| format!("{name}={}", value.replace('+', "%2B")))
+    })
+    .collect();
+
+    return match parameters.is_empty() {
+        true => did.to_string(),
+        false => format!("{did}?{}", parameters.join("&")),
+    };
+}
+
 #[async_trait::async_trait]
 impl ssi::DIDResolver for RegistryResolver {
-    fn get_method() -> &'static str {
+    fn get_method(&self) -> &'static str {
         return Self::get_method_helper();
     }
 
     async fn create(
-        self,
-        did: String,
-        document: serde_json::Value,
+        &self,
+        did: &str,
+        document: ssi::did::DidDocument,
     ) -> Result<(), ssi::error::ResolverError> {
-        let document: pbjson_types::Struct = serde_json::from_value(document).map_err(|e| {
-            ssi::error::ResolverError::new(e.to_string(), ssi::error::ErrorKind::InvalidData)
-        })?;
+        let document = self.document_to_struct(did, document)?;
+        self.client
+            .create(did.to_string(), Some(document))
+            .await
+            .map_err(status_to_error)?;
 
-        self.client.create(did, Some(document)).await.map_err(|e| {
-            ssi::error::ResolverError::new(e.to_string(), ssi::error::ErrorKind::NetworkFailure)
-        })?;
+        Ok(())
+    }
+
+    async fn update(
+        &self,
+        did: &str,
+        document: ssi::did::DidDocument,
+    ) -> Result<(), ssi::error::ResolverError> {
+        let document = self.document_to_struct(did, document)?;
+        self.client
+            .update(did.to_string(), Some(document))
+            .await
+            .map_err(status_to_error)?;
 
         Ok(())
     }
 
-    async fn read(self, did: String) -> Result<serde_json::Value, ssi::error::ResolverError> {
-        let res = self.client.read(did).await.map_err(|e| {
-            ssi::error::ResolverError::new(e.to_string(), ssi::error::ErrorKind::NetworkFailure)
-        })?;
+    /// With a signer, the request carries its proof of control of the deactivation of the latest registered version,
+    /// see `sign_deactivation`.
+    async fn deactivate(&self, did: &str) -> Result<(), ssi::error::ResolverError> {
+        let proof = match &self.signer {
+            Some(signer) => {
+                let (_, metadata) = self.fetch(did, &ResolutionOptions::default()).await?;
+                let version_id = metadata.version_id.ok_or_else(|| {
+                    ssi::error::ResolverError::new(
+                        format!("the registry did not report the version of {did}"),
+                        ssi::error::ErrorKind::InvalidData,
+                    )
+                })?;
+                let proof = sign_deactivation(did, &version_id, signer.as_ref())?;
+                Some(serde_json::from_value(proof).map_err(|e| {
+                    ssi::error::ResolverError::new(
+                        e.to_string(),
+                        ssi::error::ErrorKind::InvalidData,
+                    )
+                })?)
+            }
+            None => None,
+        };
+        self.client
+            .deactivate(did.to_string(), proof)
+            .await
+            .map_err(status_to_error)?;
 
-        let document = res.into_inner().document;
+        Ok(())
+    }
 
-        match document {
-            Some(document) => Ok(serde_json::to_value(document).map_err(|e| {
-                ssi::error::ResolverError::new(e.to_string(), ssi::error::ErrorKind::InvalidData)
-            })?),
-            None => Err(ssi::error::ResolverError::new(
+    /// The document metadata reported by the registry is returned as `didDocumentMetadata`.
+    /// A deactivated DID without a document resolves to the `deactivated` error,
+    /// a document without the key of the identifier to the `invalidDidDocument` error
+    /// and a document without a valid proof of its version to the `unauthorized` error.
+    async fn resolve(&self, did: &str, options: &ResolutionOptions) -> ResolutionResult {
+        let (document, metadata) = match self.fetch(did, options).await {
+            Ok(res) => res,
+            Err(e) => return ResolutionResult::from_error(e),
+        };
+
+        let res = match document {
+            Some(document) => match self.verify(did, document, &metadata).await {
+                Ok(document) => ResolutionResult::from_document(document, options),
+                Err(e) => ResolutionResult::from_error(e),
+            },
+            None if metadata.deactivated == Some(true) => {
+                ResolutionResult::from_error(ssi::error::ResolverError::new(
+                    "DID has been deactivated",
+                    ssi::error::ErrorKind::Deactivated,
+                ))
+            }
+            None => ResolutionResult::from_error(ssi::error::ResolverError::new(
                 "Document not found",
                 ssi::error::ErrorKind::DocumentNotFound,
             )),
-        }
+        };
+        return res.with_document_metadata(metadata);
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use ssi::resolver::{DocumentMetadata, ResolutionOptions};
     use ssi::DIDResolver;
 
     use crate::{
-        registry_client::{registry::CreateResponse, registry::ReadResponse, MockRegistryClient},
-        RegistryResolver,
+        registry_client::{
+            registry::CreateResponse, registry::DeactivateResponse, registry::ReadResponse,
+            registry::UpdateResponse, MockRegistryClient,
+        },
+        sign_document, verify_signed_document, RegistryResolver, RegistryResolverConfig,
+        RetryPolicy, TlsConfig,
     };
 
     macro_rules! aw {
@@ -74,8 +343,8 @@ mod tests {
         };
     }
 
-    fn create_did_doc(did: String) -> serde_json::Value {
-        return serde_json::json!({
+    fn create_did_doc(did: String) -> ssi::did::DidDocument {
+        let doc = serde_json::json!({
                 "@context":["https://www.w3.org/ns/did/v1","https://w3id.org/security/suites/ed25519-2020/v1"],
                 "id":did,
                 "authentication":[
@@ -92,10 +361,25 @@ mod tests {
                 }]
             }
         );
+        return ssi::did::DidDocument::deserialize(doc).unwrap();
     }
 
-    fn create_did_struct(doc: serde_json::Value) -> pbjson_types::Struct {
-        return serde_json::from_value(doc).unwrap();
+    fn create_invalid_did_doc(did: String) -> ssi::did::DidDocument {
+        let mut doc = create_did_doc(did);
+        doc.verification_method.push(ssi::did::VerificationMethod {
+            id: format!("{}#invalid", doc.id),
+            method_type: ssi::did::VerificationMethodType::Ed25519VerificationKey2020,
+            controller: doc.id.clone(),
+            public_key_multibase: None,
+            public_key_base58: None,
+            public_key_jwk: None,
+            property_set: std::collections::HashMap::new(),
+        });
+        return doc;
+    }
+
+    fn create_did_struct(doc: ssi::did::DidDocument) -> pbjson_types::Struct {
+        return serde_json::from_value(doc.serialize()).unwrap();
     }
 
     fn create_did() -> String {
@@ -106,7 +390,7 @@ mod tests {
     #[case::network_failure(
         create_did(),
         create_did_doc(create_did()),
-        Some(Err(tonic::Status::invalid_argument("message"))),
+        Some(Err(tonic::Status::unavailable("message"))),
         Some(ssi::error::ErrorKind::NetworkFailure),
         false
     )]
@@ -119,14 +403,14 @@ mod tests {
     )]
     #[case::parsing_failure(
         create_did(),
-        serde_json::json!("{}"),
+        create_invalid_did_doc(create_did()),
         None,
         Some(ssi::error::ErrorKind::InvalidData),
         false
     )]
     fn test_create(
         #[case] did: String,
-        #[case] doc: serde_json::Value,
+        #[case] doc: ssi::did::DidDocument,
         #[case] mock_create_response: Option<
             Result<tonic::Response<CreateResponse>, tonic::Status>,
         >,
@@ -146,9 +430,11 @@ mod tests {
 
         let resolver = RegistryResolver {
             client: Box::new(mock_client),
+            signer: None,
+            require_proof: false,
         };
 
-        let res = aw!(resolver.create(did, doc));
+        let res = aw!(resolver.create(&did, doc));
         assert_eq!(res.is_ok(), expect_ok);
         match res.err() {
             Some(e) => assert_eq!(e.kind, expect_error_kind.unwrap()),
@@ -159,10 +445,16 @@ mod tests {
     #[rstest::rstest]
     #[case::network_failure(
         create_did(),
-        Some(Err(tonic::Status::invalid_argument("message"))),
+        Some(Err(tonic::Status::unavailable("message"))),
         Some(ssi::error::ErrorKind::NetworkFailure),
         false
     )]
+    #[case::not_found_status(
+        create_did(),
+        Some(Err(tonic::Status::not_found("message"))),
+        Some(ssi::error::ErrorKind::DocumentNotFound),
+        false
+    )]
     #[case::success(
         create_did(),
         Some(Ok(tonic::Response::new(ReadResponse {
@@ -183,6 +475,16 @@ mod tests {
         Some(ssi::error::ErrorKind::DocumentNotFound),
         false
     )]
+    #[case::invalid_document(
+        create_did(),
+        Some(Ok(tonic::Response::new(ReadResponse {
+            did: create_did(),
+            document: Some(create_did_struct(create_invalid_did_doc(create_did()))),
+            metadata: None,
+         }))),
+        Some(ssi::error::ErrorKind::InvalidData),
+        false
+    )]
     fn test_read(
         #[case] did: String,
         #[case] mock_read_response: Option<Result<tonic::Response<ReadResponse>, tonic::Status>>,
@@ -199,9 +501,11 @@ mod tests {
 
         let resolver = RegistryResolver {
             client: Box::new(mock_client),
+            signer: None,
+            require_proof: false,
         };
 
-        let res = aw!(resolver.read(did));
+        let res = aw!(resolver.read(&did));
         assert_eq!(res.is_ok(), expect_ok);
         match res.err() {
             Some(e) => assert_eq!(e.kind, expect_error_kind.unwrap()),
@@ -209,9 +513,650 @@ mod tests {
         }
     }
 
+    #[rstest::rstest]
+    #[case::success(Ok(()), None)]
+    #[case::network_failure(
+        Err(tonic::Status::unavailable("message")),
+        Some(ssi::error::ErrorKind::NetworkFailure)
+    )]
+    #[case::not_found(
+        Err(tonic::Status::not_found("message")),
+        Some(ssi::error::ErrorKind::DocumentNotFound)
+    )]
+    #[case::rejected(
+        Err(tonic::Status::invalid_argument("message")),
+        Some(ssi::error::ErrorKind::InvalidData)
+    )]
+    #[case::deactivated(
+        Err(tonic::Status::failed_precondition("message")),
+        Some(ssi::error::ErrorKind::Deactivated)
+    )]
+    #[case::unimplemented(
+        Err(tonic::Status::unimplemented("message")),
+        Some(ssi::error::ErrorKind::OperationNotSupported)
+    )]
+    fn test_update_deactivate(
+        #[case] mock_response: Result<(), tonic::Status>,
+        #[case] expect_error_kind: Option<ssi::error::ErrorKind>,
+    ) {
+        let mut doc = create_did_doc(create_did());
+        doc.also_known_as = vec!["https://example.com/issuer".to_string()];
+
+        let mut mock_client = MockRegistryClient::default();
+        let update_response = mock_response
+            .as_ref()
+            .map(|_| ())
+            .map_err(|status| tonic::Status::new(status.code(), status.message()));
+        mock_client
+            .expect_update()
+            .with(
+                mockall::predicate::eq(create_did()),
+                mockall::predicate::eq(Some(create_did_struct(doc.clone()))),
+            )
+            .return_once(|_, _| update_response.map(|_| tonic::Response::new(UpdateResponse {})));
+        mock_client
+            .expect_deactivate()
+            .with(
+                mockall::predicate::eq(create_did()),
+                mockall::predicate::eq(None),
+            )
+            .return_once(|_, _| mock_response.map(|_| tonic::Response::new(DeactivateResponse {})));
+        let resolver = RegistryResolver {
+            client: Box::new(mock_client),
+            signer: None,
+            require_proof: false,
+        };
+
+        let res = aw!(resolver.update(&create_did(), doc));
+        assert_eq!(res.err().map(|e| e.kind), expect_error_kind);
+        let res = aw!(resolver.deactivate(&create_did()));
+        assert_eq!(res.err().map(|e| e.kind), expect_error_kind);
+    }
+
+    #[test]
+    fn test_update_invalid_document() {
+        let resolver = RegistryResolver {
+            client: Box::new(MockRegistryClient::default()),
+            signer: None,
+            require_proof: false,
+        };
+        let res = aw!(resolver.update(&create_did(), create_invalid_did_doc(create_did())));
+        assert_eq!(res.unwrap_err().kind, ssi::error::ErrorKind::InvalidData);
+    }
+
+    #[rstest::rstest]
+    #[case::create(true)]
+    #[case::update(false)]
+    fn test_signed_write(#[case] create: bool) {
+        let signer = signature::signer::Ed25519DidSigner::new();
+        let doc = ssi::test_utils::signer_document(
+            &signer,
+            &[signature::suite::VerificationRelation::CapabilityInvocation],
+        );
+        let did = doc.id.clone();
+
+        let registered = doc.clone();
+        let verify = move |did: &String, document: &Option<pbjson_types::Struct>| {
+            let signed = serde_json::to_value(document.as_ref().unwrap()).unwrap();
+            let registered = (!create).then_some(&registered);
+            verify_signed_document(did, signed, registered).is_ok()
+        };
+        let mut mock_client = MockRegistryClient::default();
+        match create {
+            true => {
+                mock_client
+                    .expect_create()
+                    .withf(verify)
+                    .return_once(|_, _| Ok(tonic::Response::new(CreateResponse {})));
+            }
+            false => {
+                mock_client
+                    .expect_update()
+                    .withf(verify)
+                    .return_once(|_, _| Ok(tonic::Response::new(UpdateResponse {})));
+            }
+        }
+        let resolver = RegistryResolver {
+            client: Box::new(mock_client),
+            signer: None,
+            require_proof: false,
+        }
+        .with_signer(signer);
+
+        let res = match create {
+            true => aw!(resolver.create(&did, doc)),
+            false => aw!(resolver.update(&did, doc)),
+        };
+        assert!(res.is_ok());
+    }
+
+    fn create_metadata(metadata: serde_json::Value) -> Option<pbjson_types::Struct> {
+        return Some(serde_json::from_value(metadata).unwrap());
+    }
+
+    #[rstest::rstest]
+    #[case::metadata(
+        Some(create_did_struct(create_did_doc(create_did()))),
+        create_metadata(serde_json::json!({
+            "created": "2022-06-01T00:00:00Z",
+            "updated": "2022-07-01T00:00:00Z",
+            "versionId": "2"
+        })),
+        None,
+        DocumentMetadata {
+            created: Some("2022-06-01T00:00:00Z".to_string()),
+            updated: Some("2022-07-01T00:00:00Z".to_string()),
+            version_id: Some("2".to_string()),
+            ..Default::default()
+        }
+    )]
+    #[case::no_metadata(
+        Some(create_did_struct(create_did_doc(create_did()))),
+        None,
+        None,
+        DocumentMetadata::default()
+    )]
+    #[case::deactivated_without_document(
+        None,
+        create_metadata(serde_json::json!({"deactivated": true})),
+        Some("deactivated"),
+        DocumentMetadata {
+            deactivated: Some(true),
+            ..Default::default()
+        }
+    )]
+    #[case::not_found(None, None, Some("notFound"), DocumentMetadata::default())]
+    fn test_resolve(
+        #[case] document: Option<pbjson_types::Struct>,
+        #[case] metadata: Option<pbjson_types::Struct>,
+        #[case] expect_error: Option<&str>,
+        #[case] expect_metadata: DocumentMetadata,
+    ) {
+        let mut mock_client = MockRegistryClient::default();
+        mock_client.expect_read().return_once(move |did| {
+            Ok(tonic::Response::new(ReadResponse {
+                did,
+                document,
+                metadata,
+            }))
+        });
+        let resolver = RegistryResolver {
+            client: Box::new(mock_client),
+            signer: None,
+            require_proof: false,
+        };
+
+        let res = aw!(resolver.resolve(&create_did(), &ResolutionOptions::default()));
+        assert_eq!(res.did_resolution_metadata.error.as_deref(), expect_error);
+        assert_eq!(res.did_document.is_some(), expect_error.is_none());
+        assert_eq!(res.did_document_metadata, expect_metadata);
+    }
+
+    #[rstest::rstest]
+    #[case::first_version(Some("1"), "signer", None)]
+    #[case::without_version(None, "signer", None)]
+    #[case::later_version(Some("2"), "signer", None)]
+    #[case::later_version_other_key(Some("2"), "other", Some("unauthorized"))]
+    #[case::unsigned(Some("2"), "none", Some("unauthorized"))]
+    fn test_resolve_signed(
+        #[case] version_id: Option<&str>,
+        #[case] signed_by: &str,
+        #[case] expect_error: Option<&str>,
+    ) {
+        let signer = signature::signer::Ed25519DidSigner::new();
+        let registered = ssi::test_utils::signer_document(
+            &signer,
+            &[signature::suite::VerificationRelation::CapabilityInvocation],
+        );
+        let mut doc = registered.clone();
+        doc.also_known_as
+            .push("https://example.com/knox".to_string());
+        let signed = match signed_by {
+            "signer" => sign_document(&doc, &signer).unwrap(),
+            "other" => sign_document(&doc, &signature::signer::Ed25519DidSigner::new()).unwrap(),
+            _ => doc.serialize(),
+        };
+        let signed_registered = sign_document(&registered, &signer).unwrap();
+
+        let version_id = version_id.map(String::from);
+        let mut mock_client = MockRegistryClient::default();
+        mock_client.expect_read().returning(move |did| {
+            // version 1 is read to check the proof of version 2
+            let (document, version_id) = match did.ends_with("?versionId=1") {
+                true => (signed_registered.clone(), Some("1".to_string())),
+                false => (signed.clone(), version_id.clone()),
+            };
+            Ok(tonic::Response::new(ReadResponse {
+                did,
+                document: Some(serde_json::from_value(document).unwrap()),
+                metadata: version_id.and_then(|version_id| {
+                    create_metadata(serde_json::json!({ "versionId": version_id }))
+                }),
+            }))
+        });
+        let resolver = RegistryResolver {
+            client: Box::new(mock_client),
+            signer: None,
+            require_proof: true,
+        };
+
+        let res = aw!(resolver.resolve(&doc.id, &ResolutionOptions::default()));
+        assert_eq!(res.did_resolution_metadata.error.as_deref(), expect_error);
+        if expect_error.is_none() {
+            assert_eq!(res.did_document, Some(doc));
+        }
+    }
+
+    #[rstest::rstest]
+    #[case::authorized_key(true, None)]
+    #[case::forged_key(false, Some("unauthorized"))]
+    fn test_resolve_signed_history(#[case] authorized: bool, #[case] expect_error: Option<&str>) {
+        let signer = signature::signer::Ed25519DidSigner::new();
+        let other = signature::signer::Ed25519DidSigner::new();
+        let relations = [signature::suite::VerificationRelation::CapabilityInvocation];
+        let first = ssi::test_utils::signer_document(&signer, &relations);
+        // the second version adds the key of `other`, which signs the third version
+        let added = ssi::test_utils::signer_document(&other, &relations);
+        let mut second = first.clone();
+        second.verification_method.extend(added.verification_method);
+        second
+            .capability_invocation
+            .extend(added.capability_invocation);
+        let mut third = second.clone();
+        third
+            .also_known_as
+            .push("https://example.com/knox".to_string());
+        let versions = [
+            sign_document(&first, &signer).unwrap(),
+            match authorized {
+                true => sign_document(&second, &signer).unwrap(),
+                false => sign_document(&second, &other).unwrap(),
+            },
+            sign_document(&third, &other).unwrap(),
+        ];
+
+        let mut mock_client = MockRegistryClient::default();
+        mock_client.expect_read().returning(move |did| {
+            let version = match did.split_once("?versionId=") {
+                Some((_, version)) => version.parse().unwrap(),
+                None => versions.len(),
+            };
+            Ok(tonic::Response::new(ReadResponse {
+                did,
+                document: Some(serde_json::from_value(versions[version - 1].clone()).unwrap()),
+                metadata: create_metadata(serde_json::json!({ "versionId": version.to_string() })),
+            }))
+        });
+        let resolver = RegistryResolver {
+            client: Box::new(mock_client),
+            signer: None,
+            require_proof: true,
+        };
+
+        let res = aw!(resolver.resolve(&first.id, &ResolutionOptions::default()));
+        assert_eq!(res.did_resolution_metadata.error.as_deref(), expect_error);
+        if expect_error.is_none() {
+            assert_eq!(res.did_document, Some(third));
+        }
+    }
+
+    #[rstest::rstest]
+    #[case::latest(None, None, create_did())]
+    #[case::version_id(Some("2"), None, format!("{}?versionId=2", create_did()))]
+    #[case::version_time(
+        None,
+        Some("2022-06-01T00:00:00+02:00"),
+        format!("{}?versionTime=2022-06-01T00:00:00%2B02:00", create_did())
+    )]
+    fn test_resolve_version(
+        #[case] version_id: Option<&str>,
+        #[case] version_time: Option<&str>,
+        #[case] expect_did: String,
+    ) {
+        let mut mock_client = MockRegistryClient::default();
+        mock_client
+            .expect_read()
+            .with(mockall::predicate::eq(expect_did))
+            .return_once(|_| {
+                Ok(tonic::Response::new(ReadResponse {
+                    did: create_did(),
+                    document: Some(create_did_struct(create_did_doc(create_did()))),
+                    metadata: create_metadata(serde_json::json!({"versionId": "2"})),
+                }))
+            });
+        let resolver = RegistryResolver {
+            client: Box::new(mock_client),
+            signer: None,
+            require_proof: false,
+        };
+
+        let options = ResolutionOptions {
+            version_id: version_id.map(String::from),
+            version_time: version_time.map(String::from),
+            ..Default::default()
+        };
+        let res = aw!(resolver.resolve(&create_did(), &options));
+        assert!(res.did_document.is_some());
+        assert_eq!(res.did_document_metadata.version_id.as_deref(), Some("2"));
+    }
+
+    #[rstest::rstest]
+    #[case::substituted_key(
+        create_did(),
+        Some({
+            let mut doc = create_did_doc(create_did());
+            doc.capability_invocation = vec![];
+            doc.authentication = vec![];
+            doc.capability_delegation = vec![];
+            doc.assertion_method = vec![ssi::did::VerificationRelationship::Embedded(Box::new(ssi::did::VerificationMethod {
+                id: format!("{}#key-2", create_did()),
+                method_type: ssi::did::VerificationMethodType::Ed25519VerificationKey2020,
+                controller: create_did(),
+                public_key_multibase: Some("z6MkiTBz1ymuepAQ4HEHYSF1H8quG5GLVVQR3djdX3mDooWp".to_string()),
+                public_key_base58: None,
+                public_key_jwk: None,
+                property_set: std::collections::HashMap::new(),
+            }))];
+            doc
+        }),
+        ssi::error::ErrorKind::InvalidData
+    )]
+    #[case::invalid_did("did:knox:12345".to_string(), None, ssi::error::ErrorKind::InvalidDid)]
+    fn test_read_not_self_certifying(
+        #[case] did: String,
+        #[case] document: Option<ssi::did::DidDocument>,
+        #[case] expect_error_kind: ssi::error::ErrorKind,
+    ) {
+        let submitted = document
+            .clone()
+            .unwrap_or_else(|| create_did_doc(did.clone()));
+        let mut mock_client = MockRegistryClient::default();
+        if let Some(document) = document {
+            mock_client.expect_read().return_once(move |did| {
+                Ok(tonic::Response::new(ReadResponse {
+                    did,
+                    document: Some(create_did_struct(document)),
+                    metadata: None,
+                }))
+            });
+        }
+        let resolver = RegistryResolver {
+            client: Box::new(mock_client),
+            signer: None,
+            require_proof: false,
+        };
+
+        let res = aw!(resolver.read(&did));
+        assert_eq!(res.unwrap_err().kind, expect_error_kind);
+        let res = aw!(resolver.create(&did, submitted));
+        assert_eq!(res.unwrap_err().kind, expect_error_kind);
+    }
+
+    #[test]
+    fn test_read_deactivated() {
+        let mut mock_client = MockRegistryClient::default();
+        mock_client.expect_read().return_once(|did| {
+            Ok(tonic::Response::new(ReadResponse {
+                did: did.clone(),
+                document: Some(create_did_struct(create_did_doc(did))),
+                metadata: create_metadata(serde_json::json!({"deactivated": true})),
+            }))
+        });
+        let resolver = RegistryResolver {
+            client: Box::new(mock_client),
+            signer: None,
+            require_proof: false,
+        };
+
+        let res = aw!(resolver.read(&create_did()));
+        assert_eq!(res.unwrap_err().kind, ssi::error::ErrorKind::Deactivated);
+    }
+
+    #[rstest::rstest]
+    #[case::invalid_url("not a url", false, Some(ssi::error::ErrorKind::InvalidData))]
+    #[case::lazy("http://127.0.0.1:1", true, None)]
+    #[case::unreachable(
+        "http://127.0.0.1:1",
+        false,
+        Some(ssi::error::ErrorKind::NetworkFailure)
+    )]
+    fn test_from_config(
+        #[case] url: &str,
+        #[case] lazy: bool,
+        #[case] expect_error_kind: Option<ssi::error::ErrorKind>,
+    ) {
+        let config = RegistryResolverConfig {
+            connect_timeout: Some(std::time::Duration::from_millis(500)),
+            lazy,
+            retry: RetryPolicy::none(),
+            gzip: true,
+            ..RegistryResolverConfig::new(url)
+        };
+
+        aw!(async {
+            let res = RegistryResolver::from_config(config).await;
+            match expect_error_kind {
+                Some(kind) => assert_eq!(res.err().unwrap().kind, kind),
+                None => {
+                    // a lazy connection only fails once the registry is used
+                    let res = res.unwrap().read(&create_did()).await;
+                    assert_eq!(res.unwrap_err().kind, ssi::error::ErrorKind::NetworkFailure);
+                }
+            }
+        });
+    }
+
+    #[test]
+    fn test_read_many() {
+        let unavailable = "did:knox:z6MkiTBz1ymuepAQ4HEHYSF1H8quG5GLVVQR3djdX3mDooWp";
+        let mut mock_client = MockRegistryClient::default();
+        mock_client
+            .expect_read()
+            .times(2)
+            .returning(move |did| match did == unavailable {
+                true => Err(tonic::Status::unavailable("message")),
+                false => Ok(tonic::Response::new(ReadResponse {
+                    did: did.clone(),
+                    document: Some(create_did_struct(create_did_doc(did))),
+                    metadata: None,
+                })),
+            });
+        let resolver = RegistryResolver {
+            client: Box::new(mock_client),
+            signer: None,
+            require_proof: false,
+        };
+
+        let dids = [
+            create_did(),
+            unavailable.to_string(),
+            "did:knox:123".to_string(),
+        ];
+        let res = aw!(resolver.read_many(&dids, 4));
+        assert_eq!(res[&create_did()].as_ref().unwrap().id, create_did());
+        assert_eq!(
+            res[unavailable].as_ref().unwrap_err().kind,
+            ssi::error::ErrorKind::NetworkFailure
+        );
+        assert_eq!(
+            res["did:knox:123"].as_ref().unwrap_err().kind,
+            ssi::error::ErrorKind::InvalidDid
+        );
+    }
+
+    /// gRPC service answering every call with a `NotFound` status carrying the `authorization` metadata of the call.
+    #[derive(Clone)]
+    struct EchoAuthorization;
+
+    impl tonic::transport::NamedService for EchoAuthorization {
+        const NAME: &'static str = "registry_api.v1.RegistryService";
+    }
+
+    impl tonic::codegen::Service<tonic::codegen::http::Request<tonic::transport::Body>>
+        for EchoAuthorization
+    {
+        type Response = tonic::codegen::http::Response<tonic::body::BoxBody>;
+        type Error = std::convert::Infallible;
+        type Future = std::future::Ready<Result<Self::Response, Self::Error>>;
+
+        fn poll_ready(
+            &mut self,
+            _cx: &mut std::task::Context<'_>,
+        ) -> std::task::Poll<Result<(), Self::Error>> {
+            std::task::Poll::Ready(Ok(()))
+        }
+
+        fn call(
+            &mut self,
+            request: tonic::codegen::http::Request<tonic::transport::Body>,
+        ) -> Self::Future {
+            let authorization = request
+                .headers()
+                .get("authorization")
+                .and_then(|value| value.to_str().ok())
+                .unwrap_or("none");
+            std::future::ready(Ok(tonic::Status::not_found(authorization).to_http()))
+        }
+    }
+
+    fn create_ca() -> rcgen::Certificate {
+        let mut params = rcgen::CertificateParams::new(vec![]);
+        params.is_ca = rcgen::IsCa::Ca(rcgen::BasicConstraints::Unconstrained);
+        params
+            .distinguished_name
+            .push(rcgen::DnType::CommonName, "registry test CA");
+        return rcgen::Certificate::from_params(params).unwrap();
+    }
+
+    /// PEM encoded certificate for `names` issued by `ca`, and its private key.
+    fn create_certificate(ca: &rcgen::Certificate, names: Vec<String>) -> (String, String) {
+        let mut params = rcgen::CertificateParams::new(names);
+        params
+            .distinguished_name
+            .push(rcgen::DnType::CommonName, "registry test");
+        let certificate = rcgen::Certificate::from_params(params).unwrap();
+        return (
+            certificate.serialize_pem_with_signer(ca).unwrap(),
+            certificate.serialize_private_key_pem(),
+        );
+    }
+
+    /// Serve `EchoAuthorization` over TLS for `registry.test`, requiring client certificates issued by `ca`.
+    async fn start_tls_server(ca: &rcgen::Certificate) -> std::net::SocketAddr {
+        let (certificate, key) = create_certificate(ca, vec!["registry.test".to_string()]);
+        let tls = tonic::transport::ServerTlsConfig::new()
+            .identity(tonic::transport::Identity::from_pem(certificate, key))
+            .client_ca_root(tonic::transport::Certificate::from_pem(
+                ca.serialize_pem().unwrap(),
+            ));
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = tonic::transport::Server::builder()
+            .tls_config(tls)
+            .unwrap()
+            .add_service(EchoAuthorization)
+            .serve_with_incoming(tokio_stream::wrappers::TcpListenerStream::new(listener));
+        tokio::spawn(server);
+        return addr;
+    }
+
+    #[rstest::rstest]
+    #[case::mutual_tls(true, "certificate", "registry.test", Ok("Bearer secret"))]
+    #[case::no_client_certificate(
+        true,
+        "none",
+        "registry.test",
+        Err(ssi::error::ErrorKind::NetworkFailure)
+    )]
+    #[case::untrusted_server(
+        false,
+        "certificate",
+        "registry.test",
+        Err(ssi::error::ErrorKind::NetworkFailure)
+    )]
+    #[case::wrong_domain(
+        true,
+        "certificate",
+        "other.test",
+        Err(ssi::error::ErrorKind::NetworkFailure)
+    )]
+    #[case::client_certificate_without_key(
+        true,
+        "certificate_only",
+        "registry.test",
+        Err(ssi::error::ErrorKind::InvalidData)
+    )]
+    fn test_tls(
+        #[case] trust_server: bool,
+        #[case] client_identity: &str,
+        #[case] domain_name: &str,
+        #[case] expect: Result<&str, ssi::error::ErrorKind>,
+    ) {
+        let ca = create_ca();
+        let (client_certificate, client_key) =
+            create_certificate(&ca, vec!["client.registry.test".to_string()]);
+        let trusted_ca = match trust_server {
+            true => ca.serialize_pem().unwrap(),
+            false => create_ca().serialize_pem().unwrap(),
+        };
+        let tls = TlsConfig {
+            ca_certificate: Some(trusted_ca),
+            client_certificate: (client_identity != "none").then_some(client_certificate),
+            client_key: (client_identity == "certificate").then_some(client_key),
+            domain_name: Some(domain_name.to_string()),
+        };
+
+        aw!(async {
+            let addr = start_tls_server(&ca).await;
+            let config = RegistryResolverConfig {
+                retry: RetryPolicy::none(),
+                tls: Some(tls),
+                bearer_token: Some("secret".to_string()),
+                ..RegistryResolverConfig::new(format!("https://{addr}"))
+            };
+
+            let res = match RegistryResolver::from_config(config).await {
+                Ok(resolver) => resolver.read(&create_did()).await,
+                Err(e) => Err(e),
+            };
+            let e = res.unwrap_err();
+            match expect {
+                Ok(authorization) => {
+                    assert_eq!(e.kind, ssi::error::ErrorKind::DocumentNotFound, "{}", e);
+                    assert_eq!(e.message, authorization);
+                }
+                Err(kind) => assert_eq!(e.kind, kind, "{}", e),
+            }
+        });
+    }
+
+    #[test]
+    fn test_config_debug_redacts_secrets() {
+        let config = RegistryResolverConfig {
+            tls: Some(TlsConfig {
+                client_key: Some("private key".to_string()),
+                ..Default::default()
+            }),
+            bearer_token: Some("secret".to_string()),
+            ..RegistryResolverConfig::new("https://registry.test")
+        };
+        let debug = format!("{config:?}");
+        assert!(!debug.contains("secret"));
+        assert!(!debug.contains("private key"));
+    }
+
     #[test]
     fn test_get_method() -> Result<(), String> {
-        assert_eq!(RegistryResolver::get_method(), "knox");
+        assert_eq!(
+            RegistryResolver {
+                client: Box::new(MockRegistryClient::default()),
+                signer: None,
+                require_proof: false,
+            }
+            .get_method(),
+            "knox"
+        );
         Ok(())
     }
 
@@ -219,8 +1164,13 @@ mod tests {
     fn test_create_verification_method() -> Result<(), String> {
         let did = String::from("12345");
         let key_id = String::from("123456");
+        let resolver = RegistryResolver {
+            client: Box::new(MockRegistryClient::default()),
+            signer: None,
+            require_proof: false,
+        };
         assert_eq!(
-            RegistryResolver::create_verification_method(did, key_id),
+            resolver.create_verification_method(did, key_id),
             "did:knox:12345#123456"
         );
         Ok(())
@@ -1,18 +1,61 @@
+use crate::config::{BearerToken, RegistryResolverConfig, RetryPolicy};
 use mockall::*;
 use registry::registry_service_client::RegistryServiceClient;
+use tonic::codegen::InterceptedService;
 
 #[rustfmt::skip]
 #[path = "gen/registry_api.v1.rs"]
 pub mod registry;
 
 pub struct GrpcClient {
-    inner: RegistryServiceClient<tonic::transport::Channel>,
+    inner: RegistryServiceClient<InterceptedService<tonic::transport::Channel, BearerToken>>,
+    retry: RetryPolicy,
 }
 
 impl GrpcClient {
-    pub async fn new(url: String) -> impl RegistryClient + Send + Sync {
-        let inner = RegistryServiceClient::connect(url.clone()).await.unwrap();
-        return Self { inner };
+    /// Connect to the registry, an invalid configuration fails with `InvalidData` and an unreachable registry with `NetworkFailure`.
+    pub async fn connect(
+        config: &RegistryResolverConfig,
+    ) -> Result<impl RegistryClient + Send + Sync, ssi::error::ResolverError> {
+        let invalid = |e: tonic::transport::Error| {
+            ssi::error::ResolverError::new(
+                format!("{}: {e}", config.url),
+                ssi::error::ErrorKind::InvalidData,
+            )
+        };
+        let mut endpoint =
+            tonic::transport::Endpoint::from_shared(config.url.clone()).map_err(invalid)?;
+        if let Some(timeout) = config.connect_timeout {
+            endpoint = endpoint.connect_timeout(timeout);
+        }
+        if let Some(timeout) = config.request_timeout {
+            endpoint = endpoint.timeout(timeout);
+        }
+        if let Some(tls) = &config.tls {
+            endpoint = endpoint
+                .tls_config(tls.to_client_tls_config()?)
+                .map_err(invalid)?;
+        }
+        let bearer_token = BearerToken::new(&config.bearer_token)?;
+
+        let channel = match config.lazy {
+            true => endpoint.connect_lazy(),
+            false => endpoint.connect().await.map_err(|e| {
+                ssi::error::ResolverError::new(
+                    format!("{}: {e}", config.url),
+                    ssi::error::ErrorKind::NetworkFailure,
+                )
+            })?,
+        };
+        let mut inner = RegistryServiceClient::with_interceptor(channel, bearer_token);
+        if config.gzip {
+            inner = inner.send_gzip().accept_gzip();
+        }
+
+        return Ok(Self {
+            inner,
+            retry: config.retry.clone(),
+        });
     }
 }
 
@@ -34,8 +77,40 @@ impl RegistryClient for GrpcClient {
         &self,
         did: String,
     ) -> Result<tonic::Response<registry::ReadResponse>, tonic::Status> {
+        let request = registry::ReadRequest { did };
+
+        return self
+            .retry
+            .run(|| {
+                let mut client = self.inner.to_owned();
+                let request = request.clone();
+                async move { client.read(request).await }
+            })
+            .await;
+    }
+
+    async fn update(
+        &self,
+        did: String,
+        document: Option<pbjson_types::Struct>,
+    ) -> Result<tonic::Response<registry::UpdateResponse>, tonic::Status> {
         let mut client = self.inner.to_owned();
-        return client.read(registry::ReadRequest { did }).await;
+
+        return client
+            .update(registry::UpdateRequest { did, document })
+            .await;
+    }
+
+    async fn deactivate(
+        &self,
+        did: String,
+        proof: Option<pbjson_types::Struct>,
+    ) -> Result<tonic::Response<registry::DeactivateResponse>, tonic::Status> {
+        let mut client = self.inner.to_owned();
+
+        return client
+            .deactivate(registry::DeactivateRequest { did, proof })
+            .await;
     }
 }
 
@@ -52,4 +127,16 @@ pub trait RegistryClient {
         &self,
         did: String,
     ) -> Result<tonic::Response<registry::ReadResponse>, tonic::Status>;
+
+    async fn update(
+        &self,
+        did: String,
+        document: Option<pbjson_types::Struct>,
+    ) -> Result<tonic::Response<registry::UpdateResponse>, tonic::Status>;
+
+    async fn deactivate(
+        &self,
+        did: String,
+        proof: Option<pbjson_types::Struct>,
+    ) -> Result<tonic::Response<registry::DeactivateResponse>, tonic::Status>;
 }
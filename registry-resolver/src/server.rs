@@ -0,0 +1,632 @@
+mod storage;
+
+use crate::registry_client::registry;
+use crate::registry_client::registry::registry_service_server::{
+    RegistryService, RegistryServiceServer,
+};
+use crate::{knox, verify_deactivation};
+use ssi::did::{DidDocument, DidUrl};
+use ssi::error::{ErrorKind, ResolverError};
+use ssi::proof::DataIntegrityProof;
+use ssi::resolver::DocumentMetadata;
+pub use storage::{DidRecord, DocumentVersion, FileStorage, MemoryStorage, Storage};
+
+/// Map a `ResolverError` to the gRPC status the registry answers with, the reverse of the mapping of the resolver.
+fn error_to_status(e: ResolverError) -> tonic::Status {
+    return match e.kind {
+        ErrorKind::DocumentNotFound => tonic::Status::not_found(e.message),
+        ErrorKind::InvalidData | ErrorKind::InvalidDid => {
+            tonic::Status::invalid_argument(e.message)
+        }
+        ErrorKind::Deactivated => tonic::Status::failed_precondition(e.message),
+        ErrorKind::Unauthorized => tonic::Status::permission_denied(e.message),
+        ErrorKind::OperationNotSupported => tonic::Status::unimplemented(e.message),
+        _ => tonic::Status::internal(e.message),
+    };
+}
+
+fn now() -> String {
+    return chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
+}
+
+fn parse_time(time: &str) -> Result<chrono::DateTime<chrono::FixedOffset>, tonic::Status> {
+    return chrono::DateTime::parse_from_rfc3339(time)
+        .map_err(|e| tonic::Status::invalid_argument(format!("{time} is not a valid time: {e}")));
+}
+
+/// In-process implementation of the Knox registry `RegistryService`, keeping the documents of `did:knox`
+/// identifiers in a `Storage` with their version history.
+/// Created and updated documents are validated and must contain the key of their identifier. Unless unsigned writes
+/// are allowed, they must carry a `capabilityInvocation` proof, see `knox::verify_version`, created after the proof of
+/// the registered version so that earlier updates cannot be replayed. The proof is kept and returned with the version.
+/// Deactivation requests must likewise carry a proof, made for the registered version, see `verify_deactivation`.
+pub struct RegistryServer<S: Storage> {
+    storage: S,
+    require_proof: bool,
+    writes: tokio::sync::Mutex<()>,
+}
+
+impl<S: Storage + 'static> RegistryServer<S> {
+    pub fn new(storage: S) -> Self {
+        return Self {
+            storage,
+            require_proof: true,
+            writes: tokio::sync::Mutex::new(()),
+        };
+    }
+
+    /// Accept documents without a proof, as sent by a `RegistryResolver` without a signer.
+    /// Documents that do carry a proof are still verified.
+    pub fn allow_unsigned(mut self) -> Self {
+        self.require_proof = false;
+        return self;
+    }
+
+    pub fn into_service(self) -> RegistryServiceServer<Self> {
+        return RegistryServiceServer::new(self);
+    }
+
+    /// Serve the registry on `addr` until the server fails.
+    pub async fn serve(self, addr: std::net::SocketAddr) -> Result<(), tonic::transport::Error> {
+        return tonic::transport::Server::builder()
+            .add_service(self.into_service())
+            .serve(addr)
+            .await;
+    }
+
+    /// Serve the registry in the background on `addr`, which may use port `0` to pick a free port,
+    /// and return the address it listens on.
+    pub async fn spawn(
+        self,
+        addr: std::net::SocketAddr,
+    ) -> Result<
+        (
+            std::net::SocketAddr,
+            tokio::task::JoinHandle<Result<(), tonic::transport::Error>>,
+        ),
+        std::io::Error,
+    > {
+        let listener = tokio::net::TcpListener::bind(addr).await?;
+        let addr = listener.local_addr()?;
+        let server = tonic::transport::Server::builder()
+            .add_service(self.into_service())
+            .serve_with_incoming(tokio_stream::wrappers::TcpListenerStream::new(listener));
+        return Ok((addr, tokio::spawn(server)));
+    }
+
+    async fn load(&self, did: &str) -> Result<DidRecord, tonic::Status> {
+        return self
+            .storage
+            .load(did)
+            .await
+            .map_err(error_to_status)?
+            .ok_or_else(|| tonic::Status::not_found(format!("{did} is not registered")));
+    }
+
+    /// The latest version of the document of a registered DID, which authorizes changes to it.
+    fn registered(record: &DidRecord) -> Result<DidDocument, tonic::Status> {
+        let latest = &record.versions[record.versions.len() - 1];
+        return DidDocument::deserialize(latest.document.clone())
+            .map_err(|e| tonic::Status::internal(e.to_string()));
+    }
+
+    /// Reject a proof that is not newer than the proof of the registered version, so that it cannot be replayed.
+    fn verify_newer(
+        did: &str,
+        record: &DidRecord,
+        proof: &DataIntegrityProof,
+    ) -> Result<(), tonic::Status> {
+        let previous = match record
+            .versions
+            .iter()
+            .rev()
+            .find_map(|version| version.proof.clone())
+        {
+            Some(previous) => previous,
+            None => return Ok(()),
+        };
+        let previous: DataIntegrityProof =
+            serde_json::from_value(previous).map_err(|e| tonic::Status::internal(e.to_string()))?;
+        if parse_time(&proof.created)? <= parse_time(&previous.created)? {
+            return Err(tonic::Status::permission_denied(format!(
+                "proof of {did} is not newer than the registered version"
+            )));
+        }
+        return Ok(());
+    }
+
+    /// Verify a submitted document, returning it without its proof along with the proof.
+    fn verify_write(
+        &self,
+        did: &str,
+        document: Option<pbjson_types::Struct>,
+        registered: Option<&DidDocument>,
+    ) -> Result<(serde_json::Value, Option<DataIntegrityProof>), tonic::Status> {
+        let document = document.ok_or_else(|| {
+            tonic::Status::invalid_argument(format!("document of {did} is missing"))
+        })?;
+        let document = serde_json::to_value(document)
+            .map_err(|e| tonic::Status::invalid_argument(e.to_string()))?;
+        let proof = document
+            .get("proof")
+            .map(|proof| serde_json::from_value::<DataIntegrityProof>(proof.clone()))
+            .transpose()
+            .map_err(|e| tonic::Status::invalid_argument(e.to_string()))?;
+
+        let document = match (&proof, self.require_proof) {
+            (None, false) => DidDocument::deserialize(document)
+                .map_err(|e| tonic::Status::invalid_argument(e.to_string()))?,
+            _ => knox::verify_version(did, document, registered).map_err(error_to_status)?,
+        };
+        knox::verify_document(did, &document).map_err(error_to_status)?;
+        document
+            .validate()
+            .map_err(|e| tonic::Status::invalid_argument(e.to_string()))?;
+
+        return Ok((document.serialize(), proof));
+    }
+}
+
+/// Index of the version of `record` selected by the `versionId` or `versionTime` parameter, the latest by default.
+fn select_version(
+    record: &DidRecord,
+    version_id: Option<String>,
+    version_time: Option<String>,
+) -> Result<usize, tonic::Status> {
+    if let Some(version_id) = version_id {
+        return version_id
+            .parse::<usize>()
+            .ok()
+            .filter(|id| (1..=record.versions.len()).contains(id))
+            .map(|id| id - 1)
+            .ok_or_else(|| tonic::Status::not_found(format!("version {version_id} not found")));
+    }
+
+    if let Some(version_time) = version_time {
+        let time = parse_time(&version_time)?;
+        let mut selected = None;
+        for (index, version) in record.versions.iter().enumerate() {
+            if parse_time(&version.created)? <= time {
+                selected = Some(index);
+            }
+        }
+        return selected
+            .ok_or_else(|| tonic::Status::not_found(format!("no version at {version_time}")));
+    }
+
+    return Ok(record.versions.len() - 1);
+}
+
+fn version_metadata(record: &DidRecord, index: usize) -> DocumentMetadata {
+    let latest = index + 1 == record.versions.len();
+    let updated = match (&record.deactivated, latest) {
+        (Some(deactivated), true) => Some(deactivated.clone()),
+        _ => (index > 0).then(|| record.versions[index].created.clone()),
+    };
+    return DocumentMetadata {
+        created: Some(record.versions[0].created.clone()),
+        updated,
+        deactivated: record.deactivated.as_ref().map(|_| true),
+        version_id: Some((index + 1).to_string()),
+        next_update: record
+            .versions
+            .get(index + 1)
+            .map(|next| next.created.clone()),
+        next_version_id: (!latest).then(|| (index + 2).to_string()),
+        ..Default::default()
+    };
+}
+
+fn to_value<T: serde::Serialize>(value: T) -> Result<serde_json::Value, tonic::Status> {
+    return serde_json::to_value(value).map_err(|e| tonic::Status::internal(e.to_string()));
+}
+
+fn to_struct<T: serde::Serialize>(value: T) -> Result<pbjson_types::Struct, tonic::Status> {
+    return serde_json::to_value(value)
+        .and_then(serde_json::from_value)
+        .map_err(|e| tonic::Status::internal(e.to_string()));
+}
+
+#[async_trait::async_trait]
+impl<S: Storage + 'static> RegistryService for RegistryServer<S> {
+    async fn create(
+        &self,
+        request: tonic::Request<registry::CreateRequest>,
+    ) -> Result<tonic::Response<registry::CreateResponse>, tonic::Status> {
+        let request = request.into_inner();
+        let (document, proof) = self.verify_write(&request.did, request.document, None)?;
+
+        let _writes = self.writes.lock().await;
+        if self
+            .storage
+            .load(&request.did)
+            .await
+            .map_err(error_to_status)?
+            .is_some()
+        {
+            return Err(tonic::Status::already_exists(format!(
+                "{} is already registered",
+                request.did
+            )));
+        }
+        let record = DidRecord {
+            versions: vec![DocumentVersion {
+                document,
+                created: now(),
+                proof: proof.map(to_value).transpose()?,
+            }],
+            deactivated: None,
+        };
+        self.storage
+            .store(&request.did, &record)
+            .await
+            .map_err(error_to_status)?;
+
+        return Ok(tonic::Response::new(registry::CreateResponse {}));
+    }
+
+    /// The requested DID may be a DID URL with a `versionId` or `versionTime` parameter.
+    /// The document of a deactivated DID is only returned when a version is requested.
+    async fn read(
+        &self,
+        request: tonic::Request<registry::ReadRequest>,
+    ) -> Result<tonic::Response<registry::ReadResponse>, tonic::Status> {
+        let request = request.into_inner();
+        let url = DidUrl::parse(&request.did)
+            .map_err(|e| tonic::Status::invalid_argument(e.to_string()))?;
+        let did = url.did().to_string();
+        knox::decode_did(&did).map_err(error_to_status)?;
+
+        let record = self.load(&did).await?;
+        let versioned = url.version_id().is_some() || url.version_time().is_some();
+        let index = select_version(&record, url.version_id(), url.version_time())?;
+        let version = &record.versions[index];
+        let document = match record.deactivated.is_some() && !versioned {
+            true => None,
+            false => {
+                let mut document = version.document.clone();
+                if let Some(proof) = &version.proof {
+                    document["proof"] = proof.clone();
+                }
+                Some(to_struct(document)?)
+            }
+        };
+
+        return Ok(tonic::Response::new(registry::ReadResponse {
+            did,
+            document,
+            metadata: Some(to_struct(version_metadata(&record, index))?),
+        }));
+    }
+
+    async fn update(
+        &self,
+        request: tonic::Request<registry::UpdateRequest>,
+    ) -> Result<tonic::Response<registry::UpdateResponse>, tonic::Status> {
+        let request = request.into_inner();
+        let did = request.did;
+
+        let _writes = self.writes.lock().await;
+        let mut record = self.load(&did).await?;
+        if record.deactivated.is_some() {
+            return Err(tonic::Status::failed_precondition(format!(
+                "{did} has been deactivated"
+            )));
+        }
+
+        let registered = Self::registered(&record)?;
+        let (document, proof) = self.verify_write(&did, request.document, Some(&registered))?;
+        if let Some(proof) = &proof {
+            Self::verify_newer(&did, &record, proof)?;
+        }
+
+        record.versions.push(DocumentVersion {
+            document,
+            created: now(),
+            proof: proof.map(to_value).transpose()?,
+        });
+        self.storage
+            .store(&did, &record)
+            .await
+            .map_err(error_to_status)?;
+
+        return Ok(tonic::Response::new(registry::UpdateResponse {}));
+    }
+
+    async fn deactivate(
+        &self,
+        request: tonic::Request<registry::DeactivateRequest>,
+    ) -> Result<tonic::Response<registry::DeactivateResponse>, tonic::Status> {
+        let request = request.into_inner();
+        let did = request.did;
+
+        let _writes = self.writes.lock().await;
+        let mut record = self.load(&did).await?;
+        if record.deactivated.is_some() {
+            return Err(tonic::Status::failed_precondition(format!(
+                "{did} has already been deactivated"
+            )));
+        }
+
+        match request.proof {
+            Some(proof) => {
+                let proof = serde_json::to_value(proof)
+                    .map_err(|e| tonic::Status::invalid_argument(e.to_string()))?;
+                let version_id = record.versions.len().to_string();
+                verify_deactivation(&did, &version_id, proof, &Self::registered(&record)?)
+                    .map_err(error_to_status)?;
+            }
+            None if self.require_proof => {
+                return Err(tonic::Status::permission_denied(format!(
+                    "deactivation of {did} has no proof"
+                )))
+            }
+            None => {}
+        }
+
+        record.deactivated = Some(now());
+        self.storage
+            .store(&did, &record)
+            .await
+            .map_err(error_to_status)?;
+
+        return Ok(tonic::Response::new(registry::DeactivateResponse {}));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FileStorage, MemoryStorage, RegistryServer, Storage};
+    use crate::registry_client::registry::registry_service_server::RegistryService;
+    use crate::registry_client::registry::{
+        CreateRequest, DeactivateRequest, ReadRequest, UpdateRequest,
+    };
+    use crate::{
+        sign_deactivation, sign_document, RegistryResolver, RegistryResolverConfig, RetryPolicy,
+    };
+    use signature::signer::Ed25519DidSigner;
+    use signature::suite::VerificationRelation;
+    use ssi::resolver::ResolutionOptions;
+    use ssi::test_utils::signer_document;
+    use ssi::DIDResolver;
+
+    macro_rules! aw {
+        ($e:expr) => {
+            tokio_test::block_on($e)
+        };
+    }
+
+    fn create_struct(document: serde_json::Value) -> Option<pbjson_types::Struct> {
+        return Some(serde_json::from_value(document).unwrap());
+    }
+
+    /// Resolver connected to `server` served on a free local port.
+    async fn connect<S: Storage + 'static>(server: RegistryServer<S>) -> RegistryResolver {
+        let (addr, _) = server.spawn("127.0.0.1:0".parse().unwrap()).await.unwrap();
+        let config = RegistryResolverConfig {
+            retry: RetryPolicy::none(),
+            ..RegistryResolverConfig::new(format!("http://{addr}"))
+        };
+        return RegistryResolver::from_config(config).await.unwrap();
+    }
+
+    #[test]
+    fn test_lifecycle() {
+        let signer = Ed25519DidSigner::new();
+        let doc = signer_document(&signer, &[VerificationRelation::CapabilityInvocation]);
+        let did = doc.id.clone();
+        let mut updated = doc.clone();
+        updated
+            .also_known_as
+            .push("https://example.com/knox".to_string());
+
+        aw!(async {
+            let resolver = connect(RegistryServer::new(MemoryStorage::new()))
+                .await
+                .with_signer(signer);
+            resolver.create(&did, doc.clone()).await.unwrap();
+            resolver.update(&did, updated.clone()).await.unwrap();
+
+            let res = resolver.resolve(&did, &ResolutionOptions::default()).await;
+            assert_eq!(res.did_document, Some(updated.clone()));
+            assert_eq!(res.did_document_metadata.version_id, Some("2".to_string()));
+            assert!(res.did_document_metadata.updated.is_some());
+
+            let options = ResolutionOptions {
+                version_id: Some("1".to_string()),
+                ..Default::default()
+            };
+            let res = resolver.resolve(&did, &options).await;
+            assert_eq!(res.did_document, Some(doc.clone()));
+            assert_eq!(
+                res.did_document_metadata.next_version_id,
+                Some("2".to_string())
+            );
+
+            resolver.deactivate(&did).await.unwrap();
+            let res = resolver.resolve(&did, &ResolutionOptions::default()).await;
+            assert_eq!(
+                res.did_resolution_metadata.error,
+                Some("deactivated".to_string())
+            );
+            let res = resolver.update(&did, updated).await;
+            assert_eq!(res.unwrap_err().kind, ssi::error::ErrorKind::Deactivated);
+        });
+    }
+
+    #[rstest::rstest]
+    #[case::signed(true, false, None)]
+    #[case::unsigned(false, false, Some(ssi::error::ErrorKind::Unauthorized))]
+    #[case::unsigned_allowed(false, true, None)]
+    fn test_create(
+        #[case] signed: bool,
+        #[case] allow_unsigned: bool,
+        #[case] expect_error_kind: Option<ssi::error::ErrorKind>,
+    ) {
+        let signer = Ed25519DidSigner::new();
+        let doc = signer_document(&signer, &[VerificationRelation::CapabilityInvocation]);
+        let server = match allow_unsigned {
+            true => RegistryServer::new(MemoryStorage::new()).allow_unsigned(),
+            false => RegistryServer::new(MemoryStorage::new()),
+        };
+
+        aw!(async {
+            let resolver = match signed {
+                true => connect(server).await.with_signer(signer),
+                false => connect(server).await,
+            };
+            let res = resolver.create(&doc.id, doc.clone()).await;
+            assert_eq!(res.err().map(|e| e.kind), expect_error_kind);
+
+            // a DID can only be registered once
+            if expect_error_kind.is_none() {
+                let res = resolver.create(&doc.id, doc.clone()).await;
+                assert_eq!(res.unwrap_err().kind, ssi::error::ErrorKind::InvalidData);
+            }
+        });
+    }
+
+    #[test]
+    fn test_replayed_update() {
+        let signer = Ed25519DidSigner::new();
+        let doc = signer_document(&signer, &[VerificationRelation::CapabilityInvocation]);
+        let server = RegistryServer::new(MemoryStorage::new());
+        let create = CreateRequest {
+            did: doc.id.clone(),
+            document: create_struct(sign_document(&doc, &signer).unwrap()),
+        };
+        aw!(server.create(tonic::Request::new(create))).unwrap();
+
+        let older = sign_document(&doc, &signer).unwrap();
+        let newer = sign_document(&doc, &signer).unwrap();
+        let update = |document| {
+            let request = UpdateRequest {
+                did: doc.id.clone(),
+                document: create_struct(document),
+            };
+            aw!(server.update(tonic::Request::new(request))).map(|_| ())
+        };
+        assert!(update(newer.clone()).is_ok());
+        assert_eq!(
+            update(older).unwrap_err().code(),
+            tonic::Code::PermissionDenied
+        );
+        assert_eq!(
+            update(newer).unwrap_err().code(),
+            tonic::Code::PermissionDenied
+        );
+    }
+
+    #[rstest::rstest]
+    #[case::signed(Some(false), false, None)]
+    #[case::unsigned(None, false, Some(ssi::error::ErrorKind::Unauthorized))]
+    #[case::other_key(Some(true), false, Some(ssi::error::ErrorKind::Unauthorized))]
+    #[case::unsigned_allowed(None, true, None)]
+    fn test_deactivate(
+        #[case] signed_by_other: Option<bool>,
+        #[case] allow_unsigned: bool,
+        #[case] expect_error_kind: Option<ssi::error::ErrorKind>,
+    ) {
+        let signer = Ed25519DidSigner::new();
+        let doc = signer_document(&signer, &[VerificationRelation::CapabilityInvocation]);
+        let server = match allow_unsigned {
+            true => RegistryServer::new(MemoryStorage::new()).allow_unsigned(),
+            false => RegistryServer::new(MemoryStorage::new()),
+        };
+        let create = CreateRequest {
+            did: doc.id.clone(),
+            document: create_struct(sign_document(&doc, &signer).unwrap()),
+        };
+        aw!(server.create(tonic::Request::new(create))).unwrap();
+
+        aw!(async {
+            let resolver = match signed_by_other {
+                Some(true) => connect(server).await.with_signer(Ed25519DidSigner::new()),
+                Some(false) => connect(server).await.with_signer(signer),
+                None => connect(server).await,
+            };
+            let res = resolver.deactivate(&doc.id).await;
+            assert_eq!(res.err().map(|e| e.kind), expect_error_kind);
+        });
+    }
+
+    #[test]
+    fn test_deactivate_stale_proof() {
+        let signer = Ed25519DidSigner::new();
+        let doc = signer_document(&signer, &[VerificationRelation::CapabilityInvocation]);
+        let server = RegistryServer::new(MemoryStorage::new());
+        let create = CreateRequest {
+            did: doc.id.clone(),
+            document: create_struct(sign_document(&doc, &signer).unwrap()),
+        };
+        aw!(server.create(tonic::Request::new(create))).unwrap();
+        let proof = sign_deactivation(&doc.id, "1", &signer).unwrap();
+
+        // the proof of control of the deactivation is void once the DID has been updated
+        let update = UpdateRequest {
+            did: doc.id.clone(),
+            document: create_struct(sign_document(&doc, &signer).unwrap()),
+        };
+        aw!(server.update(tonic::Request::new(update))).unwrap();
+        let deactivate = DeactivateRequest {
+            did: doc.id.clone(),
+            proof: create_struct(proof),
+        };
+        let res = aw!(server.deactivate(tonic::Request::new(deactivate)));
+        assert_eq!(res.unwrap_err().code(), tonic::Code::PermissionDenied);
+    }
+
+    #[rstest::rstest]
+    #[case::not_registered(
+        "did:knox:z6MkfFmsob7fC3MmqU1JVfdBnMbnAw7xm1mrEtPvAoojLcRh",
+        tonic::Code::NotFound
+    )]
+    #[case::other_method(
+        "did:key:z6MkfFmsob7fC3MmqU1JVfdBnMbnAw7xm1mrEtPvAoojLcRh",
+        tonic::Code::InvalidArgument
+    )]
+    #[case::missing_version("{did}?versionId=2", tonic::Code::NotFound)]
+    #[case::before_creation("{did}?versionTime=2000-01-01T00:00:00Z", tonic::Code::NotFound)]
+    #[case::invalid_time("{did}?versionTime=yesterday", tonic::Code::InvalidArgument)]
+    fn test_read_failure(#[case] did: &str, #[case] expect_code: tonic::Code) {
+        let signer = Ed25519DidSigner::new();
+        let doc = signer_document(&signer, &[VerificationRelation::CapabilityInvocation]);
+        let server = RegistryServer::new(MemoryStorage::new());
+        let create = CreateRequest {
+            did: doc.id.clone(),
+            document: create_struct(sign_document(&doc, &signer).unwrap()),
+        };
+        aw!(server.create(tonic::Request::new(create))).unwrap();
+
+        let request = ReadRequest {
+            did: did.replace("{did}", &doc.id),
+        };
+        let res = aw!(server.read(tonic::Request::new(request)));
+        assert_eq!(res.unwrap_err().code(), expect_code);
+    }
+
+    #[test]
+    fn test_file_storage() {
+        let directory = tempfile::tempdir().unwrap();
+        let signer = Ed25519DidSigner::new();
+        let doc = signer_document(&signer, &[VerificationRelation::CapabilityInvocation]);
+        let create = CreateRequest {
+            did: doc.id.clone(),
+            document: create_struct(sign_document(&doc, &signer).unwrap()),
+        };
+        let server = RegistryServer::new(FileStorage::new(directory.path()).unwrap());
+        aw!(server.create(tonic::Request::new(create))).unwrap();
+
+        // documents are kept when the registry restarts
+        let server = RegistryServer::new(FileStorage::new(directory.path()).unwrap());
+        let res = aw!(server.read(tonic::Request::new(ReadRequest {
+            did: doc.id.clone()
+        })))
+        .unwrap()
+        .into_inner();
+        // the proof of the version is returned with the document
+        let mut document = serde_json::to_value(res.document.unwrap()).unwrap();
+        assert!(document.as_object_mut().unwrap().remove("proof").is_some());
+        assert_eq!(ssi::did::DidDocument::deserialize(document).unwrap(), doc);
+    }
+}
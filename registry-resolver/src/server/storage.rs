@@ -0,0 +1,188 @@
+use ssi::error::{ErrorKind, ResolverError};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// Everything the registry keeps about a DID.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DidRecord {
+    /// Versions of the document, oldest first, version `n` being at index `n - 1`.
+    pub versions: Vec<DocumentVersion>,
+    /// When the DID was deactivated.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deactivated: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DocumentVersion {
+    /// The document without its proof.
+    pub document: serde_json::Value,
+    /// When the version was registered.
+    pub created: String,
+    /// The `capabilityInvocation` proof that authorized the version, when it was signed.
+    /// It is returned with the document so that resolvers can check it against the previous version.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proof: Option<serde_json::Value>,
+}
+
+fn storage_error(e: impl std::fmt::Display) -> ResolverError {
+    return ResolverError::new(e.to_string(), ErrorKind::Uncategorized);
+}
+
+/// Backend keeping the records of the registry. The server serializes writes, so implementations only need
+/// `store` to replace the record of a DID atomically.
+#[async_trait::async_trait]
+pub trait Storage: Send + Sync {
+    async fn load(&self, did: &str) -> Result<Option<DidRecord>, ResolverError>;
+
+    async fn store(&self, did: &str, record: &DidRecord) -> Result<(), ResolverError>;
+}
+
+/// Storage keeping the records in memory, for tests.
+#[derive(Debug, Default)]
+pub struct MemoryStorage {
+    records: std::sync::Mutex<HashMap<String, DidRecord>>,
+}
+
+impl MemoryStorage {
+    pub fn new() -> Self {
+        return Self::default();
+    }
+}
+
+#[async_trait::async_trait]
+impl Storage for MemoryStorage {
+    async fn load(&self, did: &str) -> Result<Option<DidRecord>, ResolverError> {
+        Ok(self.records.lock().unwrap().get(did).cloned())
+    }
+
+    async fn store(&self, did: &str, record: &DidRecord) -> Result<(), ResolverError> {
+        self.records
+            .lock()
+            .unwrap()
+            .insert(did.to_string(), record.clone());
+        return Ok(());
+    }
+}
+
+/// Storage keeping the record of each DID as a JSON file in a directory, named after the method-specific id.
+#[derive(Debug, Clone)]
+pub struct FileStorage {
+    directory: PathBuf,
+}
+
+impl FileStorage {
+    /// Keep the records in `directory`, which is created if it does not exist.
+    pub fn new(directory: impl Into<PathBuf>) -> Result<Self, ResolverError> {
+        let directory = directory.into();
+        std::fs::create_dir_all(&directory).map_err(storage_error)?;
+        return Ok(Self { directory });
+    }
+
+    fn path(&self, did: &str) -> Result<PathBuf, ResolverError> {
+        let did = ssi::did::Did::parse(did)
+            .map_err(|e| ResolverError::new(e.to_string(), ErrorKind::InvalidDid))?;
+        let id = did.method_specific_id();
+        if !id.chars().all(|c| c.is_ascii_alphanumeric()) {
+            return Err(ResolverError::new(
+                format!("{did} cannot be stored as a file"),
+                ErrorKind::InvalidDid,
+            ));
+        }
+        return Ok(self.directory.join(format!("{id}.json")));
+    }
+}
+
+#[async_trait::async_trait]
+impl Storage for FileStorage {
+    async fn load(&self, did: &str) -> Result<Option<DidRecord>, ResolverError> {
+        let contents = match tokio::fs::read(self.path(did)?).await {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(storage_error(e)),
+        };
+        return serde_json::from_slice(&contents).map_err(storage_error);
+    }
+
+    /// The record is written to a temporary file first, so that a failed write does not corrupt it.
+    async fn store(&self, did: &str, record: &DidRecord) -> Result<(), ResolverError> {
+        let path = self.path(did)?;
+        let temporary = path.with_extension("json.tmp");
+        let contents = serde_json::to_vec_pretty(record).map_err(storage_error)?;
+        tokio::fs::write(&temporary, contents)
+            .await
+            .map_err(storage_error)?;
+        tokio::fs::rename(&temporary, &path)
+            .await
+            .map_err(storage_error)?;
+        return Ok(());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DidRecord, DocumentVersion, FileStorage, MemoryStorage, Storage};
+    use ssi::error::ErrorKind;
+
+    macro_rules! aw {
+        ($e:expr) => {
+            tokio_test::block_on($e)
+        };
+    }
+
+    const DID: &str = "did:knox:z6MkfFmsob7fC3MmqU1JVfdBnMbnAw7xm1mrEtPvAoojLcRh";
+
+    fn create_record() -> DidRecord {
+        return DidRecord {
+            versions: vec![DocumentVersion {
+                document: serde_json::json!({ "id": DID }),
+                created: "2022-06-01T00:00:00Z".to_string(),
+                proof: None,
+            }],
+            deactivated: None,
+        };
+    }
+
+    #[rstest::rstest]
+    #[case::memory(false)]
+    #[case::file(true)]
+    fn test_store_and_load(#[case] file: bool) {
+        let directory = tempfile::tempdir().unwrap();
+        let storage: Box<dyn Storage> = match file {
+            true => Box::new(FileStorage::new(directory.path()).unwrap()),
+            false => Box::new(MemoryStorage::new()),
+        };
+        assert_eq!(aw!(storage.load(DID)).unwrap(), None);
+
+        let mut record = create_record();
+        aw!(storage.store(DID, &record)).unwrap();
+        assert_eq!(aw!(storage.load(DID)).unwrap(), Some(record.clone()));
+
+        record.deactivated = Some("2022-06-02T00:00:00Z".to_string());
+        aw!(storage.store(DID, &record)).unwrap();
+        assert_eq!(aw!(storage.load(DID)).unwrap(), Some(record));
+    }
+
+    #[rstest::rstest]
+    #[case::path_traversal("did:knox:..%2F..%2Fetc%2Fpasswd")]
+    #[case::not_a_did("knox")]
+    fn test_file_storage_invalid_did(#[case] did: &str) {
+        let directory = tempfile::tempdir().unwrap();
+        let storage = FileStorage::new(directory.path()).unwrap();
+        let res = aw!(storage.store(did, &create_record()));
+        assert_eq!(res.unwrap_err().kind, ErrorKind::InvalidDid);
+    }
+
+    #[test]
+    fn test_file_storage_persists() {
+        let directory = tempfile::tempdir().unwrap();
+        aw!(FileStorage::new(directory.path())
+            .unwrap()
+            .store(DID, &create_record()))
+        .unwrap();
+
+        let reopened = FileStorage::new(directory.path()).unwrap();
+        assert_eq!(aw!(reopened.load(DID)).unwrap(), Some(create_record()));
+    }
+}
@@ -0,0 +1,218 @@
+use signature::error::{ErrorKind, SignatureError};
+use signature::signer::AsyncDIDSigner;
+use signature::suite::{Ed25519Signature, Signature, VerificationRelation};
+
+pub mod server;
+
+/// Body of `POST /sign`, asking the signing service to sign `data` with the key of the `keyId` verification method.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SignRequest {
+    pub key_id: String,
+    /// base64url encoded, without padding.
+    pub data: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct SignResponse {
+    /// base64url encoded, without padding.
+    pub signature: String,
+}
+
+fn error(kind: ErrorKind, message: String) -> SignatureError {
+    return SignatureError {
+        message,
+        ..SignatureError::new(kind)
+    };
+}
+
+fn request_error(e: reqwest::Error) -> SignatureError {
+    return SignatureError {
+        message: e.to_string(),
+        source: Some(Box::new(e)),
+        ..SignatureError::new(ErrorKind::Uncategorized)
+    };
+}
+
+/// Ed25519 signer whose key is held by a signing service, reached over HTTP, see `server::SigningServer`.
+pub struct RemoteSigner {
+    client: reqwest::Client,
+    url: String,
+    key_id: String,
+    bearer_token: Option<String>,
+}
+
+impl RemoteSigner {
+    /// Sign with the key of the `key_id` verification method, e.g. `did:knox:z6Mk…#key-1`, held by the service
+    /// at `url`.
+    pub fn new(url: impl Into<String>, key_id: impl Into<String>) -> Self {
+        return Self {
+            client: reqwest::Client::new(),
+            url: url.into().trim_end_matches('/').to_string(),
+            key_id: key_id.into(),
+            bearer_token: None,
+        };
+    }
+
+    /// Authorize the requests to the signing service with a bearer token.
+    pub fn with_bearer_token(mut self, token: impl Into<String>) -> Self {
+        self.bearer_token = Some(token.into());
+        return self;
+    }
+}
+
+#[async_trait::async_trait]
+impl AsyncDIDSigner<Ed25519Signature> for RemoteSigner {
+    async fn try_sign(&self, msg: &[u8]) -> Result<Ed25519Signature, SignatureError> {
+        let mut request = self
+            .client
+            .post(format!("{}/sign", self.url))
+            .json(&SignRequest {
+                key_id: self.key_id.clone(),
+                data: base64::encode_config(msg, base64::URL_SAFE_NO_PAD),
+            });
+        if let Some(token) = &self.bearer_token {
+            request = request.bearer_auth(token);
+        }
+
+        let response = request.send().await.map_err(request_error)?;
+        let status = response.status();
+        if !status.is_success() {
+            let kind = match status {
+                reqwest::StatusCode::NOT_FOUND => ErrorKind::KeyNotFound,
+                _ => ErrorKind::Uncategorized,
+            };
+            let body = response.text().await.unwrap_or_default();
+            return Err(error(
+                kind,
+                format!("signing service returned {status}: {body}"),
+            ));
+        }
+
+        let response: SignResponse = response.json().await.map_err(request_error)?;
+        let signature = base64::decode_config(response.signature, base64::URL_SAFE_NO_PAD)
+            .map_err(|e| error(ErrorKind::Uncategorized, format!("invalid signature: {e}")))?;
+        return Ed25519Signature::from_bytes(&signature);
+    }
+
+    fn get_proof_type(&self) -> String {
+        return signature::suite::PROOF_TYPE.to_string();
+    }
+
+    fn get_verification_method(&self, _relation: VerificationRelation) -> String {
+        return self.key_id.clone();
+    }
+
+    fn encode(&self, sig: Ed25519Signature) -> String {
+        return multibase::encode(multibase::Base::Base58Btc, sig);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::server::SigningServer;
+    use super::RemoteSigner;
+    use signature::error::ErrorKind;
+    use signature::keystore::{EncryptedFileKeyStore, KdfParams, KeyStore};
+    use signature::signer::{AsyncDIDSigner, Ed25519DidSigner};
+    use signature::suite::VerificationRelation;
+    use signature::verifier::{DIDVerifier, Ed25519DidVerifier};
+    use std::sync::Arc;
+
+    macro_rules! aw {
+        ($e:expr) => {
+            tokio_test::block_on($e)
+        };
+    }
+
+    const KEY_ID: &str = "did:knox:z6MkfFmsob7fC3MmqU1JVfdBnMbnAw7xm1mrEtPvAoojLcRh#key-1";
+    const TOKEN: &str = "secret";
+
+    /// Key store holding a single key for `KEY_ID`, with the verifier of that key.
+    fn create_key_store(directory: &tempfile::TempDir) -> (Arc<dyn KeyStore>, Ed25519DidVerifier) {
+        let params = KdfParams {
+            memory_cost: 64,
+            time_cost: 1,
+            parallelism: 1,
+        };
+        let keys = EncryptedFileKeyStore::open_with_params(
+            directory.path().join("keys.json"),
+            "password",
+            params,
+        )
+        .unwrap();
+        let signer = Ed25519DidSigner::new();
+        keys.insert(KEY_ID, &signer).unwrap();
+        return (Arc::new(keys), Ed25519DidVerifier::from(&signer));
+    }
+
+    #[test]
+    fn test_sign() {
+        let directory = tempfile::tempdir().unwrap();
+        let (keys, verifier) = create_key_store(&directory);
+
+        aw!(async {
+            let (addr, _) = SigningServer::new(keys)
+                .spawn("127.0.0.1:0".parse().unwrap())
+                .unwrap();
+            let signer = RemoteSigner::new(format!("http://{addr}/"), KEY_ID);
+            assert_eq!(
+                signer.get_verification_method(VerificationRelation::AssertionMethod),
+                KEY_ID
+            );
+
+            let signature = signer.try_sign(b"message").await.unwrap();
+            assert!(verifier.verify(b"message", &signature).is_ok());
+
+            let doc = serde_json::Value::default();
+            let proof = ssi::proof::create_data_integrity_proof_async(
+                &signer,
+                doc.clone(),
+                VerificationRelation::AssertionMethod,
+            )
+            .await
+            .unwrap();
+            assert_eq!(proof.verification_method, KEY_ID);
+            assert!(ssi::proof::verify_proof_value(doc, &proof, &verifier).is_ok());
+        });
+    }
+
+    #[rstest::rstest]
+    #[case::authorized(KEY_ID, Some(TOKEN), None)]
+    #[case::unknown_key(
+        "did:knox:z6MkfFmsob7fC3MmqU1JVfdBnMbnAw7xm1mrEtPvAoojLcRh#key-2",
+        Some(TOKEN),
+        Some(ErrorKind::KeyNotFound)
+    )]
+    #[case::missing_token(KEY_ID, None, Some(ErrorKind::Uncategorized))]
+    #[case::wrong_token(KEY_ID, Some("wrong"), Some(ErrorKind::Uncategorized))]
+    fn test_sign_errors(
+        #[case] key_id: &str,
+        #[case] token: Option<&str>,
+        #[case] expect_error: Option<ErrorKind>,
+    ) {
+        let directory = tempfile::tempdir().unwrap();
+        let (keys, _) = create_key_store(&directory);
+
+        aw!(async {
+            let (addr, _) = SigningServer::new(keys)
+                .with_bearer_token(TOKEN)
+                .spawn("127.0.0.1:0".parse().unwrap())
+                .unwrap();
+            let mut signer = RemoteSigner::new(format!("http://{addr}"), key_id);
+            if let Some(token) = token {
+                signer = signer.with_bearer_token(token);
+            }
+
+            let res = signer.try_sign(b"message").await;
+            assert_eq!(res.err().map(|e| e.kind), expect_error);
+        });
+    }
+
+    #[test]
+    fn test_unreachable() {
+        let signer = RemoteSigner::new("http://127.0.0.1:1", KEY_ID);
+        let res = aw!(signer.try_sign(b"message"));
+        assert_eq!(res.err().unwrap().kind, ErrorKind::Uncategorized);
+    }
+}
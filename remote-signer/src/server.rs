@@ -0,0 +1,111 @@
+use crate::{SignRequest, SignResponse};
+use axum::extract::Extension;
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use signature::error::ErrorKind;
+use signature::keystore::KeyStore;
+use signature::signer::DIDSigner;
+use std::sync::Arc;
+
+/// Stand-in for a KMS or signing service: signs with the keys of a `KeyStore`, which never leave the server.
+pub struct SigningServer {
+    keys: Arc<dyn KeyStore>,
+    bearer_token: Option<String>,
+}
+
+struct State {
+    keys: Arc<dyn KeyStore>,
+    bearer_token: Option<String>,
+}
+
+impl SigningServer {
+    pub fn new(keys: Arc<dyn KeyStore>) -> Self {
+        return Self {
+            keys,
+            bearer_token: None,
+        };
+    }
+
+    /// Only serve requests authorized with `token` as a bearer token.
+    pub fn with_bearer_token(mut self, token: impl Into<String>) -> Self {
+        self.bearer_token = Some(token.into());
+        return self;
+    }
+
+    /// Router serving `POST /sign`.
+    pub fn router(self) -> axum::Router {
+        let state = Arc::new(State {
+            keys: self.keys,
+            bearer_token: self.bearer_token,
+        });
+        return axum::Router::new()
+            .route("/sign", axum::routing::post(sign))
+            .layer(Extension(state));
+    }
+
+    pub async fn serve(self, addr: std::net::SocketAddr) -> Result<(), hyper::Error> {
+        return axum::Server::bind(&addr)
+            .serve(self.router().into_make_service())
+            .await;
+    }
+
+    /// Serve in the background on `addr`, which may use port `0` to pick a free port, and return the address it
+    /// listens on.
+    pub fn spawn(
+        self,
+        addr: std::net::SocketAddr,
+    ) -> Result<
+        (
+            std::net::SocketAddr,
+            tokio::task::JoinHandle<Result<(), hyper::Error>>,
+        ),
+        hyper::Error,
+    > {
+        let server = axum::Server::try_bind(&addr)?.serve(self.router().into_make_service());
+        return Ok((server.local_addr(), tokio::spawn(server)));
+    }
+}
+
+fn error(status: StatusCode, message: String) -> Response {
+    return (status, message).into_response();
+}
+
+async fn sign(
+    Extension(state): Extension<Arc<State>>,
+    headers: HeaderMap,
+    Json(request): Json<SignRequest>,
+) -> Response {
+    if let Some(token) = &state.bearer_token {
+        let authorization = headers
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok());
+        if authorization != Some(format!("Bearer {token}").as_str()) {
+            return error(StatusCode::UNAUTHORIZED, "invalid bearer token".to_string());
+        }
+    }
+
+    let data = match base64::decode_config(&request.data, base64::URL_SAFE_NO_PAD) {
+        Ok(data) => data,
+        Err(e) => {
+            return error(
+                StatusCode::BAD_REQUEST,
+                format!("data is not base64url: {e}"),
+            )
+        }
+    };
+    let signer = match state.keys.unlock(&request.key_id) {
+        Ok(signer) => signer,
+        Err(e) if e.kind == ErrorKind::KeyNotFound => {
+            return error(StatusCode::NOT_FOUND, e.message)
+        }
+        Err(e) => return error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    };
+    return match signer.try_sign(&data) {
+        Ok(signature) => Json(SignResponse {
+            signature: base64::encode_config(signature, base64::URL_SAFE_NO_PAD),
+        })
+        .into_response(),
+        Err(e) => error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    };
+}
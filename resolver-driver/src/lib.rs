@@ -0,0 +1,357 @@
+use axum::extract::{Extension, Path, Query};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use ssi::error::{ErrorKind, ResolverError};
+use ssi::resolver::{
+    ResolutionOptions, ResolutionResult, CONTENT_TYPE_DID_JSON, CONTENT_TYPE_DID_LD_JSON,
+};
+use ssi::DIDResolver;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+pub const CONTENT_TYPE_DID_RESOLUTION: &str =
+    "application/ld+json;profile=\"https://w3id.org/did-resolution\"";
+const DID_RESOLUTION_PROFILE: &str = "https://w3id.org/did-resolution";
+const DID_RESOLUTION_CONTEXT: &str = "https://w3id.org/did-resolution/v1";
+
+/// Representation of a resolved DID returned by the endpoint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Representation {
+    /// The DID document with its JSON-LD context.
+    DidLdJson,
+    /// The DID document without its JSON-LD context.
+    DidJson,
+    /// The DID Resolution result, holding the document along with the resolution and document metadata.
+    ResolutionResult,
+}
+
+impl Representation {
+    pub fn content_type(&self) -> &'static str {
+        return match self {
+            Representation::DidLdJson => CONTENT_TYPE_DID_LD_JSON,
+            Representation::DidJson => CONTENT_TYPE_DID_JSON,
+            Representation::ResolutionResult => CONTENT_TYPE_DID_RESOLUTION,
+        };
+    }
+
+    /// The representation matching a media range of an `Accept` header.
+    fn from_media_range(range: &str) -> Option<Self> {
+        let mut parts = range.split(';').map(str::trim);
+        let media_type = parts.next()?.to_ascii_lowercase();
+        let profile = parts
+            .filter_map(|parameter| parameter.split_once('='))
+            .find(|(name, _)| name.trim().eq_ignore_ascii_case("profile"))
+            .map(|(_, value)| value.trim().trim_matches('"'));
+
+        return match (media_type.as_str(), profile) {
+            ("application/ld+json", Some(DID_RESOLUTION_PROFILE)) => {
+                Some(Representation::ResolutionResult)
+            }
+            (CONTENT_TYPE_DID_LD_JSON, _) | ("application/*", _) | ("*/*", _) => {
+                Some(Representation::DidLdJson)
+            }
+            (CONTENT_TYPE_DID_JSON, _) | ("application/json", _) => Some(Representation::DidJson),
+            _ => None,
+        };
+    }
+
+    /// Negotiate the representation for an `Accept` header, taking the supported media range with the highest quality.
+    /// Without an `Accept` header, the document is returned as `application/did+ld+json`.
+    pub fn negotiate(accept: Option<&str>) -> Option<Self> {
+        let accept = match accept {
+            Some(accept) if !accept.trim().is_empty() => accept,
+            _ => return Some(Representation::DidLdJson),
+        };
+
+        let mut selected: Option<(f32, Self)> = None;
+        for range in accept.split(',') {
+            let quality = quality(range);
+            let representation = match Self::from_media_range(range) {
+                Some(representation) if quality > 0.0 => representation,
+                _ => continue,
+            };
+            match selected {
+                Some((selected_quality, _)) if selected_quality >= quality => {}
+                _ => selected = Some((quality, representation)),
+            }
+        }
+        return selected.map(|(_, representation)| representation);
+    }
+}
+
+/// The `q` parameter of a media range, `1` when not set.
+fn quality(range: &str) -> f32 {
+    return range
+        .split(';')
+        .skip(1)
+        .filter_map(|parameter| parameter.split_once('='))
+        .find(|(name, _)| name.trim() == "q")
+        .and_then(|(_, value)| value.trim().parse().ok())
+        .unwrap_or(1.0);
+}
+
+/// The HTTP status of a resolution error, following the DID Resolution HTTP(S) binding.
+pub fn error_status(kind: &ErrorKind) -> StatusCode {
+    return match kind {
+        ErrorKind::InvalidDid => StatusCode::BAD_REQUEST,
+        ErrorKind::DocumentNotFound => StatusCode::NOT_FOUND,
+        ErrorKind::RepresentationNotSupported => StatusCode::NOT_ACCEPTABLE,
+        ErrorKind::Deactivated => StatusCode::GONE,
+        ErrorKind::MethodNotSupported => StatusCode::NOT_IMPLEMENTED,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+}
+
+/// Router serving `GET /1.0/identifiers/{did}` with `resolver`, per the DID Resolution HTTP(S) binding.
+/// The `versionId` and `versionTime` query parameters select a version of the document.
+/// Failed resolutions are answered with the DID Resolution result reporting the error, whatever the requested representation.
+pub fn router(resolver: Arc<dyn DIDResolver>) -> axum::Router {
+    return axum::Router::new()
+        .route("/1.0/identifiers/:did", axum::routing::get(resolve))
+        .layer(Extension(resolver));
+}
+
+async fn resolve(
+    Extension(resolver): Extension<Arc<dyn DIDResolver>>,
+    Path(did): Path<String>,
+    Query(parameters): Query<HashMap<String, String>>,
+    headers: HeaderMap,
+) -> Response {
+    let accept = headers
+        .get(header::ACCEPT)
+        .and_then(|accept| accept.to_str().ok());
+    let representation = match Representation::negotiate(accept) {
+        Some(representation) => representation,
+        None => {
+            let e = ResolverError::new(
+                format!("representation {} is not supported", accept.unwrap_or("")),
+                ErrorKind::RepresentationNotSupported,
+            );
+            return respond(
+                ResolutionResult::from_error(e),
+                Representation::ResolutionResult,
+            );
+        }
+    };
+
+    let options = ResolutionOptions {
+        accept: Some(match representation {
+            Representation::DidJson => CONTENT_TYPE_DID_JSON.to_string(),
+            _ => CONTENT_TYPE_DID_LD_JSON.to_string(),
+        }),
+        version_id: parameters.get("versionId").cloned(),
+        version_time: parameters.get("versionTime").cloned(),
+        ..Default::default()
+    };
+    let result = resolver.resolve(&did, &options).await;
+    return respond(result, representation);
+}
+
+fn respond(result: ResolutionResult, representation: Representation) -> Response {
+    let status = match result.error() {
+        Some(e) => error_status(&e.kind),
+        None => StatusCode::OK,
+    };
+
+    let (representation, body) = match (representation, &result.did_document) {
+        (Representation::DidLdJson, Some(document)) if status == StatusCode::OK => {
+            (representation, document.serialize())
+        }
+        (Representation::DidJson, Some(document)) if status == StatusCode::OK => {
+            let mut document = document.clone();
+            document.context.clear();
+            (representation, document.serialize())
+        }
+        _ => {
+            let mut body = serde_json::to_value(&result).unwrap_or_default();
+            body["@context"] = serde_json::Value::from(DID_RESOLUTION_CONTEXT);
+            (Representation::ResolutionResult, body)
+        }
+    };
+
+    return (
+        status,
+        [(header::CONTENT_TYPE, representation.content_type())],
+        body.to_string(),
+    )
+        .into_response();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{router, Representation, CONTENT_TYPE_DID_RESOLUTION};
+    use axum::http::{header, Request, StatusCode};
+    use ssi::did::{Did, DidDocument};
+    use ssi::error::{ErrorKind, ResolverError};
+    use ssi::resolver::{
+        DocumentMetadata, ResolutionOptions, ResolutionResult, CONTENT_TYPE_DID_JSON,
+        CONTENT_TYPE_DID_LD_JSON,
+    };
+    use ssi::DIDResolver;
+    use std::sync::Arc;
+    use tower::ServiceExt;
+
+    macro_rules! aw {
+        ($e:expr) => {
+            tokio_test::block_on($e)
+        };
+    }
+
+    /// Resolves `did:example:found`, reports `did:example:deactivated` as deactivated and does not find other DIDs.
+    struct ExampleResolver {}
+
+    #[async_trait::async_trait]
+    impl DIDResolver for ExampleResolver {
+        async fn resolve(&self, did: &str, options: &ResolutionOptions) -> ResolutionResult {
+            let method = match Did::parse(did) {
+                Ok(parsed) => parsed.method().to_string(),
+                Err(e) => {
+                    return ResolutionResult::from_error(ResolverError::new(
+                        e.to_string(),
+                        ErrorKind::InvalidDid,
+                    ))
+                }
+            };
+            let metadata = DocumentMetadata {
+                version_id: options.version_id.clone(),
+                ..Default::default()
+            };
+
+            return match (method.as_str(), did) {
+                ("example", "did:example:found") => {
+                    ResolutionResult::from_document(DidDocument::new(did), options)
+                        .with_document_metadata(metadata)
+                }
+                ("example", "did:example:deactivated") => ResolutionResult::default()
+                    .with_document_metadata(DocumentMetadata {
+                        deactivated: Some(true),
+                        ..metadata
+                    }),
+                ("example", _) => ResolutionResult::from_error(ResolverError::new(
+                    did,
+                    ErrorKind::DocumentNotFound,
+                )),
+                _ => ResolutionResult::from_error(ResolverError::new(
+                    did,
+                    ErrorKind::MethodNotSupported,
+                )),
+            };
+        }
+
+        async fn create(&self, _did: &str, _doc: DidDocument) -> Result<(), ResolverError> {
+            Ok(())
+        }
+
+        fn get_method(&self) -> &'static str {
+            return "example";
+        }
+    }
+
+    /// `GET` the resolution of `path` and return the status, content type and body of the response.
+    fn get(path: &str, accept: Option<&str>) -> (StatusCode, String, serde_json::Value) {
+        let mut request = Request::builder().uri(path);
+        if let Some(accept) = accept {
+            request = request.header(header::ACCEPT, accept);
+        }
+        let request = request.body(axum::body::Body::empty()).unwrap();
+
+        let response = aw!(router(Arc::new(ExampleResolver {})).oneshot(request)).unwrap();
+        let status = response.status();
+        let content_type = response.headers()[header::CONTENT_TYPE]
+            .to_str()
+            .unwrap()
+            .to_string();
+        let body = aw!(hyper::body::to_bytes(response.into_body())).unwrap();
+        return (status, content_type, serde_json::from_slice(&body).unwrap());
+    }
+
+    #[rstest::rstest]
+    #[case::default(None, Some(Representation::DidLdJson))]
+    #[case::any(Some("*/*"), Some(Representation::DidLdJson))]
+    #[case::did_json(Some("application/did+json"), Some(Representation::DidJson))]
+    #[case::resolution_result(
+        Some("application/ld+json;profile=\"https://w3id.org/did-resolution\""),
+        Some(Representation::ResolutionResult)
+    )]
+    #[case::unquoted_profile(
+        Some("application/ld+json; profile=https://w3id.org/did-resolution"),
+        Some(Representation::ResolutionResult)
+    )]
+    #[case::quality(
+        Some("application/did+json;q=0.5, application/did+ld+json;q=0.8, text/html"),
+        Some(Representation::DidLdJson)
+    )]
+    #[case::refused(Some("application/did+json;q=0"), None)]
+    #[case::plain_ld_json(Some("application/ld+json"), None)]
+    #[case::unsupported(Some("text/html"), None)]
+    fn test_negotiate(#[case] accept: Option<&str>, #[case] expect: Option<Representation>) {
+        assert_eq!(Representation::negotiate(accept), expect);
+    }
+
+    #[rstest::rstest]
+    #[case::did_ld_json(None, CONTENT_TYPE_DID_LD_JSON, true)]
+    #[case::did_json(Some(CONTENT_TYPE_DID_JSON), CONTENT_TYPE_DID_JSON, false)]
+    fn test_resolve_document(
+        #[case] accept: Option<&str>,
+        #[case] expect_content_type: &str,
+        #[case] expect_context: bool,
+    ) {
+        let (status, content_type, body) = get("/1.0/identifiers/did:example:found", accept);
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(content_type, expect_content_type);
+        assert_eq!(body["id"], "did:example:found");
+        assert_eq!(body.get("@context").is_some(), expect_context);
+    }
+
+    #[test]
+    fn test_resolve_resolution_result() {
+        let (status, content_type, body) = get(
+            "/1.0/identifiers/did:example:found?versionId=2",
+            Some(CONTENT_TYPE_DID_RESOLUTION),
+        );
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(content_type, CONTENT_TYPE_DID_RESOLUTION);
+        assert_eq!(body["didDocument"]["id"], "did:example:found");
+        assert_eq!(body["didDocumentMetadata"]["versionId"], "2");
+        assert_eq!(
+            body["didResolutionMetadata"]["contentType"],
+            CONTENT_TYPE_DID_LD_JSON
+        );
+    }
+
+    #[rstest::rstest]
+    #[case::not_found("did:example:missing", None, StatusCode::NOT_FOUND, "notFound")]
+    #[case::invalid_did("not-a-did", None, StatusCode::BAD_REQUEST, "invalidDid")]
+    #[case::method_not_supported(
+        "did:other:found",
+        None,
+        StatusCode::NOT_IMPLEMENTED,
+        "methodNotSupported"
+    )]
+    #[case::not_acceptable(
+        "did:example:found",
+        Some("text/html"),
+        StatusCode::NOT_ACCEPTABLE,
+        "representationNotSupported"
+    )]
+    fn test_resolve_failure(
+        #[case] did: &str,
+        #[case] accept: Option<&str>,
+        #[case] expect_status: StatusCode,
+        #[case] expect_error: &str,
+    ) {
+        let (status, content_type, body) = get(&format!("/1.0/identifiers/{did}"), accept);
+        assert_eq!(status, expect_status);
+        assert_eq!(content_type, CONTENT_TYPE_DID_RESOLUTION);
+        assert_eq!(body["didResolutionMetadata"]["error"], expect_error);
+        assert!(body["didDocument"].is_null());
+    }
+
+    #[test]
+    fn test_resolve_deactivated() {
+        let (status, content_type, body) = get("/1.0/identifiers/did:example:deactivated", None);
+        assert_eq!(status, StatusCode::GONE);
+        assert_eq!(content_type, CONTENT_TYPE_DID_RESOLUTION);
+        assert_eq!(body["didDocumentMetadata"]["deactivated"], true);
+    }
+}
@@ -0,0 +1,27 @@
+use registry_resolver::{RegistryResolver, RegistryResolverConfig};
+use ssi::resolver::{CachingResolver, MultiResolver};
+use std::sync::Arc;
+
+/// Serve the DID Resolution HTTP(S) binding for `did:knox`, resolving DIDs with the registry at `REGISTRY_URL`
+/// and listening on `LISTEN_ADDR`.
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let registry_url =
+        std::env::var("REGISTRY_URL").unwrap_or_else(|_| "http://localhost:50051".to_string());
+    let addr: std::net::SocketAddr = std::env::var("LISTEN_ADDR")
+        .unwrap_or_else(|_| "0.0.0.0:8080".to_string())
+        .parse()?;
+
+    // connect lazily, so that the endpoint starts and reports failures while the registry is unavailable
+    let config = RegistryResolverConfig {
+        lazy: true,
+        ..RegistryResolverConfig::new(registry_url)
+    };
+    let registry = RegistryResolver::from_config(config).await?;
+    let resolver = MultiResolver::new().with_resolver(CachingResolver::new(registry));
+
+    axum::Server::bind(&addr)
+        .serve(resolver_driver::router(Arc::new(resolver)).into_make_service())
+        .await?;
+    Ok(())
+}
@@ -6,6 +6,12 @@ pub enum Error {}
 #[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
 pub enum ErrorKind {
     Uncategorized,
+    InvalidKey,
+    KeyNotFound,
+    DuplicateKey,
+    DecryptionFailed,
+    UnsupportedSuite,
+    UnauthorizedRelation,
 }
 
 impl std::fmt::Display for ErrorKind {
@@ -35,6 +41,14 @@ impl SignatureError {
             source: None,
         }
     }
+
+    pub fn invalid_key(message: String) -> Self {
+        Self {
+            message,
+            kind: ErrorKind::InvalidKey,
+            source: None,
+        }
+    }
 }
 
 impl From<Box<dyn std::error::Error + Send + Sync + 'static>> for SignatureError {
@@ -0,0 +1,624 @@
+use crate::error::{ErrorKind, SignatureError};
+use crate::signer::{DIDSigner, DynDIDSigner, Ed25519DidSigner};
+use crate::suite::{Ed25519Signature, VerificationRelation};
+use chacha20poly1305::aead::{Aead, NewAead, Payload};
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use zeroize::Zeroizing;
+
+const FORMAT_VERSION: u32 = 1;
+const KDF_ALGORITHM: &str = "argon2id";
+const SALT_LENGTH: usize = 16;
+const NONCE_LENGTH: usize = 24;
+/// Associated data of the entry used to check the password when the store is opened.
+const PASSWORD_CHECK: &[u8] = b"keystore";
+
+/// Keeps the signing keys of DID verification methods, identified by the id of their verification method, e.g.
+/// `did:knox:z6Mk…#key-1`.
+pub trait KeyStore: Send + Sync {
+    /// Store the key of a new verification method. Replacing a key is done with `rotate`.
+    fn insert(
+        &self,
+        verification_method: &str,
+        signer: &Ed25519DidSigner,
+    ) -> Result<(), SignatureError>;
+
+    /// Replace the key of a verification method, the public key it replaces is kept in its history.
+    fn rotate(
+        &self,
+        verification_method: &str,
+        signer: &Ed25519DidSigner,
+    ) -> Result<(), SignatureError>;
+
+    /// The signer of the current key of a verification method, whose proofs name that verification method.
+    fn unlock(&self, verification_method: &str) -> Result<KeyStoreSigner, SignatureError>;
+
+    /// The verification methods with a key in the store, sorted.
+    fn list(&self) -> Result<Vec<String>, SignatureError>;
+
+    /// The keys a verification method has had, oldest first, the current key being the last one.
+    fn history(&self, verification_method: &str) -> Result<Vec<KeyVersion>, SignatureError>;
+
+    /// Remove a verification method and its history from the store.
+    fn delete(&self, verification_method: &str) -> Result<(), SignatureError>;
+}
+
+/// A key a verification method has had. Only the public key is kept once a key is retired.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KeyVersion {
+    pub public_key_multibase: String,
+    /// Seconds since the Unix epoch.
+    pub created: u64,
+    /// Seconds since the Unix epoch, unset for the current key.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retired: Option<u64>,
+}
+
+/// Signer of a key unlocked from a `KeyStore`. Its proofs name the verification method the key is stored under,
+/// for every relation, rather than the `did:knox` method an `Ed25519DidSigner` derives from its public key.
+pub struct KeyStoreSigner {
+    verification_method: String,
+    key: Ed25519DidSigner,
+}
+
+impl KeyStoreSigner {
+    pub fn new(verification_method: impl Into<String>, key: Ed25519DidSigner) -> Self {
+        return Self {
+            verification_method: verification_method.into(),
+            key,
+        };
+    }
+
+    /// The unlocked key.
+    pub fn key(&self) -> &Ed25519DidSigner {
+        return &self.key;
+    }
+}
+
+impl DIDSigner<Ed25519Signature> for KeyStoreSigner {
+    fn try_sign(&self, data: &[u8]) -> Result<Ed25519Signature, SignatureError> {
+        return DIDSigner::try_sign(&self.key, data);
+    }
+
+    fn get_proof_type(&self) -> String {
+        return DIDSigner::get_proof_type(&self.key);
+    }
+
+    fn get_verification_method(&self, _relation: VerificationRelation) -> String {
+        return self.verification_method.clone();
+    }
+
+    fn encode(&self, sig: Ed25519Signature) -> String {
+        return DIDSigner::encode(&self.key, sig);
+    }
+}
+
+impl DynDIDSigner for KeyStoreSigner {
+    fn proof_type(&self) -> String {
+        return DIDSigner::get_proof_type(self);
+    }
+
+    fn verification_method(&self, relation: VerificationRelation) -> String {
+        return DIDSigner::get_verification_method(self, relation);
+    }
+
+    fn sign_encoded(
+        &self,
+        data: &[u8],
+        relation: VerificationRelation,
+    ) -> Result<String, SignatureError> {
+        return DIDSigner::try_encoded_relational_sign(self, data, relation);
+    }
+}
+
+/// Cost of the Argon2id derivation of the encryption key from the password, the memory cost being in KiB.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KdfParams {
+    pub memory_cost: u32,
+    pub time_cost: u32,
+    pub parallelism: u32,
+}
+
+impl Default for KdfParams {
+    fn default() -> Self {
+        return Self {
+            memory_cost: 19456,
+            time_cost: 2,
+            parallelism: 1,
+        };
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Kdf {
+    algorithm: String,
+    salt: String,
+    #[serde(flatten)]
+    params: KdfParams,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Encrypted {
+    nonce: String,
+    ciphertext: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct KeyRecord {
+    /// The seed of the current key.
+    secret_key: Encrypted,
+    versions: Vec<KeyVersion>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct KeyStoreFile {
+    version: u32,
+    kdf: Kdf,
+    password_check: Encrypted,
+    keys: BTreeMap<String, KeyRecord>,
+}
+
+fn error(kind: ErrorKind, message: String) -> SignatureError {
+    return SignatureError {
+        message,
+        ..SignatureError::new(kind)
+    };
+}
+
+fn io_error(e: impl std::error::Error + Send + Sync + 'static) -> SignatureError {
+    return SignatureError {
+        message: e.to_string(),
+        source: Some(Box::new(e)),
+        ..SignatureError::new(ErrorKind::Uncategorized)
+    };
+}
+
+fn now() -> u64 {
+    return std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+}
+
+fn check_verification_method(verification_method: &str) -> Result<(), SignatureError> {
+    let valid = matches!(
+        verification_method.split_once('#'),
+        Some((did, fragment)) if did.starts_with("did:") && !fragment.is_empty()
+    );
+    if !valid {
+        return Err(error(
+            ErrorKind::InvalidKey,
+            format!("{verification_method} is not a verification method id"),
+        ));
+    }
+    return Ok(());
+}
+
+/// Key store keeping the keys in a JSON file encrypted with a password. The encryption key is derived from the
+/// password with Argon2id and every key is encrypted with XChaCha20-Poly1305, bound to the id of its
+/// verification method, so that entries cannot be swapped in the file.
+pub struct EncryptedFileKeyStore {
+    path: PathBuf,
+    key: Zeroizing<[u8; 32]>,
+    file: Mutex<KeyStoreFile>,
+}
+
+impl EncryptedFileKeyStore {
+    /// Open the key store at `path`, creating it if it does not exist.
+    pub fn open(path: impl AsRef<Path>, password: &str) -> Result<Self, SignatureError> {
+        return Self::open_with_params(path, password, KdfParams::default());
+    }
+
+    /// Open the key store at `path`, creating it with the given key derivation cost if it does not exist.
+    /// An existing store keeps the cost it was created with.
+    pub fn open_with_params(
+        path: impl AsRef<Path>,
+        password: &str,
+        params: KdfParams,
+    ) -> Result<Self, SignatureError> {
+        let path = path.as_ref().to_path_buf();
+        let contents = match std::fs::read(&path) {
+            Ok(contents) => Some(contents),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+            Err(e) => return Err(io_error(e)),
+        };
+
+        let file = match contents {
+            Some(contents) => {
+                serde_json::from_slice::<KeyStoreFile>(&contents).map_err(io_error)?
+            }
+            None => {
+                let mut salt = [0u8; SALT_LENGTH];
+                rand::thread_rng().fill_bytes(&mut salt);
+                let kdf = Kdf {
+                    algorithm: KDF_ALGORITHM.to_string(),
+                    salt: base64::encode(salt),
+                    params,
+                };
+                let key = derive_key(password, &kdf)?;
+                let store = Self {
+                    file: Mutex::new(KeyStoreFile {
+                        version: FORMAT_VERSION,
+                        password_check: encrypt(&key, PASSWORD_CHECK, &[])?,
+                        kdf,
+                        keys: BTreeMap::new(),
+                    }),
+                    path,
+                    key,
+                };
+                store.save(&store.file.lock().unwrap())?;
+                return Ok(store);
+            }
+        };
+
+        if file.version != FORMAT_VERSION || file.kdf.algorithm != KDF_ALGORITHM {
+            return Err(error(
+                ErrorKind::Uncategorized,
+                format!(
+                    "unsupported key store version {} with {}",
+                    file.version, file.kdf.algorithm
+                ),
+            ));
+        }
+        let key = derive_key(password, &file.kdf)?;
+        decrypt(&key, PASSWORD_CHECK, &file.password_check)?;
+        return Ok(Self {
+            path,
+            key,
+            file: Mutex::new(file),
+        });
+    }
+
+    /// Write the file to a temporary file first, so that a failed write does not lose the keys.
+    fn save(&self, file: &KeyStoreFile) -> Result<(), SignatureError> {
+        let contents = serde_json::to_vec_pretty(file).map_err(io_error)?;
+        let temporary = self.path.with_extension("tmp");
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        let mut writer = options.open(&temporary).map_err(io_error)?;
+        std::io::Write::write_all(&mut writer, &contents).map_err(io_error)?;
+        writer.sync_all().map_err(io_error)?;
+        std::fs::rename(&temporary, &self.path).map_err(io_error)?;
+        return Ok(());
+    }
+
+    fn new_version(signer: &Ed25519DidSigner) -> KeyVersion {
+        return KeyVersion {
+            public_key_multibase: signer.public_key_multibase(),
+            created: now(),
+            retired: None,
+        };
+    }
+
+    fn not_found(verification_method: &str) -> SignatureError {
+        return error(
+            ErrorKind::KeyNotFound,
+            format!("no key for {verification_method}"),
+        );
+    }
+}
+
+impl KeyStore for EncryptedFileKeyStore {
+    fn insert(
+        &self,
+        verification_method: &str,
+        signer: &Ed25519DidSigner,
+    ) -> Result<(), SignatureError> {
+        check_verification_method(verification_method)?;
+        let mut file = self.file.lock().unwrap();
+        if file.keys.contains_key(verification_method) {
+            return Err(error(
+                ErrorKind::DuplicateKey,
+                format!("{verification_method} already has a key"),
+            ));
+        }
+
+        let mut updated = file.clone();
+        updated.keys.insert(
+            verification_method.to_string(),
+            KeyRecord {
+                secret_key: encrypt(&self.key, verification_method.as_bytes(), &signer.seed())?,
+                versions: vec![Self::new_version(signer)],
+            },
+        );
+        self.save(&updated)?;
+        *file = updated;
+        return Ok(());
+    }
+
+    fn rotate(
+        &self,
+        verification_method: &str,
+        signer: &Ed25519DidSigner,
+    ) -> Result<(), SignatureError> {
+        let mut file = self.file.lock().unwrap();
+        let mut updated = file.clone();
+        let record = updated
+            .keys
+            .get_mut(verification_method)
+            .ok_or_else(|| Self::not_found(verification_method))?;
+
+        let retired = now();
+        for version in record.versions.iter_mut() {
+            version.retired.get_or_insert(retired);
+        }
+        record.versions.push(Self::new_version(signer));
+        record.secret_key = encrypt(&self.key, verification_method.as_bytes(), &signer.seed())?;
+        self.save(&updated)?;
+        *file = updated;
+        return Ok(());
+    }
+
+    fn unlock(&self, verification_method: &str) -> Result<KeyStoreSigner, SignatureError> {
+        let file = self.file.lock().unwrap();
+        let record = file
+            .keys
+            .get(verification_method)
+            .ok_or_else(|| Self::not_found(verification_method))?;
+        let seed = decrypt(
+            &self.key,
+            verification_method.as_bytes(),
+            &record.secret_key,
+        )?;
+        let seed: [u8; 32] = seed.as_slice().try_into().map_err(|_| {
+            error(
+                ErrorKind::InvalidKey,
+                format!("the key of {verification_method} is not an Ed25519 seed"),
+            )
+        })?;
+        return Ok(KeyStoreSigner::new(
+            verification_method,
+            Ed25519DidSigner::from_seed(seed),
+        ));
+    }
+
+    fn list(&self) -> Result<Vec<String>, SignatureError> {
+        return Ok(self.file.lock().unwrap().keys.keys().cloned().collect());
+    }
+
+    fn history(&self, verification_method: &str) -> Result<Vec<KeyVersion>, SignatureError> {
+        return self
+            .file
+            .lock()
+            .unwrap()
+            .keys
+            .get(verification_method)
+            .map(|record| record.versions.clone())
+            .ok_or_else(|| Self::not_found(verification_method));
+    }
+
+    fn delete(&self, verification_method: &str) -> Result<(), SignatureError> {
+        let mut file = self.file.lock().unwrap();
+        let mut updated = file.clone();
+        updated
+            .keys
+            .remove(verification_method)
+            .ok_or_else(|| Self::not_found(verification_method))?;
+        self.save(&updated)?;
+        *file = updated;
+        return Ok(());
+    }
+}
+
+fn derive_key(password: &str, kdf: &Kdf) -> Result<Zeroizing<[u8; 32]>, SignatureError> {
+    let salt = base64::decode(&kdf.salt).map_err(io_error)?;
+    let params = argon2::Params::new(
+        kdf.params.memory_cost,
+        kdf.params.time_cost,
+        kdf.params.parallelism,
+        Some(32),
+    )
+    .map_err(|e| {
+        error(
+            ErrorKind::Uncategorized,
+            format!("invalid KDF parameters: {e}"),
+        )
+    })?;
+    let mut key = Zeroizing::new([0u8; 32]);
+    argon2::Argon2::new(argon2::Algorithm::Argon2id, argon2::Version::V0x13, params)
+        .hash_password_into(password.as_bytes(), &salt, key.as_mut())
+        .map_err(|e| {
+            error(
+                ErrorKind::Uncategorized,
+                format!("key derivation failed: {e}"),
+            )
+        })?;
+    return Ok(key);
+}
+
+fn encrypt(
+    key: &[u8; 32],
+    associated_data: &[u8],
+    plaintext: &[u8],
+) -> Result<Encrypted, SignatureError> {
+    let mut nonce = [0u8; NONCE_LENGTH];
+    rand::thread_rng().fill_bytes(&mut nonce);
+    let ciphertext = XChaCha20Poly1305::new(Key::from_slice(key))
+        .encrypt(
+            XNonce::from_slice(&nonce),
+            Payload {
+                msg: plaintext,
+                aad: associated_data,
+            },
+        )
+        .map_err(|_| error(ErrorKind::Uncategorized, "encryption failed".to_string()))?;
+    return Ok(Encrypted {
+        nonce: base64::encode(nonce),
+        ciphertext: base64::encode(ciphertext),
+    });
+}
+
+/// Fails with `DecryptionFailed` when the password is wrong or the entry was tampered with.
+fn decrypt(
+    key: &[u8; 32],
+    associated_data: &[u8],
+    encrypted: &Encrypted,
+) -> Result<Zeroizing<Vec<u8>>, SignatureError> {
+    let failed = || {
+        error(
+            ErrorKind::DecryptionFailed,
+            "wrong password or corrupted key store".to_string(),
+        )
+    };
+    let nonce = base64::decode(&encrypted.nonce).map_err(|_| failed())?;
+    let ciphertext = base64::decode(&encrypted.ciphertext).map_err(|_| failed())?;
+    if nonce.len() != NONCE_LENGTH {
+        return Err(failed());
+    }
+    let plaintext = XChaCha20Poly1305::new(Key::from_slice(key))
+        .decrypt(
+            XNonce::from_slice(&nonce),
+            Payload {
+                msg: &ciphertext,
+                aad: associated_data,
+            },
+        )
+        .map_err(|_| failed())?;
+    return Ok(Zeroizing::new(plaintext));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{EncryptedFileKeyStore, KdfParams, KeyStore};
+    use crate::error::ErrorKind;
+    use crate::signer::{DIDSigner, Ed25519DidSigner};
+    use crate::suite::VerificationRelation;
+    use crate::verifier::{DIDVerifier, Ed25519DidVerifier};
+
+    const KEY_1: &str = "did:knox:z6MkfFmsob7fC3MmqU1JVfdBnMbnAw7xm1mrEtPvAoojLcRh#key-1";
+    const KEY_2: &str = "did:knox:z6MkfFmsob7fC3MmqU1JVfdBnMbnAw7xm1mrEtPvAoojLcRh#key-2";
+
+    // Cheap parameters, the defaults take too long for tests.
+    const PARAMS: KdfParams = KdfParams {
+        memory_cost: 64,
+        time_cost: 1,
+        parallelism: 1,
+    };
+
+    fn open(path: &std::path::Path, password: &str) -> Result<EncryptedFileKeyStore, ErrorKind> {
+        return EncryptedFileKeyStore::open_with_params(path, password, PARAMS).map_err(|e| e.kind);
+    }
+
+    #[test]
+    fn test_insert_and_unlock() {
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("keys.json");
+        let signer = Ed25519DidSigner::new();
+        let store = open(&path, "password").unwrap();
+        store.insert(KEY_1, &signer).unwrap();
+        store.insert(KEY_2, &Ed25519DidSigner::new()).unwrap();
+
+        let reopened = open(&path, "password").unwrap();
+        assert_eq!(reopened.list().unwrap(), vec![KEY_1, KEY_2]);
+        let unlocked = reopened.unlock(KEY_1).unwrap();
+        assert_eq!(unlocked.key().seed(), signer.seed());
+        assert_eq!(
+            unlocked.get_verification_method(VerificationRelation::AssertionMethod),
+            KEY_1
+        );
+        let signature = unlocked.sign(b"message");
+        assert!(Ed25519DidVerifier::from(&signer)
+            .verify(b"message", &signature)
+            .is_ok());
+
+        // The seed is not written in clear.
+        let contents = std::fs::read_to_string(&path).unwrap();
+        assert!(!contents.contains(&base64::encode(signer.seed())));
+    }
+
+    #[rstest::rstest]
+    #[case::wrong_password(|path: &std::path::Path| {
+        assert_eq!(open(path, "wrong").err(), Some(ErrorKind::DecryptionFailed));
+    })]
+    #[case::swapped_entries(|path: &std::path::Path| {
+        let mut file: serde_json::Value =
+            serde_json::from_slice(&std::fs::read(path).unwrap()).unwrap();
+        let key_1 = file["keys"][KEY_1]["secretKey"].clone();
+        file["keys"][KEY_1]["secretKey"] = file["keys"][KEY_2]["secretKey"].clone();
+        file["keys"][KEY_2]["secretKey"] = key_1;
+        std::fs::write(path, serde_json::to_vec(&file).unwrap()).unwrap();
+
+        let res = open(path, "password").unwrap().unlock(KEY_1);
+        assert_eq!(res.err().unwrap().kind, ErrorKind::DecryptionFailed);
+    })]
+    fn test_tampering(#[case] check: fn(&std::path::Path)) {
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("keys.json");
+        let store = open(&path, "password").unwrap();
+        store.insert(KEY_1, &Ed25519DidSigner::new()).unwrap();
+        store.insert(KEY_2, &Ed25519DidSigner::new()).unwrap();
+        check(&path);
+    }
+
+    #[test]
+    fn test_rotate() {
+        let directory = tempfile::tempdir().unwrap();
+        let store = open(&directory.path().join("keys.json"), "password").unwrap();
+        let first = Ed25519DidSigner::new();
+        let second = Ed25519DidSigner::new();
+        store.insert(KEY_1, &first).unwrap();
+
+        let res = store.insert(KEY_1, &second);
+        assert_eq!(res.err().unwrap().kind, ErrorKind::DuplicateKey);
+        store.rotate(KEY_1, &second).unwrap();
+        assert_eq!(store.unlock(KEY_1).unwrap().key().seed(), second.seed());
+
+        let history = store.history(KEY_1).unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(
+            history[0].public_key_multibase,
+            first.public_key_multibase()
+        );
+        assert!(history[0].retired.is_some());
+        assert_eq!(
+            history[1].public_key_multibase,
+            second.public_key_multibase()
+        );
+        assert_eq!(history[1].retired, None);
+
+        let res = store.rotate(KEY_2, &second);
+        assert_eq!(res.err().unwrap().kind, ErrorKind::KeyNotFound);
+    }
+
+    #[test]
+    fn test_delete() {
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("keys.json");
+        let store = open(&path, "password").unwrap();
+        store.insert(KEY_1, &Ed25519DidSigner::new()).unwrap();
+        store.delete(KEY_1).unwrap();
+
+        let reopened = open(&path, "password").unwrap();
+        assert!(reopened.list().unwrap().is_empty());
+        assert_eq!(
+            reopened.unlock(KEY_1).err().unwrap().kind,
+            ErrorKind::KeyNotFound
+        );
+        assert_eq!(
+            reopened.delete(KEY_1).err().unwrap().kind,
+            ErrorKind::KeyNotFound
+        );
+    }
+
+    #[rstest::rstest]
+    #[case::no_fragment("did:knox:z6MkfFmsob7fC3MmqU1JVfdBnMbnAw7xm1mrEtPvAoojLcRh")]
+    #[case::not_a_did("key-1#key-1")]
+    #[case::empty_fragment("did:knox:z6MkfFmsob7fC3MmqU1JVfdBnMbnAw7xm1mrEtPvAoojLcRh#")]
+    fn test_invalid_verification_method(#[case] verification_method: &str) {
+        let directory = tempfile::tempdir().unwrap();
+        let store = open(&directory.path().join("keys.json"), "password").unwrap();
+        let res = store.insert(verification_method, &Ed25519DidSigner::new());
+        assert_eq!(res.err().unwrap().kind, ErrorKind::InvalidKey);
+    }
+}
@@ -1,4 +1,6 @@
 pub mod error;
+pub mod key;
+pub mod keystore;
 pub mod signer;
 pub mod suite;
 pub mod verifier;
@@ -1,5 +1,7 @@
 use crate::error::SignatureError;
-use crate::suite::{Ed25519Signature, Signature, VerificationRelation};
+use crate::key::{Ed25519Jwk, ED25519_PRIV_MULTICODEC, ED25519_PUB_MULTICODEC};
+use crate::suite::{Ed25519Signature, Signature, VerificationRelation, VERIFICATION_RELATIONS};
+use std::collections::HashMap;
 
 pub trait DIDSigner<S>
 where
@@ -19,12 +21,110 @@ where
         return Ok(self.encode(signature));
     }
 
+    /// Sign with the key of `get_verification_method(relation)`. Signers holding a single key use it for every relation.
+    fn try_relational_sign(
+        &self,
+        msg: &[u8],
+        _relation: VerificationRelation,
+    ) -> Result<S, SignatureError> {
+        return self.try_sign(msg);
+    }
+
+    fn try_encoded_relational_sign(
+        &self,
+        data: &[u8],
+        relation: VerificationRelation,
+    ) -> Result<String, SignatureError> {
+        let signature = self.try_relational_sign(data, relation)?;
+        return Ok(self.encode(signature));
+    }
+
     fn try_sign(&self, msg: &[u8]) -> Result<S, SignatureError>;
     fn get_proof_type(&self) -> String;
     fn get_verification_method(&self, relation: VerificationRelation) -> String;
     fn encode(&self, sig: S) -> String;
 }
 
+/// Object-safe signer, for code that picks the suite at runtime, e.g. from a `SuiteRegistry`.
+/// The signature is returned in the encoding of the suite's proof values.
+pub trait DynDIDSigner: Send + Sync {
+    fn proof_type(&self) -> String;
+    fn verification_method(&self, relation: VerificationRelation) -> String;
+    /// Sign with the key of `verification_method(relation)`.
+    fn sign_encoded(
+        &self,
+        data: &[u8],
+        relation: VerificationRelation,
+    ) -> Result<String, SignatureError>;
+}
+
+/// Signer whose private key is not held in process memory, e.g. a key kept by a KMS or a signing service.
+/// Every `DIDSigner` is also an `AsyncDIDSigner`, so that code accepting an `AsyncDIDSigner` works with local keys too.
+#[async_trait::async_trait]
+pub trait AsyncDIDSigner<S>: Send + Sync
+where
+    S: Signature + Send,
+{
+    async fn try_encoded_sign(&self, data: &[u8]) -> Result<String, SignatureError> {
+        let signature = self.try_sign(data).await?;
+        return Ok(self.encode(signature));
+    }
+
+    /// Sign with the key of `get_verification_method(relation)`. Signers holding a single key use it for every relation.
+    async fn try_relational_sign(
+        &self,
+        msg: &[u8],
+        _relation: VerificationRelation,
+    ) -> Result<S, SignatureError> {
+        self.try_sign(msg).await
+    }
+
+    async fn try_encoded_relational_sign(
+        &self,
+        data: &[u8],
+        relation: VerificationRelation,
+    ) -> Result<String, SignatureError> {
+        let signature = self.try_relational_sign(data, relation).await?;
+        return Ok(self.encode(signature));
+    }
+
+    async fn try_sign(&self, msg: &[u8]) -> Result<S, SignatureError>;
+    fn get_proof_type(&self) -> String;
+    fn get_verification_method(&self, relation: VerificationRelation) -> String;
+    fn encode(&self, sig: S) -> String;
+}
+
+#[async_trait::async_trait]
+impl<S, T> AsyncDIDSigner<S> for T
+where
+    S: Signature + Send,
+    T: DIDSigner<S> + Send + Sync,
+{
+    async fn try_sign(&self, msg: &[u8]) -> Result<S, SignatureError> {
+        DIDSigner::try_sign(self, msg)
+    }
+
+    async fn try_relational_sign(
+        &self,
+        msg: &[u8],
+        relation: VerificationRelation,
+    ) -> Result<S, SignatureError> {
+        DIDSigner::try_relational_sign(self, msg, relation)
+    }
+
+    fn get_proof_type(&self) -> String {
+        return DIDSigner::get_proof_type(self);
+    }
+
+    fn get_verification_method(&self, relation: VerificationRelation) -> String {
+        return DIDSigner::get_verification_method(self, relation);
+    }
+
+    fn encode(&self, sig: S) -> String {
+        return DIDSigner::encode(self, sig);
+    }
+}
+
 pub struct Ed25519DidSigner {
     private_key: ed25519_zebra::SigningKey,
     pub(crate) public_key: ed25519_zebra::VerificationKey,
@@ -32,13 +132,105 @@ pub struct Ed25519DidSigner {
 
 impl Ed25519DidSigner {
     pub fn new() -> Self {
-        let sk = ed25519_zebra::SigningKey::new(rand::thread_rng());
+        return Self::from_signing_key(ed25519_zebra::SigningKey::new(rand::thread_rng()));
+    }
 
+    fn from_signing_key(sk: ed25519_zebra::SigningKey) -> Self {
         return Self {
             private_key: sk,
             public_key: ed25519_zebra::VerificationKey::from(&sk),
         };
     }
+
+    /// Restore a signer from the 32-byte seed of its private key.
+    pub fn from_seed(seed: [u8; 32]) -> Self {
+        return Self::from_signing_key(ed25519_zebra::SigningKey::from(seed));
+    }
+
+    /// The 32-byte seed of the private key.
+    pub fn seed(&self) -> [u8; 32] {
+        return self.private_key.into();
+    }
+
+    /// Restore a signer from a multibase `ed25519-priv` private key, as exported by `secret_key_multibase`.
+    pub fn from_multibase(secret_key_multibase: &str) -> Result<Self, SignatureError> {
+        let seed = crate::key::decode_multibase(ED25519_PRIV_MULTICODEC, secret_key_multibase)?;
+        return Ok(Self::from_seed(seed));
+    }
+
+    /// The private key as a multibase `ed25519-priv` key, as used by the `secretKeyMultibase` of a `Multikey`.
+    pub fn secret_key_multibase(&self) -> String {
+        return crate::key::encode_multibase(ED25519_PRIV_MULTICODEC, self.private_key.as_ref());
+    }
+
+    /// The public key as a multibase `ed25519-pub` key (`z6Mk…`), as used by the `publicKeyMultibase` of a
+    /// `Multikey`.
+    pub fn public_key_multibase(&self) -> String {
+        return crate::key::encode_multibase(ED25519_PUB_MULTICODEC, self.public_key.as_ref());
+    }
+
+    /// Restore a signer from a private JWK. The public key `x`, when it does not match the private key `d`,
+    /// is rejected.
+    pub fn from_jwk(jwk: &Ed25519Jwk) -> Result<Self, SignatureError> {
+        let seed = jwk
+            .seed()?
+            .ok_or_else(|| SignatureError::invalid_key("the JWK has no private key".to_string()))?;
+        let signer = Self::from_seed(seed);
+        if jwk.public_key()? != <[u8; 32]>::from(signer.public_key) {
+            return Err(SignatureError::invalid_key(
+                "the public key of the JWK does not match its private key".to_string(),
+            ));
+        }
+        return Ok(signer);
+    }
+
+    /// The private key as a JWK, including the public key.
+    pub fn private_key_jwk(&self) -> Ed25519Jwk {
+        return Ed25519Jwk::new(self.public_key.as_ref(), Some(self.private_key.as_ref()));
+    }
+
+    /// The public key as a JWK, as used by `publicKeyJwk`.
+    pub fn public_key_jwk(&self) -> Ed25519Jwk {
+        return Ed25519Jwk::new(self.public_key.as_ref(), None);
+    }
+
+    /// Restore a signer from a PKCS#8 `PRIVATE KEY` PEM document.
+    pub fn from_pkcs8_pem(pem: &str) -> Result<Self, SignatureError> {
+        return Ok(Self::from_seed(crate::key::decode_pkcs8_pem(pem)?));
+    }
+
+    /// The private key as a PKCS#8 `PRIVATE KEY` PEM document.
+    pub fn to_pkcs8_pem(&self) -> String {
+        return crate::key::encode_pkcs8_pem(self.private_key.as_ref());
+    }
+
+    /// The public key as a SubjectPublicKeyInfo `PUBLIC KEY` PEM document.
+    pub fn public_key_spki_pem(&self) -> String {
+        return crate::key::encode_spki_pem(self.public_key.as_ref());
+    }
+
+    /// The `did:key` identifier of the public key, using the `ed25519-pub` multicodec prefix.
+    pub fn to_did_key(&self) -> String {
+        return format!("did:key:{}", self.public_key_multibase());
+    }
+}
+
+impl DynDIDSigner for Ed25519DidSigner {
+    fn proof_type(&self) -> String {
+        return DIDSigner::get_proof_type(self);
+    }
+
+    fn verification_method(&self, relation: VerificationRelation) -> String {
+        return DIDSigner::get_verification_method(self, relation);
+    }
+
+    fn sign_encoded(
+        &self,
+        data: &[u8],
+        relation: VerificationRelation,
+    ) -> Result<String, SignatureError> {
+        return DIDSigner::try_encoded_relational_sign(self, data, relation);
+    }
 }
 
 impl DIDSigner<Ed25519Signature> for Ed25519DidSigner {
@@ -60,3 +252,151 @@ impl DIDSigner<Ed25519Signature> for Ed25519DidSigner {
         multibase::encode(multibase::Base::Base58Btc, sig)
     }
 }
+
+/// Signer holding a distinct Ed25519 key for each verification relationship of a DID, so that, e.g., its
+/// `authentication` key cannot issue credentials. The verification method of each key is `{did}#{publicKeyMultibase}`.
+/// Signing without a relation, e.g. with `sign` or `encoded_sign`, uses the key of `DEFAULT_RELATION`.
+pub struct Ed25519RelationalSigner {
+    did: String,
+    keys: HashMap<VerificationRelation, Ed25519DidSigner>,
+}
+
+impl Ed25519RelationalSigner {
+    /// The relation whose key signs when no relation is given, issuing credentials being the most common use of a key.
+    pub const DEFAULT_RELATION: VerificationRelation = VerificationRelation::AssertionMethod;
+
+    /// Generate a new key for each relationship of `did`.
+    pub fn generate(did: impl Into<String>) -> Self {
+        return Self {
+            did: did.into(),
+            keys: VERIFICATION_RELATIONS
+                .iter()
+                .map(|relation| (*relation, Ed25519DidSigner::new()))
+                .collect(),
+        };
+    }
+
+    /// Use `signer` for the proofs of `relation`.
+    pub fn with_key(mut self, relation: VerificationRelation, signer: Ed25519DidSigner) -> Self {
+        self.keys.insert(relation, signer);
+        return self;
+    }
+
+    pub fn did(&self) -> &str {
+        return &self.did;
+    }
+
+    /// The key used for `relation`.
+    pub fn key(&self, relation: VerificationRelation) -> &Ed25519DidSigner {
+        return &self.keys[&relation];
+    }
+}
+
+impl DIDSigner<Ed25519Signature> for Ed25519RelationalSigner {
+    /// Sign with the key of `DEFAULT_RELATION`, use `try_relational_sign` for the other relations.
+    fn try_sign(&self, data: &[u8]) -> Result<Ed25519Signature, SignatureError> {
+        return DIDSigner::try_sign(self.key(Self::DEFAULT_RELATION), data);
+    }
+
+    fn try_relational_sign(
+        &self,
+        data: &[u8],
+        relation: VerificationRelation,
+    ) -> Result<Ed25519Signature, SignatureError> {
+        return DIDSigner::try_sign(self.key(relation), data);
+    }
+
+    fn get_proof_type(&self) -> String {
+        return crate::suite::PROOF_TYPE.to_string();
+    }
+
+    fn get_verification_method(&self, relation: VerificationRelation) -> String {
+        return format!("{}#{}", self.did, self.key(relation).public_key_multibase());
+    }
+
+    fn encode(&self, sig: Ed25519Signature) -> String {
+        multibase::encode(multibase::Base::Base58Btc, sig)
+    }
+}
+
+impl DynDIDSigner for Ed25519RelationalSigner {
+    fn proof_type(&self) -> String {
+        return DIDSigner::get_proof_type(self);
+    }
+
+    fn verification_method(&self, relation: VerificationRelation) -> String {
+        return DIDSigner::get_verification_method(self, relation);
+    }
+
+    fn sign_encoded(
+        &self,
+        data: &[u8],
+        relation: VerificationRelation,
+    ) -> Result<String, SignatureError> {
+        return DIDSigner::try_encoded_relational_sign(self, data, relation);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DIDSigner, Ed25519DidSigner, Ed25519RelationalSigner};
+    use crate::error::ErrorKind;
+    use crate::suite::{VerificationRelation, VERIFICATION_RELATIONS};
+    use crate::verifier::{DIDVerifier, Ed25519DidVerifier};
+
+    const DID: &str = "did:knox:z6MkfFmsob7fC3MmqU1JVfdBnMbnAw7xm1mrEtPvAoojLcRh";
+
+    #[test]
+    fn test_relational_signer() {
+        let assertion_key = Ed25519DidSigner::from_seed([1u8; 32]);
+        let signer = Ed25519RelationalSigner::generate(DID)
+            .with_key(VerificationRelation::AssertionMethod, assertion_key);
+        assert_eq!(
+            signer.get_verification_method(VerificationRelation::AssertionMethod),
+            format!(
+                "{DID}#{}",
+                Ed25519DidSigner::from_seed([1u8; 32]).public_key_multibase()
+            )
+        );
+
+        let mut methods: Vec<String> = VERIFICATION_RELATIONS
+            .iter()
+            .map(|relation| signer.get_verification_method(*relation))
+            .collect();
+        methods.sort();
+        methods.dedup();
+        assert_eq!(methods.len(), VERIFICATION_RELATIONS.len());
+
+        for relation in VERIFICATION_RELATIONS {
+            let encoded = signer
+                .try_encoded_relational_sign(b"message", relation)
+                .unwrap();
+            let verifier = Ed25519DidVerifier::from(signer.key(relation));
+            assert!(verifier
+                .decoded_relational_verify(b"message", encoded, relation)
+                .is_ok());
+        }
+
+        // signing without a relation uses the assertionMethod key
+        let encoded = signer.encoded_sign(b"message");
+        let verifier = Ed25519DidVerifier::from(signer.key(VerificationRelation::AssertionMethod));
+        assert!(verifier.decoded_verify(b"message", encoded).is_ok());
+    }
+
+    #[rstest::rstest]
+    #[case::allowed(VerificationRelation::Authentication, None)]
+    #[case::not_allowed(
+        VerificationRelation::AssertionMethod,
+        Some(ErrorKind::UnauthorizedRelation)
+    )]
+    fn test_verifier_for_relations(
+        #[case] relation: VerificationRelation,
+        #[case] expect_error: Option<ErrorKind>,
+    ) {
+        let signer = Ed25519DidSigner::new();
+        let verifier = Ed25519DidVerifier::from(&signer)
+            .for_relations(&[VerificationRelation::Authentication]);
+        let res = verifier.relational_verify(b"message", &signer.sign(b"message"), relation);
+        assert_eq!(res.err().map(|e| e.kind), expect_error);
+    }
+}
@@ -8,6 +8,13 @@ pub enum VerificationRelation {
     CapabilityDelegation,
 }
 
+pub const VERIFICATION_RELATIONS: [VerificationRelation; 4] = [
+    VerificationRelation::AssertionMethod,
+    VerificationRelation::Authentication,
+    VerificationRelation::CapabilityInvocation,
+    VerificationRelation::CapabilityDelegation,
+];
+
 pub const PROOF_TYPE: &str = "Ed25519Signature2018";
 
 #[derive(Debug, PartialEq, Clone)]
@@ -40,6 +47,23 @@ impl std::fmt::Display for VerificationRelation {
     }
 }
 
+impl std::str::FromStr for VerificationRelation {
+    type Err = SignatureError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "assertionMethod" => Ok(VerificationRelation::AssertionMethod),
+            "authentication" => Ok(VerificationRelation::Authentication),
+            "capabilityInvocation" => Ok(VerificationRelation::CapabilityInvocation),
+            "capabilityDelegation" => Ok(VerificationRelation::CapabilityDelegation),
+            _ => Err(SignatureError {
+                message: format!("unknown verification relation {s}"),
+                ..SignatureError::new(crate::error::ErrorKind::Uncategorized)
+            }),
+        }
+    }
+}
+
 impl Signature for Ed25519Signature {
     fn from_bytes(bytes: &[u8]) -> Result<Self, SignatureError> {
         Ok(Ed25519Signature(bytes.to_vec()))
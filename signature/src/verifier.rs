@@ -24,20 +24,89 @@ where
         relation: VerificationRelation,
     ) -> Result<(), SignatureError>;
     fn decode(&self, encoded_sig: String) -> Result<S, SignatureError>;
+    /// The key signatures are verified with, so that callers can check it is the key of the expected verification method.
+    fn public_key(&self) -> Vec<u8>;
+}
+
+/// Object-safe verifier, for code that only learns the suite at runtime from the proof, e.g. through a `SuiteRegistry`.
+pub trait DynDIDVerifier: Send + Sync {
+    fn proof_type(&self) -> String;
+    /// Verify a signature in the encoding of the suite's proof values for the given relation.
+    fn verify_encoded(
+        &self,
+        msg: &[u8],
+        encoded_sig: &str,
+        relation: VerificationRelation,
+    ) -> Result<(), SignatureError>;
 }
 
 pub struct Ed25519DidVerifier {
     public_key: ed25519_zebra::VerificationKey,
+    /// Relations the key may be used for, all of them unless restricted with `for_relations`.
+    relations: Vec<VerificationRelation>,
 }
 
 impl From<&crate::signer::Ed25519DidSigner> for Ed25519DidVerifier {
     fn from(signer: &crate::signer::Ed25519DidSigner) -> Self {
         Self {
             public_key: signer.public_key,
+            relations: crate::suite::VERIFICATION_RELATIONS.to_vec(),
         }
     }
 }
 
+/// Build a verifier from the 32 bytes of an Ed25519 public key, e.g. one decoded from a DID document.
+impl TryFrom<&[u8]> for Ed25519DidVerifier {
+    type Error = SignatureError;
+
+    fn try_from(public_key: &[u8]) -> Result<Self, Self::Error> {
+        let public_key = ed25519_zebra::VerificationKey::try_from(public_key)?;
+        return Ok(Self {
+            public_key,
+            relations: crate::suite::VERIFICATION_RELATIONS.to_vec(),
+        });
+    }
+}
+
+impl Ed25519DidVerifier {
+    /// Only accept `relational_verify` for `relations`, e.g. the relationships the DID document lists the key under.
+    pub fn for_relations(mut self, relations: &[VerificationRelation]) -> Self {
+        self.relations = relations.to_vec();
+        return self;
+    }
+
+    /// Build a verifier from a multibase `ed25519-pub` key, e.g. the `publicKeyMultibase` of a `Multikey`.
+    pub fn from_public_key_multibase(encoded: &str) -> Result<Self, SignatureError> {
+        let public_key = crate::key::decode_multibase(crate::key::ED25519_PUB_MULTICODEC, encoded)?;
+        return Self::try_from(&public_key[..]);
+    }
+
+    /// Build a verifier from a `publicKeyJwk`. The private key `d`, if any, is ignored.
+    pub fn from_jwk(jwk: &crate::key::Ed25519Jwk) -> Result<Self, SignatureError> {
+        return Self::try_from(&jwk.public_key()?[..]);
+    }
+
+    /// Build a verifier from a SubjectPublicKeyInfo `PUBLIC KEY` PEM document.
+    pub fn from_spki_pem(pem: &str) -> Result<Self, SignatureError> {
+        return Self::try_from(&crate::key::decode_spki_pem(pem)?[..]);
+    }
+}
+
+impl DynDIDVerifier for Ed25519DidVerifier {
+    fn proof_type(&self) -> String {
+        return crate::suite::PROOF_TYPE.to_string();
+    }
+
+    fn verify_encoded(
+        &self,
+        msg: &[u8],
+        encoded_sig: &str,
+        relation: VerificationRelation,
+    ) -> Result<(), SignatureError> {
+        return self.decoded_relational_verify(msg, encoded_sig.to_string(), relation);
+    }
+}
+
 impl DIDVerifier<Ed25519Signature> for Ed25519DidVerifier {
     fn verify(&self, msg: &[u8], sig: &Ed25519Signature) -> Result<(), SignatureError> {
         let sig_bytes: [u8; 64] = sig
@@ -55,13 +124,21 @@ impl DIDVerifier<Ed25519Signature> for Ed25519DidVerifier {
 
         match res {
             Ok(sig) => {
-                let sig_bytes: [u8; 64] = sig.1.as_slice().try_into().unwrap();
+                let sig_bytes: [u8; 64] = sig
+                    .1
+                    .as_slice()
+                    .try_into()
+                    .map_err(|_| SignatureError::new(ErrorKind::Uncategorized))?;
                 return Ed25519Signature::from_bytes(&sig_bytes).map_err(SignatureError::from);
             }
             _ => Err(SignatureError::new(ErrorKind::Uncategorized)),
         }
     }
 
+    fn public_key(&self) -> Vec<u8> {
+        return self.public_key.as_ref().to_vec();
+    }
+
     fn decoded_relational_verify(
         &self,
         msg: &[u8],
@@ -84,24 +161,183 @@ impl DIDVerifier<Ed25519Signature> for Ed25519DidVerifier {
             .try_into()
             .map_err(|_| SignatureError::new(ErrorKind::Uncategorized))?;
 
-        let sig = ed25519_zebra::Signature::from(sig_bytes);
-        match relation {
-            VerificationRelation::AssertionMethod => self
-                .public_key
-                .verify(&sig, msg)
-                .map_err(SignatureError::from),
-            VerificationRelation::Authentication => self
-                .public_key
-                .verify(&sig, msg)
-                .map_err(SignatureError::from),
-            VerificationRelation::CapabilityInvocation => self
-                .public_key
-                .verify(&sig, msg)
-                .map_err(SignatureError::from),
-            VerificationRelation::CapabilityDelegation => self
-                .public_key
-                .verify(&sig, msg)
-                .map_err(SignatureError::from),
+        if !self.relations.contains(&relation) {
+            return Err(SignatureError {
+                message: format!("the key is not authorized for {relation}"),
+                ..SignatureError::new(ErrorKind::UnauthorizedRelation)
+            });
+        }
+
+        self.public_key
+            .verify(&ed25519_zebra::Signature::from(sig_bytes), msg)
+            .map_err(SignatureError::from)
+    }
+}
+
+/// Verifies many Ed25519 signatures at once, which is much faster than verifying them one by one, e.g. for the
+/// proofs of the credentials of a presentation. When the batch fails, each signature is checked on its own to find
+/// the failing ones.
+#[derive(Default)]
+pub struct Ed25519BatchVerifier {
+    items: Vec<ed25519_zebra::batch::Item>,
+}
+
+impl Ed25519BatchVerifier {
+    pub fn new() -> Self {
+        return Self::default();
+    }
+
+    /// Queue the signature of `msg` by the key of `verifier` for `relation` and return its index in the batch.
+    /// Signatures that are malformed or made for a relation the key is not authorized for are rejected right away.
+    pub fn queue(
+        &mut self,
+        verifier: &Ed25519DidVerifier,
+        msg: &[u8],
+        sig: &Ed25519Signature,
+        relation: VerificationRelation,
+    ) -> Result<usize, SignatureError> {
+        if !verifier.relations.contains(&relation) {
+            return Err(SignatureError {
+                message: format!("the key is not authorized for {relation}"),
+                ..SignatureError::new(ErrorKind::UnauthorizedRelation)
+            });
+        }
+        let sig_bytes: [u8; 64] = sig
+            .0
+            .as_slice()
+            .try_into()
+            .map_err(|_| SignatureError::new(ErrorKind::Uncategorized))?;
+
+        self.items.push(ed25519_zebra::batch::Item::from((
+            ed25519_zebra::VerificationKeyBytes::from(verifier.public_key),
+            ed25519_zebra::Signature::from(sig_bytes),
+            msg,
+        )));
+        return Ok(self.items.len() - 1);
+    }
+
+    /// Queue a multibase encoded signature, see `queue`.
+    pub fn queue_encoded(
+        &mut self,
+        verifier: &Ed25519DidVerifier,
+        msg: &[u8],
+        encoded_sig: &str,
+        relation: VerificationRelation,
+    ) -> Result<usize, SignatureError> {
+        let sig = verifier.decode(encoded_sig.to_string())?;
+        return self.queue(verifier, msg, &sig, relation);
+    }
+
+    pub fn len(&self) -> usize {
+        return self.items.len();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.items.is_empty();
+    }
+
+    /// Verify all the queued signatures, or return the index and error of every invalid one.
+    pub fn verify(self) -> Result<(), Vec<(usize, SignatureError)>> {
+        let mut batch = ed25519_zebra::batch::Verifier::new();
+        for item in self.items.iter().cloned() {
+            batch.queue(item);
         }
+        if batch.verify(rand::thread_rng()).is_ok() {
+            return Ok(());
+        }
+
+        let failures: Vec<(usize, SignatureError)> = self
+            .items
+            .into_iter()
+            .enumerate()
+            .filter_map(|(index, item)| {
+                item.verify_single()
+                    .err()
+                    .map(|e| (index, SignatureError::from(e)))
+            })
+            .collect();
+        return match failures.is_empty() {
+            true => Ok(()),
+            false => Err(failures),
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Ed25519BatchVerifier, Ed25519DidVerifier};
+    use crate::error::ErrorKind;
+    use crate::signer::{DIDSigner, Ed25519DidSigner};
+    use crate::suite::VerificationRelation;
+
+    #[rstest::rstest]
+    #[case::empty(0, vec![])]
+    #[case::valid(5, vec![])]
+    #[case::one_invalid(5, vec![3])]
+    #[case::several_invalid(5, vec![0, 4])]
+    fn test_batch_verify(#[case] count: usize, #[case] invalid: Vec<usize>) {
+        let signers: Vec<Ed25519DidSigner> = (0..count).map(|_| Ed25519DidSigner::new()).collect();
+        let verifiers: Vec<Ed25519DidVerifier> =
+            signers.iter().map(Ed25519DidVerifier::from).collect();
+        let messages: Vec<Vec<u8>> = (0..count)
+            .map(|i| format!("message {i}").into_bytes())
+            .collect();
+
+        let mut batch = Ed25519BatchVerifier::new();
+        for i in 0..count {
+            // An invalid signature is a signature of another message.
+            let signed = match invalid.contains(&i) {
+                true => b"other message".to_vec(),
+                false => messages[i].clone(),
+            };
+            let encoded = signers[i].encoded_sign(&signed);
+            let index = batch
+                .queue_encoded(
+                    &verifiers[i],
+                    &messages[i],
+                    &encoded,
+                    VerificationRelation::AssertionMethod,
+                )
+                .unwrap();
+            assert_eq!(index, i);
+        }
+        assert_eq!(batch.len(), count);
+
+        match batch.verify() {
+            Ok(()) => assert!(invalid.is_empty()),
+            Err(failures) => {
+                let indices: Vec<usize> = failures.iter().map(|(index, _)| *index).collect();
+                assert_eq!(indices, invalid);
+            }
+        }
+    }
+
+    #[rstest::rstest]
+    #[case::unauthorized_relation(
+        None,
+        VerificationRelation::AssertionMethod,
+        ErrorKind::UnauthorizedRelation
+    )]
+    #[case::malformed_signature(
+        Some("z3FXQ"),
+        VerificationRelation::Authentication,
+        ErrorKind::Uncategorized
+    )]
+    fn test_batch_queue_errors(
+        #[case] encoded_sig: Option<&str>,
+        #[case] relation: VerificationRelation,
+        #[case] expect_kind: ErrorKind,
+    ) {
+        let signer = Ed25519DidSigner::new();
+        let verifier = Ed25519DidVerifier::from(&signer)
+            .for_relations(&[VerificationRelation::Authentication]);
+        let encoded_sig = encoded_sig
+            .map(str::to_string)
+            .unwrap_or_else(|| signer.encoded_sign(b"message"));
+
+        let mut batch = Ed25519BatchVerifier::new();
+        let res = batch.queue_encoded(&verifier, b"message", &encoded_sig, relation);
+        assert_eq!(res.err().unwrap().kind, expect_kind);
+        assert!(batch.is_empty());
     }
 }
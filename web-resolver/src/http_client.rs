@@ -0,0 +1,59 @@
+use mockall::*;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct HttpResponse {
+    pub status: u16,
+    pub body: Vec<u8>,
+}
+
+/// Fetches DID documents over HTTPS.
+/// The resolver only depends on this trait so that tests can substitute a local stand-in for the web.
+#[automock]
+#[async_trait::async_trait]
+pub trait HttpClient {
+    async fn get(
+        &self,
+        url: String,
+    ) -> Result<HttpResponse, Box<dyn std::error::Error + Send + Sync>>;
+}
+
+pub struct ReqwestClient {
+    inner: reqwest::Client,
+}
+
+impl Default for ReqwestClient {
+    fn default() -> Self {
+        return Self::new();
+    }
+}
+
+impl ReqwestClient {
+    pub fn new() -> Self {
+        return Self {
+            inner: reqwest::Client::new(),
+        };
+    }
+}
+
+#[async_trait::async_trait]
+impl HttpClient for ReqwestClient {
+    async fn get(
+        &self,
+        url: String,
+    ) -> Result<HttpResponse, Box<dyn std::error::Error + Send + Sync>> {
+        let res = self
+            .inner
+            .get(url)
+            .header(
+                reqwest::header::ACCEPT,
+                "application/did+json, application/json",
+            )
+            .send()
+            .await?;
+
+        return Ok(HttpResponse {
+            status: res.status().as_u16(),
+            body: res.bytes().await?.to_vec(),
+        });
+    }
+}
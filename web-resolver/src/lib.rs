@@ -0,0 +1,441 @@
+mod http_client;
+const DID_METHOD: &str = "web";
+pub use http_client::{HttpClient, HttpResponse, ReqwestClient};
+use ssi::error::{ErrorKind, ResolverError};
+use ssi::resolver::{ResolutionOptions, ResolutionResult};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Resolver for the `did:web` method, which hosts DID documents as `did.json` files on web servers.
+#[derive(Clone)]
+pub struct WebResolver {
+    client: Arc<dyn HttpClient + Send + Sync>,
+    output_dir: Option<PathBuf>,
+}
+
+impl Default for WebResolver {
+    fn default() -> Self {
+        return Self::new();
+    }
+}
+
+impl WebResolver {
+    pub fn new() -> Self {
+        return Self::with_client(ReqwestClient::new());
+    }
+
+    pub fn with_client(client: impl HttpClient + Send + Sync + 'static) -> Self {
+        return Self {
+            client: Arc::new(client),
+            output_dir: None,
+        };
+    }
+
+    /// Set the web root that `create` writes DID documents to for static hosting.
+    pub fn with_output_dir(mut self, output_dir: impl Into<PathBuf>) -> Self {
+        self.output_dir = Some(output_dir.into());
+        return self;
+    }
+
+    const fn get_method_helper() -> &'static str {
+        return DID_METHOD;
+    }
+
+    /// The file below the configured output directory that the document of `did` is written to.
+    fn output_file(&self, did: &str) -> Result<PathBuf, ResolverError> {
+        let output_dir = self.output_dir.as_ref().ok_or_else(|| {
+            ResolverError::new("no output directory configured", ErrorKind::Uncategorized)
+        })?;
+        return Ok(output_dir.join(did_to_path(did)?));
+    }
+
+    /// Validate `document` and write it to the output file of `did`.
+    fn write(&self, did: &str, document: ssi::did::DidDocument) -> Result<(), ResolverError> {
+        let file = self.output_file(did)?;
+
+        if document.id != did {
+            return Err(ResolverError::new(
+                format!("document id {} does not match {did}", document.id),
+                ErrorKind::InvalidData,
+            ));
+        }
+        document
+            .validate()
+            .map_err(|e| ResolverError::new(e.to_string(), ErrorKind::InvalidData))?;
+
+        let contents = serde_json::to_vec_pretty(&document.serialize())
+            .map_err(|e| ResolverError::new(e.to_string(), ErrorKind::InvalidData))?;
+
+        if let Some(parent) = file.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| ResolverError::new(e.to_string(), ErrorKind::Uncategorized))?;
+        }
+        std::fs::write(&file, contents)
+            .map_err(|e| ResolverError::new(e.to_string(), ErrorKind::Uncategorized))?;
+
+        Ok(())
+    }
+
+    /// Fetch the `did.json` of `did` and check that it is the document of `did`.
+    async fn fetch(&self, did: &str) -> Result<ssi::did::DidDocument, ResolverError> {
+        let url = did_to_url(did)?;
+        let res = self
+            .client
+            .get(url.clone())
+            .await
+            .map_err(|e| ResolverError::new(e.to_string(), ErrorKind::NetworkFailure))?;
+
+        match res.status {
+            200..=299 => {}
+            404 | 410 => {
+                return Err(ResolverError::new(
+                    format!("{url} not found"),
+                    ErrorKind::DocumentNotFound,
+                ))
+            }
+            status => {
+                return Err(ResolverError::new(
+                    format!("{url} responded with status {status}"),
+                    ErrorKind::NetworkFailure,
+                ))
+            }
+        }
+
+        let document: serde_json::Value = serde_json::from_slice(&res.body)
+            .map_err(|e| ResolverError::new(e.to_string(), ErrorKind::InvalidData))?;
+        let document = ssi::did::DidDocument::deserialize(document)
+            .map_err(|e| ResolverError::new(e.to_string(), ErrorKind::InvalidData))?;
+
+        if document.id != did {
+            return Err(ResolverError::new(
+                format!("document id {} does not match {did}", document.id),
+                ErrorKind::InvalidData,
+            ));
+        }
+
+        Ok(document)
+    }
+}
+
+/// Split a `did:web` identifier into its percent-decoded host and path segments.
+fn parse(did: &str) -> Result<(String, Vec<String>), ResolverError> {
+    let parsed = ssi::did::Did::parse(did)
+        .map_err(|e| ResolverError::new(e.to_string(), ErrorKind::InvalidData))?;
+    if parsed.method() != DID_METHOD {
+        return Err(ResolverError::new(
+            format!("{did} is not a did:{DID_METHOD} identifier"),
+            ErrorKind::InvalidData,
+        ));
+    }
+
+    let mut segments = parsed
+        .method_specific_id()
+        .split(':')
+        .map(ssi::did::percent_decode);
+    let host = segments.next().unwrap_or_default();
+    let path: Vec<String> = segments.collect();
+
+    if !is_valid_host(&host)
+        || path
+            .iter()
+            .any(|s| s.is_empty() || s == "." || s == ".." || s.contains('/'))
+    {
+        return Err(ResolverError::new(
+            format!("{did} does not describe a valid web location"),
+            ErrorKind::InvalidData,
+        ));
+    }
+
+    return Ok((host, path));
+}
+
+/// Whether the decoded `host` is a domain name or IPv4 address, optionally followed by a port.
+/// Anything else, such as userinfo, a query or a fragment smuggled in through percent-encoding, could make the URL of
+/// the DID document point at another origin.
+fn is_valid_host(host: &str) -> bool {
+    let (name, port) = match host.split_once(':') {
+        Some((name, port)) => (name, Some(port)),
+        None => (host, None),
+    };
+    let valid_name = !name.is_empty()
+        && name.len() <= 253
+        && name.split('.').all(|label| {
+            !label.is_empty()
+                && label.len() <= 63
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        });
+    let valid_port = match port {
+        Some(port) => {
+            port.chars().all(|c| c.is_ascii_digit())
+                && matches!(port.parse::<u16>(), Ok(p) if p != 0)
+        }
+        None => true,
+    };
+    return valid_name && valid_port;
+}
+
+/// Transform a `did:web` identifier into the URL of its DID document.
+/// `did:web:example.com` maps to `https://example.com/.well-known/did.json` and
+/// `did:web:example.com%3A3000:user:alice` maps to `https://example.com:3000/user/alice/did.json`.
+pub fn did_to_url(did: &str) -> Result<String, ResolverError> {
+    let (host, path) = parse(did)?;
+    return match path.is_empty() {
+        true => Ok(format!("https://{host}/.well-known/did.json")),
+        false => Ok(format!("https://{host}/{}/did.json", path.join("/"))),
+    };
+}
+
+/// The location of the DID document relative to the web root of its host.
+fn did_to_path(did: &str) -> Result<PathBuf, ResolverError> {
+    let (_, path) = parse(did)?;
+    let mut file = PathBuf::new();
+    match path.is_empty() {
+        true => file.push(".well-known"),
+        false => path.iter().for_each(|segment| file.push(segment)),
+    }
+    file.push("did.json");
+    return Ok(file);
+}
+
+#[async_trait::async_trait]
+impl ssi::DIDResolver for WebResolver {
+    fn get_method(&self) -> &'static str {
+        return Self::get_method_helper();
+    }
+
+    /// Write the document below the configured output directory so that it can be served as a static file.
+    async fn create(
+        &self,
+        did: &str,
+        document: ssi::did::DidDocument,
+    ) -> Result<(), ResolverError> {
+        self.write(did, document)
+    }
+
+    /// Overwrite the previously created document of `did`.
+    async fn update(
+        &self,
+        did: &str,
+        document: ssi::did::DidDocument,
+    ) -> Result<(), ResolverError> {
+        if !self.output_file(did)?.is_file() {
+            return Err(ResolverError::new(
+                format!("{did} has not been created"),
+                ErrorKind::DocumentNotFound,
+            ));
+        }
+        return self.write(did, document);
+    }
+
+    /// Remove the document of `did`, `did:web` DIDs are deactivated by no longer serving their `did.json`.
+    async fn deactivate(&self, did: &str) -> Result<(), ResolverError> {
+        let file = self.output_file(did)?;
+        return std::fs::remove_file(&file).map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => ResolverError::new(
+                format!("{did} has not been created"),
+                ErrorKind::DocumentNotFound,
+            ),
+            _ => ResolverError::new(e.to_string(), ErrorKind::Uncategorized),
+        });
+    }
+
+    async fn resolve(&self, did: &str, options: &ResolutionOptions) -> ResolutionResult {
+        ResolutionResult::from_result(self.fetch(did).await, options)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ssi::DIDResolver;
+
+    use crate::{
+        did_to_url,
+        http_client::{HttpResponse, MockHttpClient},
+        WebResolver,
+    };
+
+    macro_rules! aw {
+        ($e:expr) => {
+            tokio_test::block_on($e)
+        };
+    }
+
+    fn create_did_doc(did: &str) -> serde_json::Value {
+        return serde_json::json!({
+            "@context": ["https://www.w3.org/ns/did/v1", "https://w3id.org/security/suites/ed25519-2020/v1"],
+            "id": did,
+            "verificationMethod": [{
+                "id": format!("{did}#key-1"),
+                "type": "Ed25519VerificationKey2020",
+                "controller": did,
+                "publicKeyMultibase": "z6MkfFmsob7fC3MmqU1JVfdBnMbnAw7xm1mrEtPvAoojLcRh"
+            }],
+            "assertionMethod": [format!("{did}#key-1")]
+        });
+    }
+
+    #[rstest::rstest]
+    #[case::domain(
+        "did:web:w3c-ccg.github.io",
+        Some("https://w3c-ccg.github.io/.well-known/did.json")
+    )]
+    #[case::path(
+        "did:web:w3c-ccg.github.io:user:alice",
+        Some("https://w3c-ccg.github.io/user/alice/did.json")
+    )]
+    #[case::port(
+        "did:web:example.com%3A3000:user:alice",
+        Some("https://example.com:3000/user/alice/did.json")
+    )]
+    #[case::encoded_path(
+        "did:web:example.com:user%20name",
+        Some("https://example.com/user name/did.json")
+    )]
+    #[case::invalid_port("did:web:example.com%3Ahttp", None)]
+    #[case::traversal("did:web:example.com:..:secrets", None)]
+    #[case::empty_segment("did:web:example.com::alice", None)]
+    #[case::encoded_slash("did:web:example.com%2Fevil", None)]
+    #[case::encoded_userinfo("did:web:evil.com%40good.com", None)]
+    #[case::encoded_query("did:web:good.com%3Fevil.com", None)]
+    #[case::encoded_fragment("did:web:good.com%23evil.com", None)]
+    #[case::signed_port("did:web:example.com%3A+443", None)]
+    #[case::port_zero("did:web:example.com%3A0", None)]
+    #[case::second_port("did:web:example.com%3A443%3A444", None)]
+    #[case::invalid_label("did:web:-example.com", None)]
+    #[case::wrong_method("did:knox:example.com", None)]
+    fn test_did_to_url(#[case] did: &str, #[case] expect_url: Option<&str>) {
+        let res = did_to_url(did);
+        match expect_url {
+            Some(url) => assert_eq!(res.unwrap(), url),
+            None => assert_eq!(res.unwrap_err().kind, ssi::error::ErrorKind::InvalidData),
+        }
+    }
+
+    #[rstest::rstest]
+    #[case::success(
+        "did:web:example.com",
+        Some(Ok(HttpResponse { status: 200, body: create_did_doc("did:web:example.com").to_string().into_bytes() })),
+        None
+    )]
+    #[case::network_failure(
+        "did:web:example.com",
+        Some(Err("connection refused".into())),
+        Some(ssi::error::ErrorKind::NetworkFailure)
+    )]
+    #[case::not_found(
+        "did:web:example.com",
+        Some(Ok(HttpResponse { status: 404, body: vec![] })),
+        Some(ssi::error::ErrorKind::DocumentNotFound)
+    )]
+    #[case::server_error(
+        "did:web:example.com",
+        Some(Ok(HttpResponse { status: 500, body: vec![] })),
+        Some(ssi::error::ErrorKind::NetworkFailure)
+    )]
+    #[case::invalid_json(
+        "did:web:example.com",
+        Some(Ok(HttpResponse { status: 200, body: b"<html></html>".to_vec() })),
+        Some(ssi::error::ErrorKind::InvalidData)
+    )]
+    #[case::id_mismatch(
+        "did:web:example.com",
+        Some(Ok(HttpResponse { status: 200, body: create_did_doc("did:web:evil.example").to_string().into_bytes() })),
+        Some(ssi::error::ErrorKind::InvalidData)
+    )]
+    #[case::invalid_did("did:web:", None, Some(ssi::error::ErrorKind::InvalidData))]
+    fn test_read(
+        #[case] did: &str,
+        #[case] mock_get_response: Option<
+            Result<HttpResponse, Box<dyn std::error::Error + Send + Sync>>,
+        >,
+        #[case] expect_error_kind: Option<ssi::error::ErrorKind>,
+    ) {
+        let mut mock_client = MockHttpClient::default();
+        if mock_get_response.is_some() {
+            mock_client
+                .expect_get()
+                .with(mockall::predicate::eq(did_to_url(did).unwrap()))
+                .return_once(|_| mock_get_response.unwrap());
+        }
+
+        let resolver = WebResolver::with_client(mock_client);
+        let res = aw!(resolver.read(did));
+        match res {
+            Ok(doc) => {
+                assert!(expect_error_kind.is_none());
+                assert_eq!(doc.id, did);
+            }
+            Err(e) => assert_eq!(Some(e.kind), expect_error_kind),
+        }
+    }
+
+    #[rstest::rstest]
+    #[case::domain("did:web:example.com", ".well-known/did.json")]
+    #[case::path("did:web:example.com%3A3000:user:alice", "user/alice/did.json")]
+    fn test_create(#[case] did: &str, #[case] expect_path: &str) {
+        let output_dir = tempfile::tempdir().unwrap();
+        let doc = ssi::did::DidDocument::deserialize(create_did_doc(did)).unwrap();
+
+        let resolver =
+            WebResolver::with_client(MockHttpClient::default()).with_output_dir(output_dir.path());
+        assert!(aw!(resolver.create(did, doc.clone())).is_ok());
+
+        let written = std::fs::read(output_dir.path().join(expect_path)).unwrap();
+        let written: serde_json::Value = serde_json::from_slice(&written).unwrap();
+        assert_eq!(written, doc.serialize());
+    }
+
+    #[test]
+    fn test_create_failure() {
+        let did = "did:web:example.com";
+        let doc = ssi::did::DidDocument::deserialize(create_did_doc(did)).unwrap();
+
+        let resolver = WebResolver::with_client(MockHttpClient::default());
+        let res = aw!(resolver.create(did, doc.clone()));
+        assert_eq!(res.unwrap_err().kind, ssi::error::ErrorKind::Uncategorized);
+
+        let output_dir = tempfile::tempdir().unwrap();
+        let resolver =
+            WebResolver::with_client(MockHttpClient::default()).with_output_dir(output_dir.path());
+        let res = aw!(resolver.create("did:web:other.example", doc));
+        assert_eq!(res.unwrap_err().kind, ssi::error::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_update_deactivate() {
+        let did = "did:web:example.com";
+        let output_dir = tempfile::tempdir().unwrap();
+        let resolver =
+            WebResolver::with_client(MockHttpClient::default()).with_output_dir(output_dir.path());
+        let file = output_dir.path().join(".well-known/did.json");
+        let mut doc = ssi::did::DidDocument::deserialize(create_did_doc(did)).unwrap();
+
+        let res = aw!(resolver.update(did, doc.clone()));
+        assert_eq!(
+            res.unwrap_err().kind,
+            ssi::error::ErrorKind::DocumentNotFound
+        );
+
+        assert!(aw!(resolver.create(did, doc.clone())).is_ok());
+        doc.also_known_as = vec!["https://example.org".to_string()];
+        assert!(aw!(resolver.update(did, doc.clone())).is_ok());
+        let written: serde_json::Value =
+            serde_json::from_slice(&std::fs::read(&file).unwrap()).unwrap();
+        assert_eq!(written, doc.serialize());
+
+        assert!(aw!(resolver.deactivate(did)).is_ok());
+        assert!(!file.exists());
+        let res = aw!(resolver.deactivate(did));
+        assert_eq!(
+            res.unwrap_err().kind,
+            ssi::error::ErrorKind::DocumentNotFound
+        );
+    }
+
+    #[test]
+    fn test_get_method() {
+        assert_eq!(WebResolver::new().get_method(), "web");
+    }
+}